@@ -40,141 +40,684 @@
 //!
 //! A significant chunk of [syn](syn) types are supported. See
 //! [`CallbackType`](CallbackType) for a list of supported [syn](syn) types.
+//!
+//! With only the default callbacks registered, `Moisture` reproduces its input:
+//! the output parses to the same [syn](syn) tree as the input did. The only
+//! difference allowed is the spacing of punctuation inside of syntax
+//! [syn](syn) keeps as verbatim tokens, such as closures with a `for<'a>`
+//! binder, where the spacing has no meaning. This is checked by the round-trip
+//! test suite in `tests/round_trip.rs`, which runs over the files in
+//! `tests/corpus`, including source files vendored from popular crates, and,
+//! as an ignored test, any directory of Rust files named by the
+//! `MOISTURE_CORPUS` environment variable.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::rc::Rc;
+
+extern crate proc_macro;
+
+use proc_macro2::{Delimiter, Group, Punct, Spacing, Span, TokenStream, TokenTree};
+use quote::{quote, ToTokens};
+use syn::{ext::IdentExt, parse::Parser, spanned::Spanned, *};
 
-use std::collections::HashMap;
+/// Trait for [syn](syn) enums whose variants are dispatched to their own
+/// [`CallbackType`](CallbackType).
+trait Dispatch {
+    /// Get the [`CallbackType`](CallbackType) of the variant, if it has one.
+    fn callback_type(&self) -> Option<CallbackType>;
+}
 
-use proc_macro2::{Span, TokenStream};
-use quote::{quote, ToTokens};
-use syn::{parse::Parser, spanned::Spanned, *};
+/// Define [`CallbackType`](CallbackType) along with everything derived from the
+/// node table: the schema, the default callbacks registered by
+/// [`Moisture::new`](Moisture::new) and the dispatch of [syn](syn) enums.
+///
+/// The table is a series of `nodes { ... }` blocks of standalone nodes and
+/// dispatching nodes followed by the kinds they dispatch to. Each node is given
+/// as `Name => default_callback, syn_type, [children]`, and each kind of a
+/// dispatching node also has the variant of its [syn](syn) enum, if there is
+/// one. Standalone and dispatching nodes with a [`Node`](Node) type end with
+/// `node Type(parser)`, while the node of a kind is its variant parsed out of
+/// the node of its dispatching node.
+macro_rules! callback_types {
+    (@munch [$($nodes:tt)*] [$($groups:tt)*]
+     nodes { $($body:tt)* } $($rest:tt)*) => {
+        callback_types!(@munch [$($nodes)* $($body)*] [$($groups)*] $($rest)*);
+    };
+    (@munch [$($nodes:tt)*] [$($groups:tt)*]
+     $(#[$meta:meta])*
+     $category:ident : $syn_enum:ident $(($verbatim_enum:ident :: $verbatim:ident))?
+         => $handler:ident, $syn:expr, [$($child:ident),*], node $category_node:ident($category_parser:expr) {
+         $($(#[$kind_meta:meta])*
+           $kind:ident $(($kind_enum:ident :: $variant:ident))? => $kind_handler:ident, $kind_syn:expr, [$($kind_child:ident),*];)*
+     }
+     $($rest:tt)*) => {
+        callback_types!(
+            @munch
+            [$($nodes)*
+             $(#[$meta])* $category => $handler, $syn, [$($child,)* $($kind),*],
+                 node $category_node($category_parser);
+             $($(#[$kind_meta])* $kind => $kind_handler, $kind_syn, [$($kind_child),*];)*]
+            [$($groups)*
+             $category $syn_enum [$(($verbatim_enum::$verbatim))?] ($category_parser) {
+                 $($kind $(($kind_enum::$variant))?)*
+             }]
+            $($rest)*
+        );
+    };
+    (@munch [$($(#[$meta:meta])* $variant:ident => $handler:ident, $syn:expr, [$($child:ident),*]
+               $(, node $node:ident($parser:expr $(=> $node_enum:ident :: $node_variant:ident)?))?;)*]
+     [$($category:ident $syn_enum:ident [$(($verbatim_enum:ident :: $verbatim:ident))?] ($category_parser:expr) {
+         $($kind:ident $(($kind_enum:ident :: $kind_variant:ident))?)*
+     })*]) => {
+        #[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+        /// The type of callback to register with the [`Moisture`](Moisture) structure.
+        ///
+        /// The enum variants map 1:1 to [syn](syn) types with the exception of
+        /// [`CallbackType::Stmts`](CallbackType::Stmts), which is a convenience handler
+        /// for a series of statements, and
+        /// [`CallbackType::QSelf`](CallbackType::QSelf), which receives an entire
+        /// qualified path.
+        ///
+        /// The structure of the tree is available at runtime: see
+        /// [`CallbackType::ALL`](CallbackType::ALL),
+        /// [`CallbackType::children`](CallbackType::children) and
+        /// [`CallbackType::category`](CallbackType::category).
+        pub enum CallbackType {
+            $($(#[$meta])* $variant,)*
+
+            /// A user-defined node kind, such as the body of a macro holding a small
+            /// domain-specific language. Custom kinds are registered with
+            /// [`Moisture::register_custom`](Moisture::register_custom) and the
+            /// bodies of macros are given to them with
+            /// [`Moisture::route_macro`](Moisture::route_macro).
+            Custom(&'static str),
+        }
+        impl CallbackType {
+            /// Every built-in callback type, in declaration order. Custom node kinds
+            /// aren't included.
+            pub const ALL: &'static [CallbackType] = &[$(CallbackType::$variant),*];
+
+            /// Get the name of the callback type, such as `"ItemConst"`. Custom node
+            /// kinds return the name they were registered with.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(CallbackType::$variant => stringify!($variant),)*
+                    CallbackType::Custom(name) => name,
+                }
+            }
+            /// Get the built-in callback type named *name*, such as `"ItemConst"`.
+            pub fn from_name(name: &str) -> Option<Self> {
+                match name {
+                    $(stringify!($variant) => Some(CallbackType::$variant),)*
+                    _ => None,
+                }
+            }
+            /// Get the path of the [syn](syn) type the callback type corresponds to,
+            /// such as `"syn::ItemConst"`. This is `None` for nodes which [syn](syn)
+            /// doesn't have a type for, such as
+            /// [`CallbackType::LetChain`](CallbackType::LetChain).
+            pub fn syn_type(&self) -> Option<&'static str> {
+                match self {
+                    $(CallbackType::$variant => $syn,)*
+                    CallbackType::Custom(_) => None,
+                }
+            }
+            /// Get the callback types the default callback of this type can issue
+            /// callbacks to.
+            pub fn children(&self) -> &'static [CallbackType] {
+                match self {
+                    $(CallbackType::$variant => &[$(CallbackType::$child),*],)*
+                    CallbackType::Custom(_) => &[],
+                }
+            }
+            /// Get the dispatching callback type this type is a kind of, such as
+            /// [`CallbackType::Expr`](CallbackType::Expr) for
+            /// [`CallbackType::ExprCall`](CallbackType::ExprCall).
+            pub fn category(&self) -> Option<CallbackType> {
+                match self {
+                    $($(CallbackType::$kind => Some(CallbackType::$category),)*)*
+                    _ => None,
+                }
+            }
+        }
+        impl Moisture {
+            fn load_defaults(&mut self) {
+                $(self.callbacks.insert(CallbackType::$variant, Moisture::$handler);)*
+            }
+            fn default_callback(&self, ty: CallbackType) -> Option<Callback> {
+                match ty {
+                    $(CallbackType::$variant => Some(Moisture::$handler as Callback),)*
+                    CallbackType::Custom(name) => self.custom_defaults.get(name).copied(),
+                }
+            }
+        }
+        $(
+            impl Dispatch for $syn_enum {
+                fn callback_type(&self) -> Option<CallbackType> {
+                    match self {
+                        $($($kind_enum::$kind_variant(_) => Some(CallbackType::$kind),)?)*
+                        $($verbatim_enum::$verbatim(_) => Some(CallbackType::Verbatim),)?
+                        _ => None,
+                    }
+                }
+            }
+        )*
+        impl_node! {
+            $($($node => $variant, $parser $(=> $node_enum::$node_variant)?;)?)*
+            $($($($kind => $kind, $category_parser => $kind_enum::$kind_variant;)?)*)*
+        }
+    };
+    ($($input:tt)*) => {
+        callback_types!(@munch [] [] $($input)*);
+    };
+}
+
+/// Implement [`Node`](Node) for the node types of the
+/// [`callback_types!`](callback_types) table, along with `parse_node_of`.
+macro_rules! impl_node {
+    ($($ty:ident => $callback:ident, $parser:expr $(=> $enum:ident :: $variant:ident)?;)*) => {
+        $(
+            impl Node for $ty {
+                const CALLBACK_TYPE: CallbackType = CallbackType::$callback;
+
+                fn parse_node(tokens: TokenStream) -> Result<Self> {
+                    impl_node!(@parse $ty, tokens, $parser $(=> $enum::$variant)?)
+                }
+            }
+        )*
+
+        /// Parse *tokens* as the [`Node`](Node) of the given callback type.
+        ///
+        /// Returns `None` for callback types without a node.
+        fn parse_node_of(ty: CallbackType, tokens: TokenStream) -> Option<Result<()>> {
+            match ty {
+                $(CallbackType::$callback => Some(<$ty as Node>::parse_node(tokens).map(|_| ())),)*
+                _ => None,
+            }
+        }
+    };
+    (@parse $ty:ident, $tokens:ident, $parser:expr) => {
+        $parser($tokens)
+    };
+    (@parse $ty:ident, $tokens:ident, $parser:expr => $enum:ident :: $variant:ident) => {
+        match $parser($tokens)? {
+            $enum::$variant(node) => Ok(node),
+            other => Err(Error::new(
+                other.span(),
+                concat!("expected ", stringify!($ty), " object"),
+            )),
+        }
+    };
+}
+
+callback_types! {
+    nodes {
+        File => file, Some("syn::File"), [Attribute, Item], node File(parse2::<File>);
+        /// The input of a derive macro, which is dispatched as an
+        /// [`CallbackType::Item`](CallbackType::Item). See
+        /// [`Moisture::derive`](Moisture::derive).
+        DeriveInput => derive_input, Some("syn::DeriveInput"), [Item], node DeriveInput(parse2::<DeriveInput>);
+    }
+
+    Item: Item(Item::Verbatim) => item, Some("syn::Item"), [Verbatim], node Item(parse2::<Item>) {
+        ItemConst(Item::Const) => item_const, Some("syn::ItemConst"), [Attribute, Visibility, Ident, Type, Expr];
+        ItemEnum(Item::Enum) => item_enum, Some("syn::ItemEnum"), [Attribute, Visibility, Ident, Generics, WhereClause, Variant];
+        ItemExternCrate(Item::ExternCrate) => item_extern_crate, Some("syn::ItemExternCrate"), [Attribute, Visibility, Ident];
+        ItemFn(Item::Fn) => item_fn, Some("syn::ItemFn"), [Attribute, Visibility, Signature, Block];
+        ItemForeignMod(Item::ForeignMod) => item_foreign_mod, Some("syn::ItemForeignMod"), [Attribute, ForeignItem];
+        ItemImpl(Item::Impl) => item_impl, Some("syn::ItemImpl"), [Attribute, Generics, Path, Type, Verbatim, WhereClause, ImplItem];
+        ItemMacro(Item::Macro) => item_macro, Some("syn::ItemMacro"), [Attribute, Path, Ident, Macro];
+        ItemMacro2(Item::Macro2) => item_macro2, Some("syn::ItemMacro2"), [];
+        ItemMod(Item::Mod) => item_mod, Some("syn::ItemMod"), [Attribute, Visibility, Ident, Item];
+        ItemStatic(Item::Static) => item_static, Some("syn::ItemStatic"), [Attribute, Visibility, Ident, Type, Expr];
+        ItemStruct(Item::Struct) => item_struct, Some("syn::ItemStruct"), [Attribute, Visibility, Ident, Generics, Fields, WhereClause];
+        ItemTrait(Item::Trait) => item_trait, Some("syn::ItemTrait"), [Attribute, Visibility, Ident, Generics, TypeParamBound, WhereClause, TraitItem];
+        ItemTraitAlias(Item::TraitAlias) => item_trait_alias, Some("syn::ItemTraitAlias"), [Attribute, Visibility, Ident, Generics, TypeParamBound, WhereClause];
+        ItemType(Item::Type) => item_type, Some("syn::ItemType"), [Attribute, Visibility, Ident, Generics, WhereClause, Type];
+        ItemUnion(Item::Union) => item_union, Some("syn::ItemUnion"), [Attribute, Visibility, Ident, Generics, WhereClause, Fields];
+        ItemUse(Item::Use) => item_use, Some("syn::ItemUse"), [Attribute, Visibility, Path, Ident];
+    }
+
+    nodes {
+        Variant => variant, Some("syn::Variant"), [Attribute, Ident, Fields, Expr], node Variant(parse2::<Variant>);
+    }
+
+    ForeignItem: ForeignItem(ForeignItem::Verbatim) => foreign_item, Some("syn::ForeignItem"), [Verbatim], node ForeignItem(parse2::<ForeignItem>) {
+        ForeignItemFn(ForeignItem::Fn) => foreign_item_fn, Some("syn::ForeignItemFn"), [Attribute, Visibility, Signature];
+        ForeignItemStatic(ForeignItem::Static) => foreign_item_static, Some("syn::ForeignItemStatic"), [Attribute, Visibility, Ident, Type];
+        ForeignItemType(ForeignItem::Type) => foreign_item_type, Some("syn::ForeignItemType"), [Attribute, Visibility, Ident];
+        ForeignItemMacro(ForeignItem::Macro) => foreign_item_macro, Some("syn::ForeignItemMacro"), [Attribute, Macro];
+    }
+
+    ImplItem: ImplItem(ImplItem::Verbatim) => impl_item, Some("syn::ImplItem"), [Verbatim], node ImplItem(parse2::<ImplItem>) {
+        ImplItemConst(ImplItem::Const) => impl_item_const, Some("syn::ImplItemConst"), [Attribute, Visibility, Ident, Type, Expr];
+        ImplItemMethod(ImplItem::Method) => impl_item_method, Some("syn::ImplItemMethod"), [Attribute, Visibility, Signature, Block];
+        ImplItemType(ImplItem::Type) => impl_item_type, Some("syn::ImplItemType"), [Attribute, Visibility, Ident, Generics, WhereClause, Type];
+        ImplItemMacro(ImplItem::Macro) => impl_item_macro, Some("syn::ImplItemMacro"), [Attribute, Macro];
+    }
+
+    TraitItem: TraitItem(TraitItem::Verbatim) => trait_item, Some("syn::TraitItem"), [Verbatim], node TraitItem(parse2::<TraitItem>) {
+        TraitItemConst(TraitItem::Const) => trait_item_const, Some("syn::TraitItemConst"), [Attribute, Ident, Type, Expr];
+        TraitItemMethod(TraitItem::Method) => trait_item_method, Some("syn::TraitItemMethod"), [Attribute, Signature, Block];
+        TraitItemType(TraitItem::Type) => trait_item_type, Some("syn::TraitItemType"), [Attribute, Ident, Generics, TypeParamBound, WhereClause, Type];
+        TraitItemMacro(TraitItem::Macro) => trait_item_macro, Some("syn::TraitItemMacro"), [Attribute, Macro];
+    }
+
+    nodes {
+        Block => block, Some("syn::Block"), [Stmts], node Block(parse2::<Block>);
+
+        /// A series of statements. Essentially parses `Vec<syn::Stmt>`.
+        Stmts => stmts, Some("Vec<syn::Stmt>"), [Stmt];
+
+        Stmt => stmt, Some("syn::Stmt"), [Local, Item, Expr], node Stmt(get_stmt);
+
+        Local => local, Some("syn::Local"), [Attribute, Pat, PatType, Expr, LocalElse], node Local(get_stmt => Stmt::Local);
+        /// The diverging `else` block of a let-else statement, such as
+        /// `else { return }` in `let Some(x) = opt else { return };`. This is only
+        /// given when the statement has an `else` block.
+        LocalElse => local_else, None, [Block];
+    }
+
+    Pat: Pat(Pat::Verbatim) => pat, Some("syn::Pat"), [Verbatim], node Pat(get_pat) {
+        PatBox(Pat::Box) => pat_box, Some("syn::PatBox"), [Attribute, Pat];
+        PatIdent(Pat::Ident) => pat_ident, Some("syn::PatIdent"), [Attribute, Ident, PatIdentSubpat];
+        PatLit(Pat::Lit) => pat_lit, Some("syn::PatLit"), [Attribute, Expr];
+        PatMacro(Pat::Macro) => pat_macro, Some("syn::PatMacro"), [Attribute, Macro];
+        PatOr(Pat::Or) => pat_or, Some("syn::PatOr"), [Attribute, Pat];
+        PatPath(Pat::Path) => pat_path, Some("syn::PatPath"), [Attribute, Path, QSelf];
+        PatRange(Pat::Range) => pat_range, Some("syn::PatRange"), [Attribute, Expr];
+        PatReference(Pat::Reference) => pat_reference, Some("syn::PatReference"), [Attribute, Pat];
+        PatRest(Pat::Rest) => pat_rest, Some("syn::PatRest"), [];
+        PatSlice(Pat::Slice) => pat_slice, Some("syn::PatSlice"), [Attribute, Pat];
+        PatStruct(Pat::Struct) => pat_struct, Some("syn::PatStruct"), [Attribute, Path, FieldPat];
+        PatTuple(Pat::Tuple) => pat_tuple, Some("syn::PatTuple"), [Attribute, Pat];
+        PatTupleStruct(Pat::TupleStruct) => pat_tuple_struct, Some("syn::PatTupleStruct"), [Attribute, Path, PatTuple];
+        PatType(Pat::Type) => pat_type, Some("syn::PatType"), [Attribute, Pat, Type];
+        PatWild(Pat::Wild) => pat_wild, Some("syn::PatWild"), [];
+    }
+
+    nodes {
+        /// A single field of a struct pattern, such as `x: ref y` in
+        /// `Point { x: ref y, .. }`. Returning an empty stream removes the field.
+        FieldPat => field_pat, Some("syn::FieldPat"), [Attribute, Ident, Pat], node FieldPat(get_field_pat);
+        /// The `@ subpattern` part of an identifier pattern, such as `@ 1..=5` in
+        /// `n @ 1..=5`. This is given as an empty stream when the identifier has no
+        /// subpattern, and returning an empty stream removes the subpattern.
+        PatIdentSubpat => pat_ident_subpat, None, [Pat];
+    }
+
+    Expr: Expr => expr, Some("syn::Expr"), [Verbatim], node Expr(parse2::<Expr>) {
+        ExprArray(Expr::Array) => expr_array, Some("syn::ExprArray"), [Attribute, Expr];
+        ExprAssign(Expr::Assign) => expr_assign, Some("syn::ExprAssign"), [Expr];
+        ExprAssignOp(Expr::AssignOp) => expr_assign_op, Some("syn::ExprAssignOp"), [Expr, BinOp];
+        ExprAsync(Expr::Async) => expr_async, Some("syn::ExprAsync"), [Attribute, Block];
+        ExprAwait(Expr::Await) => expr_await, Some("syn::ExprAwait"), [Attribute, Expr];
+        ExprBinary(Expr::Binary) => expr_binary, Some("syn::ExprBinary"), [Expr, BinOp];
+        ExprBlock(Expr::Block) => expr_block, Some("syn::ExprBlock"), [Attribute, Label, Block];
+        ExprBox(Expr::Box) => expr_box, Some("syn::ExprBox"), [Attribute, Expr];
+        ExprBreak(Expr::Break) => expr_break, Some("syn::ExprBreak"), [Attribute, Label, Expr];
+        ExprCall(Expr::Call) => expr_call, Some("syn::ExprCall"), [Attribute, Expr];
+        ExprCast(Expr::Cast) => expr_cast, Some("syn::ExprCast"), [Expr, Type];
+        /// A closure. Closures with a binder, such as `for<'a> |x: &'a u8| *x`,
+        /// are also given to this callback even though [syn](syn) only knows them
+        /// as verbatim tokens.
+        ExprClosure(Expr::Closure) => expr_closure, Some("syn::ExprClosure"), [Attribute, BoundLifetimes, Pat, PatType, Type, Expr];
+        /// An inline const block, such as `const { 1 + 2 }`.
+        ExprConst => expr_const, None, [Block];
+        ExprContinue(Expr::Continue) => expr_continue, Some("syn::ExprContinue"), [Attribute, Label];
+        ExprField(Expr::Field) => expr_field, Some("syn::ExprField"), [Attribute, Expr, Ident];
+        ExprForLoop(Expr::ForLoop) => expr_for_loop, Some("syn::ExprForLoop"), [Attribute, Label, Pat, Expr, Block];
+        ExprGroup(Expr::Group) => expr_group, Some("syn::ExprGroup"), [Attribute, Expr];
+        ExprIf(Expr::If) => expr_if, Some("syn::ExprIf"), [Attribute, Expr, LetChain, Block];
+        ExprIndex(Expr::Index) => expr_index, Some("syn::ExprIndex"), [Attribute, Expr];
+        /// The `_` placeholder in expression position, such as the left side of
+        /// `_ = f();`.
+        ExprInfer => expr_infer, None, [];
+        ExprLet(Expr::Let) => expr_let, Some("syn::ExprLet"), [Pat, Expr];
+        ExprLit(Expr::Lit) => expr_lit, Some("syn::ExprLit"), [Attribute, Lit];
+        ExprLoop(Expr::Loop) => expr_loop, Some("syn::ExprLoop"), [Attribute, Label, Block];
+        ExprMacro(Expr::Macro) => expr_macro, Some("syn::ExprMacro"), [Attribute, Macro];
+        ExprMatch(Expr::Match) => expr_match, Some("syn::ExprMatch"), [Attribute, Expr, Arm];
+        ExprMethodCall(Expr::MethodCall) => expr_method_call, Some("syn::ExprMethodCall"), [Attribute, Expr, Ident, MethodTurbofish];
+        ExprParen(Expr::Paren) => expr_paren, Some("syn::ExprParen"), [Attribute, Expr];
+        ExprPath(Expr::Path) => expr_path, Some("syn::ExprPath"), [Attribute, Path, QSelf];
+        ExprRange(Expr::Range) => expr_range, Some("syn::ExprRange"), [Attribute, Expr];
+        ExprReference(Expr::Reference) => expr_reference, Some("syn::ExprReference"), [Attribute, Expr];
+        ExprRepeat(Expr::Repeat) => expr_repeat, Some("syn::ExprRepeat"), [Attribute, Expr];
+        ExprReturn(Expr::Return) => expr_return, Some("syn::ExprReturn"), [Attribute, Expr];
+        ExprStruct(Expr::Struct) => expr_struct, Some("syn::ExprStruct"), [Attribute, Path, FieldValue, Expr];
+        ExprTry(Expr::Try) => expr_try, Some("syn::ExprTry"), [Attribute, Expr];
+        ExprTryBlock(Expr::TryBlock) => expr_try_block, Some("syn::ExprTryBlock"), [Attribute, Block];
+        ExprTuple(Expr::Tuple) => expr_tuple, Some("syn::ExprTuple"), [Attribute, Expr];
+        ExprType(Expr::Type) => expr_type, Some("syn::ExprType"), [Expr, Type];
+        ExprUnary(Expr::Unary) => expr_unary, Some("syn::ExprUnary"), [Attribute, Expr, UnOp];
+        ExprUnsafe(Expr::Unsafe) => expr_unsafe, Some("syn::ExprUnsafe"), [Attribute, Block];
+        ExprWhile(Expr::While) => expr_while, Some("syn::ExprWhile"), [Attribute, Label, Expr, LetChain, Block];
+        ExprYield(Expr::Yield) => expr_yield, Some("syn::ExprYield"), [Attribute, Expr];
+    }
+
+    nodes {
+        /// The condition of an `if` or `while` expression made of conditions
+        /// joined by `&&`, at least one of which is a `let` expression, such as
+        /// `let Some(x) = a && x > 1`. The conditions of a chain aren't given to
+        /// [`CallbackType::BinOp`](CallbackType::BinOp), because a chain can't be
+        /// turned into another operation.
+        LetChain => let_chain, None, [Expr];
+
+        Arm => arm, Some("syn::Arm"), [Attribute, Pat, Expr], node Arm(parse2::<Arm>);
+
+        /// A binary operator. The callback receives the operator between its
+        /// already-transformed operands, such as `a + b` or `a += b`. Returning a
+        /// lone operator replaces the operator, while anything else replaces the
+        /// entire expression. The operator of a compound assignment can only be
+        /// replaced with another compound assignment operator.
+        BinOp => bin_op, Some("syn::BinOp"), [];
+        /// A unary operator. Like [`CallbackType::BinOp`](CallbackType::BinOp),
+        /// the callback receives the operator along with its transformed operand and
+        /// can return either a new operator or a replacement expression.
+        UnOp => un_op, Some("syn::UnOp"), [];
+    }
+
+    Lit: Lit => lit, Some("syn::Lit"), [], node Lit(parse2::<Lit>) {
+        LitStr(Lit::Str) => lit_str, Some("syn::LitStr"), [];
+        LitByteStr(Lit::ByteStr) => lit_byte_str, Some("syn::LitByteStr"), [];
+        LitByte(Lit::Byte) => lit_byte, Some("syn::LitByte"), [];
+        LitChar(Lit::Char) => lit_char, Some("syn::LitChar"), [];
+        LitInt(Lit::Int) => lit_int, Some("syn::LitInt"), [];
+        LitFloat(Lit::Float) => lit_float, Some("syn::LitFloat"), [];
+        LitBool(Lit::Bool) => lit_bool, Some("syn::LitBool"), [];
+    }
+
+    nodes {
+        FieldValue => field_value, Some("syn::FieldValue"), [Attribute, Ident, Expr], node FieldValue(parse2::<FieldValue>);
+    }
+
+    Type: Type(Type::Verbatim) => type_, Some("syn::Type"), [Verbatim], node Type(parse2::<Type>) {
+        TypeArray(Type::Array) => type_array, Some("syn::TypeArray"), [Type, Expr];
+        TypeBareFn(Type::BareFn) => type_bare_fn, Some("syn::TypeBareFn"), [Attribute, BoundLifetimes, Ident, Type];
+        TypeGroup(Type::Group) => type_group, Some("syn::TypeGroup"), [Type];
+        TypeImplTrait(Type::ImplTrait) => type_impl_trait, Some("syn::TypeImplTrait"), [TypeParamBound];
+        TypeInfer(Type::Infer) => type_infer, Some("syn::TypeInfer"), [];
+        TypeMacro(Type::Macro) => type_macro, Some("syn::TypeMacro"), [Macro];
+        TypeNever(Type::Never) => type_never, Some("syn::TypeNever"), [];
+        TypeParen(Type::Paren) => type_paren, Some("syn::TypeParen"), [Type];
+        TypePath(Type::Path) => type_path, Some("syn::TypePath"), [Path, QSelf];
+        TypePtr(Type::Ptr) => type_ptr, Some("syn::TypePtr"), [Type];
+        TypeReference(Type::Reference) => type_reference, Some("syn::TypeReference"), [Lifetime, Type];
+        TypeSlice(Type::Slice) => type_slice, Some("syn::TypeSlice"), [Type];
+        TypeTraitObject(Type::TraitObject) => type_trait_object, Some("syn::TypeTraitObject"), [TypeParamBound];
+        TypeTuple(Type::Tuple) => type_tuple, Some("syn::TypeTuple"), [Type];
+    }
+
+    nodes {
+        TypeParamBound => type_param_bound, Some("syn::TypeParamBound"), [BoundLifetimes, Lifetime, Path], node TypeParamBound(parse2::<TypeParamBound>);
+
+        Generics => generics, Some("syn::Generics"), [GenericParam], node Generics(parse2::<Generics>);
+        GenericParam => generic_param, Some("syn::GenericParam"), [Attribute, Lifetime, Ident, TypeParamBound, Type, Expr], node GenericParam(parse2::<GenericParam>);
+        WhereClause => where_clause, Some("syn::WhereClause"), [WherePredicate], node WhereClause(parse2::<WhereClause>);
+        WherePredicate => where_predicate, Some("syn::WherePredicate"), [BoundLifetimes, Lifetime, Type, TypeParamBound], node WherePredicate(parse2::<WherePredicate>);
+
+        Signature => signature, Some("syn::Signature"), [Ident, Generics, FnArg, Type, WhereClause], node Signature(parse2::<Signature>);
+        FnArg => fn_arg, Some("syn::FnArg"), [Attribute, Lifetime, PatType], node FnArg(parse2::<FnArg>);
+
+        Fields => fields, Some("syn::Fields"), [Field], node Fields(get_fields);
+        /// A single named or unnamed field. Parsed with
+        /// [`syn::Field::parse_named`](syn::Field::parse_named), falling back to
+        /// [`syn::Field::parse_unnamed`](syn::Field::parse_unnamed).
+        Field => field, Some("syn::Field"), [Attribute, Visibility, Ident, Type], node Field(get_field);
+
+        /// A macro invocation. The body of a macro routed with
+        /// [`Moisture::route_macro`](Moisture::route_macro) is also given to its
+        /// [`CallbackType::Custom`](CallbackType::Custom) node kind.
+        Macro => macro_, Some("syn::Macro"), [Path], node Macro(parse2::<Macro>);
+
+        /// A path without a qualified self type, such as `std::vec::Vec<T>`.
+        Path => path, Some("syn::Path"), [PathSegment], node Path(get_path);
+        PathSegment => path_segment, Some("syn::PathSegment"), [Ident, GenericArgument, Type], node PathSegment(get_path_segment);
+        /// A qualified path, such as `<Vec<T> as IntoIterator>::Item`. Because
+        /// [`syn::QSelf`](syn::QSelf) isn't a standalone syntax node, the entire
+        /// path including the qualified self type is given to this callback.
+        QSelf => qself, Some("syn::QSelf"), [Type, Path, PathSegment], node QualifiedPath(get_qualified_path);
+        GenericArgument => generic_argument, Some("syn::GenericArgument"), [Lifetime, Type, Expr, Ident, TypeParamBound], node GenericArgument(parse2::<GenericArgument>);
+        MethodTurbofish => method_turbofish, Some("syn::MethodTurbofish"), [GenericArgument], node MethodTurbofish(parse2::<MethodTurbofish>);
+
+        /// Any outer or inner attribute in the tree, such as the helper attribute
+        /// `#[my_derive(skip)]` or `#![allow(unused)]`. The node the attribute
+        /// belongs to is the previous entry on the [`Context`](Context) stack.
+        /// Returning an empty stream removes the attribute. [syn](syn) gives the
+        /// attributes of binary, assignment and cast expressions to their left
+        /// operand, so `#[a] x + 1` issues this for `x`.
+        Attribute => attribute, Some("syn::Attribute"), [Path], node Attribute(get_attribute);
+
+        /// The visibility of an item, impl item, foreign item or field, including
+        /// inherited visibility, which is given as an empty stream. The kind of
+        /// node the visibility belongs to is the previous entry on the
+        /// [`Context`](Context) stack.
+        Visibility => visibility, Some("syn::Visibility"), [Path], node Visibility(parse2::<Visibility>);
+
+        /// Any identifier in the tree. The role the identifier plays is available
+        /// through [`Context::ident_role`](Context::ident_role).
+        Ident => ident, Some("syn::Ident"), [], node Ident(|tokens| Ident::parse_any.parse2(tokens));
+        /// A lifetime, such as `'a` in `&'a str`, wherever it's declared or used.
+        Lifetime => lifetime, Some("syn::Lifetime"), [], node Lifetime(parse2::<Lifetime>);
+        /// The name of a label, such as `'outer`, without the colon of its
+        /// definition. This is given for both label definitions and the labels of
+        /// `break` and `continue` expressions. A [`syn::Label`](syn::Label) given
+        /// to [`Moisture::transform`](Moisture::transform) keeps its colon.
+        Label => label, Some("syn::Label"), [Ident], node Label(get_label);
+        /// A higher-ranked lifetime binder, such as `for<'a>` in
+        /// `for<'a> Fn(&'a u8)` or in a closure binder.
+        BoundLifetimes => bound_lifetimes, Some("syn::BoundLifetimes"), [Attribute, Lifetime], node BoundLifetimes(parse2::<BoundLifetimes>);
+
+        Verbatim => verbatim, Some("proc_macro2::TokenStream"), [];
+    }
+}
+
+impl CallbackType {
+    /// Get every parent-to-child edge of the node schema, as given by
+    /// [`CallbackType::children`](CallbackType::children).
+    pub fn edges() -> Vec<(CallbackType, CallbackType)> {
+        CallbackType::ALL
+            .iter()
+            .flat_map(|parent| parent.children().iter().map(move |child| (*parent, *child)))
+            .collect()
+    }
+    /// Check whether this is [`CallbackType::Item`](CallbackType::Item) or one
+    /// of its kinds.
+    pub fn is_item(&self) -> bool {
+        self.is_kind_of(CallbackType::Item)
+    }
+    /// Check whether this is
+    /// [`CallbackType::ForeignItem`](CallbackType::ForeignItem) or one of its
+    /// kinds.
+    pub fn is_foreign_item(&self) -> bool {
+        self.is_kind_of(CallbackType::ForeignItem)
+    }
+    /// Check whether this is [`CallbackType::ImplItem`](CallbackType::ImplItem)
+    /// or one of its kinds.
+    pub fn is_impl_item(&self) -> bool {
+        self.is_kind_of(CallbackType::ImplItem)
+    }
+    /// Check whether this is
+    /// [`CallbackType::TraitItem`](CallbackType::TraitItem) or one of its kinds.
+    pub fn is_trait_item(&self) -> bool {
+        self.is_kind_of(CallbackType::TraitItem)
+    }
+    /// Check whether this is [`CallbackType::Pat`](CallbackType::Pat) or one of
+    /// its kinds.
+    pub fn is_pat(&self) -> bool {
+        self.is_kind_of(CallbackType::Pat)
+    }
+    /// Check whether this is [`CallbackType::Expr`](CallbackType::Expr) or one
+    /// of its kinds.
+    pub fn is_expr(&self) -> bool {
+        self.is_kind_of(CallbackType::Expr)
+    }
+    /// Check whether this is [`CallbackType::Lit`](CallbackType::Lit) or one of
+    /// its kinds.
+    pub fn is_lit(&self) -> bool {
+        self.is_kind_of(CallbackType::Lit)
+    }
+    /// Check whether this is [`CallbackType::Type`](CallbackType::Type) or one
+    /// of its kinds.
+    pub fn is_type(&self) -> bool {
+        self.is_kind_of(CallbackType::Type)
+    }
+    fn is_kind_of(&self, category: CallbackType) -> bool {
+        *self == category || self.category() == Some(category)
+    }
+}
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
-/// The type of callback to register with the [`Moisture`](Moisture) structure.
+/// The role of an identifier given to a
+/// [`CallbackType::Ident`](CallbackType::Ident) callback.
 ///
-/// The enum variants map 1:1 to [syn](syn) types with the exception of
-/// [`CallbackType::Stmts`](CallbackType::Stmts), which is a convenience handler
-/// for a series of statements.
-pub enum CallbackType {
-    File,
-
+/// Roles are purely syntactic: a unit variant such as `None` in a pattern is
+/// indistinguishable from a new binding and is given as
+/// [`IdentRole::Binding`](IdentRole::Binding).
+pub enum IdentRole {
+    /// An identifier bound by a pattern, such as `x` in `let x = 1;`.
+    Binding,
+    /// A segment of a path or use tree, such as `foo` in `foo::bar()`.
+    Path,
+    /// A named field, such as `x` in `point.x` or `Point { x: 1 }`.
+    Member,
+    /// The name of a called method, such as `len` in `vec.len()`.
+    Method,
+    /// The name of a declaration, such as a function, type, variant, constant
+    /// or generic parameter.
     Item,
-    ItemConst,
-    ItemEnum,
-    ItemExternCrate,
-    ItemFn,
-    ItemForeignMod,
-    ItemImpl,
-    ItemMacro,
-    ItemMacro2,
-    ItemMod,
-    ItemStatic,
-    ItemStruct,
-    ItemTrait,
-    ItemTraitAlias,
-    ItemType,
-    ItemUnion,
-    ItemUse,
-
-    Variant,
-
-    ForeignItem,
-    ForeignItemFn,
-    ForeignItemStatic,
-    ForeignItemType,
-    ForeignItemMacro,
-
-    ImplItem,
-    ImplItemConst,
-    ImplItemMethod,
-    ImplItemType,
-    ImplItemMacro,
-
-    TraitItem,
-    TraitItemConst,
-    TraitItemMethod,
-    TraitItemType,
-    TraitItemMacro,
-
-    Block,
-
-    /// A series of statements. Essentially parses `Vec<syn::Stmt>`.
-    Stmts,
-
-    Stmt,
+    /// The name of a label, such as `outer` in `'outer: loop {}`.
+    Label,
+    /// The name of a macro, either invoked or defined with `macro_rules!`.
+    Macro,
+}
 
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+/// The position of a callback's node within the node of its parent callback.
+///
+/// Positions are named after the field of the parent's [syn](syn) node the
+/// child was taken from, such as `ExprIf.cond` for the condition of an `if`
+/// expression or `ExprCall.args[2]` for the third argument of a call. The
+/// expressions of statements are `Stmt.expr`, or `Stmt.semi` when they're
+/// followed by a semicolon.
+pub struct Position {
+    /// The callback the node was given to.
+    pub parent: CallbackType,
+    /// The name of the field of the parent holding the node.
+    pub field: &'static str,
+    /// The index of the node when the field is a list.
+    pub index: Option<usize>,
+}
+impl Position {
+    /// Check whether this is the given field of the given parent, such as
+    /// `position.is(CallbackType::ExprIf, "cond")`.
+    pub fn is(&self, parent: CallbackType, field: &str) -> bool {
+        self.parent == parent && self.field == field
+    }
+}
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.parent.name(), self.field)?;
+
+        if let Some(index) = self.index {
+            write!(f, "[{}]", index)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Where a child node goes within the node of the current callback.
+enum Slot {
+    /// The node isn't part of the current node, such as the root of a
+    /// traversal.
+    None,
+    /// The node is taken from a field, and an index within it for lists.
+    Field(&'static str, Option<usize>),
+    /// The node is the current node, handed to the handler of its kind.
+    Dispatch,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+/// The construct a [`LocalBinding`](LocalBinding) was introduced by.
+pub enum BindingSite {
+    /// A `let` statement, including let-else statements.
     Local,
+    /// A parameter of a function or method, including `self`.
+    Param,
+    /// An input of a closure.
+    ClosureParam,
+    /// The pattern of a `for` loop.
+    ForLoop,
+    /// The condition of an `if let` expression.
+    IfLet,
+    /// The condition of a `while let` loop.
+    WhileLet,
+    /// The pattern of a `match` arm.
+    MatchArm,
+}
+
+#[derive(Clone, Debug)]
+/// A variable bound by a pattern, as found by
+/// [`Context::resolve`](Context::resolve).
+///
+/// Like [`IdentRole`](IdentRole), bindings are purely syntactic: the `None`
+/// in `if let None = x` is recorded as a binding.
+pub struct LocalBinding {
+    /// The name of the variable, with the span it was bound with.
+    pub ident: Ident,
+    /// Whether the variable was bound with `mut`.
+    pub mutable: bool,
+    /// The type the variable was declared with, such as `u8` in
+    /// `let x: u8 = 1;`. Variables within a destructured pattern have no
+    /// declared type.
+    pub ty: Option<Type>,
+    /// The construct the variable was bound by.
+    pub site: BindingSite,
+}
+
+/// A frame of the variables in scope, popping the variables bound since it
+/// was entered when dropped.
+struct ScopeFrame {
+    scope: Rc<RefCell<Vec<LocalBinding>>>,
+    len: usize,
+}
+impl Drop for ScopeFrame {
+    fn drop(&mut self) {
+        self.scope.borrow_mut().truncate(self.len);
+    }
+}
+
+/// The names declared by the items and `use` declarations of a module or
+/// block, with the blocks enclosing it.
+#[derive(Debug)]
+struct Names {
+    paths: HashMap<String, Path>,
+    outer: Option<Rc<Names>>,
+}
 
-    Pat,
-    PatBox,
-    PatIdent,
-    PatLit,
-    PatMacro,
-    PatOr,
-    PatPath,
-    PatRange,
-    PatReference,
-    PatRest,
-    PatSlice,
-    PatStruct,
-    PatTuple,
-    PatTupleStruct,
-    PatType,
-    PatWild,
-
-    Expr,
-    ExprArray,
-    ExprAssign,
-    ExprAssignOp,
-    ExprAsync,
-    ExprAwait,
-    ExprBinary,
-    ExprBlock,
-    ExprBox,
-    ExprBreak,
-    ExprCall,
-    ExprCast,
-    ExprClosure,
-    ExprContinue,
-    ExprField,
-    ExprForLoop,
-    ExprGroup,
-    ExprIf,
-    ExprIndex,
-    ExprLet,
-    ExprLit,
-    ExprLoop,
-    ExprMacro,
-    ExprMatch,
-    ExprMethodCall,
-    ExprParen,
-    ExprPath,
-    ExprRange,
-    ExprReference,
-    ExprRepeat,
-    ExprReturn,
-    ExprStruct,
-    ExprTry,
-    ExprTryBlock,
-    ExprTuple,
-    ExprType,
-    ExprUnary,
-    ExprUnsafe,
-    ExprWhile,
-    ExprYield,
-
-    Arm,
-
-    Lit,
-    LitStr,
-    LitByteStr,
-    LitByte,
-    LitChar,
-    LitInt,
-    LitFloat,
-    LitBool,
-
-    FieldValue,
-
-    Verbatim,
+#[derive(Debug, Default)]
+/// The identifiers taken in a tree, shared by every [`Context`](Context) of a
+/// traversal for generating fresh identifiers.
+struct Symbols {
+    taken: HashSet<String>,
+    next: usize,
+}
+impl Symbols {
+    fn take_all(&mut self, tokens: TokenStream) {
+        for tree in tokens {
+            match tree {
+                TokenTree::Ident(ident) => {
+                    self.taken.insert(ident.unraw().to_string());
+                }
+                TokenTree::Group(group) => self.take_all(group.stream()),
+                _ => (),
+            }
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -184,23 +727,63 @@ pub enum CallbackType {
 /// point, including the current callback at the top of the stack.
 pub struct Context {
     stack: Vec<(CallbackType, TokenStream)>,
+    positions: Vec<Option<Position>>,
+    ident_role: Option<IdentRole>,
+    signature: Option<Rc<Signature>>,
+    item_impl: Option<Rc<ItemImpl>>,
+    module_path: Vec<Ident>,
+    scope: Rc<RefCell<Vec<LocalBinding>>>,
+    scope_start: usize,
+    names: Option<Rc<Names>>,
+    symbols: Rc<RefCell<Symbols>>,
+    emitted: Rc<RefCell<TokenStream>>,
+    failure: Rc<RefCell<Option<MoistureError>>>,
+    attribute_args: Option<TokenStream>,
 }
 impl Context {
     /// Creates a new `Context` object with an empty stack.
     pub fn new() -> Self {
         Self {
             stack: Vec::<(CallbackType, TokenStream)>::new(),
+            positions: Vec::<Option<Position>>::new(),
+            ident_role: None,
+            signature: None,
+            item_impl: None,
+            module_path: Vec::<Ident>::new(),
+            scope: Rc::new(RefCell::new(Vec::new())),
+            scope_start: 0,
+            names: None,
+            symbols: Rc::new(RefCell::new(Symbols::default())),
+            emitted: Rc::new(RefCell::new(TokenStream::new())),
+            failure: Rc::new(RefCell::new(None)),
+            attribute_args: None,
         }
     }
     /// Pushes the given callback and its tokens onto the callback stack.
     ///
     /// This function is used whenever
-    /// [`Moisture::callback`](Moisture::callback) is issued.
+    /// [`Moisture::callback`](Moisture::callback) is issued. Pushing onto an
+    /// empty stack starts a new traversal: [`Context::gensym`](Context::gensym)
+    /// starts over from its first identifier, and the identifiers in the
+    /// pushed tokens are reserved.
     pub fn push(&mut self, ty: CallbackType, tokens: TokenStream) {
+        self.push_at(ty, tokens, None);
+    }
+    fn push_at(&mut self, ty: CallbackType, tokens: TokenStream, position: Option<Position>) {
+        if self.stack.is_empty() {
+            // contexts derived from this one share the new table, while other
+            // clones of a reused context keep their own
+            let mut symbols = Symbols::default();
+            symbols.take_all(tokens.clone());
+            self.symbols = Rc::new(RefCell::new(symbols));
+        }
+
         self.stack.push((ty, tokens));
+        self.positions.push(position);
     }
     /// Pops the top context off the stack.
     pub fn pop(&mut self) -> Option<(CallbackType, TokenStream)> {
+        self.positions.pop();
         self.stack.pop()
     }
     /// Get the context data in the stack at *distance* entries from the top of
@@ -208,7 +791,7 @@ impl Context {
     ///
     /// This is useful for determining a specific position in the callstack.
     pub fn peek(&self, distance: usize) -> Option<(CallbackType, TokenStream)> {
-        if self.stack.len() == 0 || distance >= self.stack.len() {
+        if self.stack.is_empty() || distance >= self.stack.len() {
             None
         } else {
             let entry = &self.stack[self.stack.len() - distance - 1];
@@ -219,2414 +802,6277 @@ impl Context {
     pub fn get_stack(&self) -> Vec<(CallbackType, TokenStream)> {
         self.stack.clone()
     }
+    /// Get the number of callbacks on the stack, including the current one.
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+    /// Iterate over the ancestors of the current callback, from its parent to
+    /// the root of the stack.
+    ///
+    /// The current callback itself isn't included, so the *n*th entry is at a
+    /// distance of *n + 1* for [`Context::peek`](Context::peek).
+    pub fn ancestors(&self) -> impl Iterator<Item = (CallbackType, &TokenStream)> {
+        self.stack
+            .iter()
+            .rev()
+            .skip(1)
+            .map(|(ty, tokens)| (*ty, tokens))
+    }
+    /// Get the closest ancestor of the given [`CallbackType`](CallbackType)
+    /// with its distance from the top of the stack.
+    ///
+    /// Like [`Context::contains`](Context::contains), this ignores the current
+    /// callback.
+    pub fn nearest(&self, ty: CallbackType) -> Option<(usize, &TokenStream)> {
+        self.ancestors()
+            .enumerate()
+            .find(|(_, (ancestor, _))| *ancestor == ty)
+            .map(|(index, (_, tokens))| (index + 1, tokens))
+    }
+    /// Get the closest ancestor of the [`CallbackType`](CallbackType) of the
+    /// [`Node`](Node) *T*, parsed as *T*.
+    ///
+    /// ```rust
+    /// use proc_macro2::TokenStream;
+    /// use quote::quote;
+    /// use syn::{ItemFn, Result};
+    ///
+    /// use moisture::*;
+    ///
+    /// fn lit_str(_: &Moisture, context: &Context, _: TokenStream) -> Result<TokenStream> {
+    ///     let name = match context.nearest_parsed::<ItemFn>()? {
+    ///         Some(item_fn) => item_fn.sig.ident.to_string(),
+    ///         None => String::new(),
+    ///     };
+    ///
+    ///     Ok(quote! { #name })
+    /// }
+    ///
+    /// let mut moisture = Moisture::new();
+    /// moisture.register_callback(CallbackType::LitStr, lit_str);
+    ///
+    /// let tokens = quote! { fn f() { let name = ""; } };
+    /// let new_tokens = run_moisture!(moisture, CallbackType::Item, tokens);
+    ///
+    /// assert_eq!(new_tokens.to_string(), quote! { fn f() { let name = "f"; } }.to_string());
+    /// ```
+    pub fn nearest_parsed<T: Node>(&self) -> Result<Option<T>> {
+        match self.nearest(T::CALLBACK_TYPE) {
+            Some((_, tokens)) => T::parse_node(tokens.clone()).map(Some),
+            None => Ok(None),
+        }
+    }
     /// Check if there's a callback in the current callback stack.
     ///
     /// This ignores the top of the stack, as it's assumed the top of the stack
     /// is the callee for determining if a callback is in the stack.
     pub fn contains(&self, ty: CallbackType) -> bool {
-        for entry in &self.stack[..self.stack.len() - 1] {
-            if entry.0 == ty {
-                return true;
+        self.nearest(ty).is_some()
+    }
+    /// Get the position of the node of the current callback within the node of
+    /// its parent.
+    ///
+    /// This is set for every callback issued by the default handlers, and is
+    /// `None` for callbacks issued with [`Moisture::callback`](Moisture::callback).
+    /// A node handed from its category to its kind, such as from
+    /// [`CallbackType::Expr`](CallbackType::Expr) to
+    /// [`CallbackType::ExprCall`](CallbackType::ExprCall), keeps its position.
+    ///
+    /// ```rust
+    /// use proc_macro2::TokenStream;
+    /// use quote::quote;
+    /// use syn::Result;
+    ///
+    /// use moisture::*;
+    ///
+    /// fn expr_call(_: &Moisture, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    ///     match context.position() {
+    ///         Some(position) if position.is(CallbackType::ExprIf, "cond") => Ok(quote! { true }),
+    ///         _ => Ok(tokens),
+    ///     }
+    /// }
+    ///
+    /// let mut moisture = Moisture::new();
+    /// moisture.register_callback(CallbackType::ExprCall, expr_call);
+    ///
+    /// let tokens = quote! { if f() { g() } };
+    /// let new_tokens = run_moisture!(moisture, CallbackType::Expr, tokens);
+    ///
+    /// assert_eq!(new_tokens.to_string(), quote! { if true { g() } }.to_string());
+    /// ```
+    pub fn position(&self) -> Option<Position> {
+        self.position_at(0)
+    }
+    /// Get the position of the callback at *distance* entries from the top of
+    /// the stack, like [`Context::peek`](Context::peek).
+    pub fn position_at(&self, distance: usize) -> Option<Position> {
+        if distance >= self.positions.len() {
+            None
+        } else {
+            self.positions[self.positions.len() - distance - 1]
+        }
+    }
+    /// Get the role of the identifier given to the current callback.
+    ///
+    /// This is set for [`CallbackType::Ident`](CallbackType::Ident) callbacks
+    /// and for [`CallbackType::PathSegment`](CallbackType::PathSegment)
+    /// callbacks, whose identifier is a [`IdentRole::Macro`](IdentRole::Macro)
+    /// when the segment names an invoked macro. It's `None` for every other
+    /// callback.
+    pub fn ident_role(&self) -> Option<IdentRole> {
+        self.ident_role
+    }
+    /// Get the signature of the function or method enclosing the current
+    /// callback, as written in the input.
+    ///
+    /// Closures don't have a signature of their own, so this is the signature
+    /// of the function they're in. Items nested in a function body, such as a
+    /// helper function, don't see the signature of the function around them.
+    ///
+    /// ```rust
+    /// use proc_macro2::TokenStream;
+    /// use quote::quote;
+    /// use syn::{Error, Result};
+    ///
+    /// use moisture::*;
+    ///
+    /// fn expr_try(_: &Moisture, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    ///     match context.signature() {
+    ///         Some(sig) if sig.asyncness.is_some() => Ok(tokens),
+    ///         _ => Err(Error::new_spanned(tokens, "`?` is only allowed in async functions")),
+    ///     }
+    /// }
+    ///
+    /// let mut moisture = Moisture::new();
+    /// moisture.register_callback(CallbackType::ExprTry, expr_try);
+    ///
+    /// let tokens = quote! { async fn f() -> Result<(), ()> { g()?; Ok(()) } };
+    /// assert!(moisture.callback(&Context::new(), CallbackType::Item, tokens).is_ok());
+    ///
+    /// let tokens = quote! { fn f() -> Result<(), ()> { g()?; Ok(()) } };
+    /// assert!(moisture.callback(&Context::new(), CallbackType::Item, tokens).is_err());
+    /// ```
+    pub fn signature(&self) -> Option<&Signature> {
+        self.signature.as_deref()
+    }
+    /// Get the `Self` type of the `impl` block enclosing the current callback.
+    pub fn self_ty(&self) -> Option<&Type> {
+        self.item_impl.as_ref().map(|item_impl| &*item_impl.self_ty)
+    }
+    /// Get the generics of the `impl` block enclosing the current callback,
+    /// including its where clause.
+    pub fn impl_generics(&self) -> Option<&Generics> {
+        self.item_impl.as_ref().map(|item_impl| &item_impl.generics)
+    }
+    /// Get the path of the trait implemented by the `impl` block enclosing the
+    /// current callback, such as `fmt::Display` in
+    /// `impl fmt::Display for Point`.
+    ///
+    /// This is `None` outside of `impl` blocks and within inherent ones.
+    pub fn impl_trait(&self) -> Option<&Path> {
+        self.item_impl
+            .as_ref()
+            .and_then(|item_impl| item_impl.trait_.as_ref())
+            .map(|(_, path, _)| path)
+    }
+    /// Get the names of the inline modules enclosing the current callback,
+    /// from the outermost to the innermost.
+    ///
+    /// The path is relative to the tree the traversal started from, so it's
+    /// empty for the top-level items of a file.
+    pub fn module_path(&self) -> &[Ident] {
+        &self.module_path
+    }
+    /// Get the variables in scope for the current callback, from the most
+    /// recently bound outwards.
+    ///
+    /// Variables are in scope from the statement after their `let` statement
+    /// to the end of their block, and within the body of the function, closure,
+    /// loop, `if let` branch or match arm binding them. Items nested in a
+    /// function body don't see its variables.
+    pub fn bindings(&self) -> Vec<LocalBinding> {
+        self.scope.borrow()[self.scope_start..]
+            .iter()
+            .rev()
+            .cloned()
+            .collect()
+    }
+    /// Resolve an identifier to the variable it refers to, taking shadowing
+    /// into account.
+    ///
+    /// `None` means the identifier isn't a variable in scope, so it refers to
+    /// an item or to something outside of the tree.
+    ///
+    /// ```rust
+    /// use proc_macro2::TokenStream;
+    /// use quote::quote;
+    /// use syn::{ExprPath, Result};
+    ///
+    /// use moisture::*;
+    ///
+    /// fn expr_path(_: &Moisture, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    ///     let path = syn::parse2::<ExprPath>(tokens.clone())?.path;
+    ///     let binding = path.get_ident().and_then(|ident| context.resolve(ident));
+    ///
+    ///     match binding {
+    ///         Some(binding) if binding.site == BindingSite::Param => Ok(quote! { param }),
+    ///         Some(_) => Ok(quote! { local }),
+    ///         None => Ok(tokens),
+    ///     }
+    /// }
+    ///
+    /// let mut moisture = Moisture::new();
+    /// moisture.register_callback(CallbackType::ExprPath, expr_path);
+    ///
+    /// let tokens = quote! { fn f(x: u8) { g(x); let x = 1; g(x); } };
+    /// let new_tokens = run_moisture!(moisture, CallbackType::Item, tokens);
+    ///
+    /// assert_eq!(
+    ///     new_tokens.to_string(),
+    ///     quote! { fn f(x: u8) { g(param); let x = 1; g(local); } }.to_string()
+    /// );
+    /// ```
+    pub fn resolve(&self, ident: &Ident) -> Option<LocalBinding> {
+        self.scope.borrow()[self.scope_start..]
+            .iter()
+            .rev()
+            .find(|binding| binding.ident == *ident)
+            .cloned()
+    }
+    /// Push a scope frame, whose variables go out of scope when it's dropped.
+    fn enter_scope(&self) -> ScopeFrame {
+        ScopeFrame {
+            scope: self.scope.clone(),
+            len: self.scope.borrow().len(),
+        }
+    }
+    /// Bring the variables bound by the given pattern into the innermost
+    /// scope frame.
+    fn bind(&self, pat: &Pat, site: BindingSite) {
+        pat_bindings(pat, None, site, &mut self.scope.borrow_mut());
+    }
+    /// Bring the parameters of a function into the innermost scope frame.
+    fn bind_inputs(&self, sig: &Signature) {
+        for input in &sig.inputs {
+            match input {
+                FnArg::Receiver(receiver) => {
+                    let pat = PatIdent {
+                        attrs: Vec::new(),
+                        by_ref: None,
+                        mutability: receiver.mutability,
+                        ident: Ident::new("self", receiver.self_token.span),
+                        subpat: None,
+                    };
+                    self.bind(&Pat::Ident(pat), BindingSite::Param)
+                }
+                FnArg::Typed(pat_type) => {
+                    self.bind(&Pat::Type(pat_type.clone()), BindingSite::Param)
+                }
+            }
+        }
+    }
+    /// Bring the variables bound by the `let` expressions of a condition into
+    /// the innermost scope frame.
+    fn bind_cond(&self, cond: &Expr, site: BindingSite) {
+        for pat in let_patterns(cond) {
+            self.bind(&pat, site);
+        }
+    }
+    /// Enter the body of a function with the given signature.
+    fn enter_fn(&self, sig: &Signature) -> Self {
+        let mut context = self.clone();
+        context.signature = Some(Rc::new(sig.clone()));
+        context
+    }
+    /// Enter an `impl` block, which is kept without its items.
+    fn enter_impl(&self, item_impl: &ItemImpl) -> Self {
+        let mut context = self.clone();
+        context.item_impl = Some(Rc::new(ItemImpl {
+            items: Vec::new(),
+            ..item_impl.clone()
+        }));
+        context
+    }
+    /// Get the canonical path of *path*, following the `use` declarations and
+    /// item definitions of the tree.
+    ///
+    /// This is a best-effort resolution of the first segment of the path: with
+    /// `use std::collections::HashMap as Map;` in scope, `Map::<K, V>::new`
+    /// is `std::collections::HashMap::<K, V>::new`. Items defined in the tree
+    /// and paths starting with `self` or `super` resolve to paths starting with
+    /// `crate`, as if the tree were the root of its crate, and items defined in
+    /// a block resolve as if they were defined in its module. Paths which don't
+    /// start with a name declared in the tree, such as those of glob imports,
+    /// the prelude or other crates, and paths naming a variable in scope are
+    /// returned as they are.
+    ///
+    /// ```rust
+    /// use proc_macro2::TokenStream;
+    /// use quote::quote;
+    /// use syn::{ExprCall, Result};
+    ///
+    /// use moisture::*;
+    ///
+    /// fn expr_call(_: &Moisture, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    ///     let func = match syn::parse2::<ExprCall>(tokens.clone())?.func.as_ref() {
+    ///         syn::Expr::Path(expr_path) => context.canonical_path(&expr_path.path),
+    ///         _ => return Ok(tokens),
+    ///     };
+    ///
+    ///     if quote! { #func }.to_string() == "std :: collections :: HashMap :: new" {
+    ///         Ok(quote! { std::collections::HashMap::with_capacity(16) })
+    ///     } else {
+    ///         Ok(tokens)
+    ///     }
+    /// }
+    ///
+    /// let mut moisture = Moisture::new();
+    /// moisture.register_callback(CallbackType::ExprCall, expr_call);
+    ///
+    /// let tokens = quote! {
+    ///     use std::collections::{self as c, HashMap as Map};
+    ///
+    ///     fn f() { let a = Map::new(); let b = c::HashMap::new(); }
+    /// };
+    /// let new_tokens = run_moisture!(moisture, CallbackType::File, tokens);
+    ///
+    /// assert_eq!(new_tokens.to_string(), quote! {
+    ///     use std::collections::{self as c, HashMap as Map};
+    ///
+    ///     fn f() {
+    ///         let a = std::collections::HashMap::with_capacity(16);
+    ///         let b = std::collections::HashMap::with_capacity(16);
+    ///     }
+    /// }.to_string());
+    /// ```
+    pub fn canonical_path(&self, path: &Path) -> Path {
+        let mut path = path.clone();
+
+        // a limit on the number of declarations followed, in case of cycles
+        for _ in 0..16 {
+            match self.resolve_first_segment(&path) {
+                Some(new_path) => path = new_path,
+                None => break,
             }
         }
 
-        false
+        path
     }
-}
+    fn resolve_first_segment(&self, path: &Path) -> Option<Path> {
+        if path.leading_colon.is_some() {
+            return None;
+        }
 
-/// The callback function to register with the [`Moisture`](Moisture) structure.
-pub type Callback = fn(&Moisture, &Context, TokenStream) -> Result<TokenStream>;
+        let first = path.segments.first()?;
 
-/// The macro to use in procedural macros when parsing with
-/// [`Moisture`](Moisture).
-///
-/// *moisture* is the [`Moisture`](Moisture) structure, *callback_ty* is the
-/// [`CallbackType`](CallbackType) to issue, and *tokens* is a
-/// [`TokenStream`](TokenStream). This effectively calls
-/// [`Moisture::callback`](Moisture::callback) and returns a compile error if an
-/// error occurred in parsing.
-#[macro_export]
-macro_rules! run_moisture {
-    ($moisture:ident, $callback_ty:path, $tokens:ident) => {
-        match $moisture.callback(&Context::new(), $callback_ty, $tokens) {
-            Ok(new_tokens) => new_tokens,
-            Err(e) => e.to_compile_error(),
+        if path.segments.len() == 1 && self.resolve(&first.ident).is_some() {
+            return None;
+        } else if first.ident == "self" || first.ident == "super" {
+            return absolute_path(path, &self.module_path);
         }
-    };
-}
 
-/// Get a [`PatType`](syn::PatType) object from a [`TokenStream`](TokenStream).
-///
-/// Because [syn](syn) doesn't provide this parsing functionality, a helper
-/// function is provided.
-pub fn get_pat_type(tokens: TokenStream) -> Result<PatType> {
-    let stub = quote! { let #tokens ; };
-    let stmt = parse2::<Stmt>(stub)?;
-    let stmt_span = stmt.span().clone();
-    let local_data;
+        let name = first.ident.unraw().to_string();
+        let target = std::iter::successors(self.names.as_deref(), |names| names.outer.as_deref())
+            .find_map(|names| names.paths.get(&name))?;
 
-    if let Stmt::Local(local) = stmt {
-        local_data = local;
-    } else {
-        return Err(Error::new(
-            stmt_span,
-            "expected Local statement in PatType interpretation",
-        ));
-    }
+        if target.segments.len() == 1 && target.segments[0].ident == first.ident {
+            return None;
+        }
 
-    if let Pat::Type(pat) = local_data.pat {
-        Ok(pat)
-    } else {
-        Err(Error::new(
-            stmt_span,
-            "expected PatType object in Local statement pattern",
-        ))
+        // the arguments of the first segment go to the last segment it stands for
+        let mut new_path = target.clone();
+
+        if let Some(last) = new_path.segments.last_mut() {
+            last.arguments = first.arguments.clone();
+        }
+
+        for segment in path.segments.iter().skip(1) {
+            new_path.segments.push(segment.clone());
+        }
+
+        Some(new_path)
     }
-}
+    /// Declare the names of the given items, which are in scope throughout the
+    /// module or block they're in.
+    fn declare(&self, items: &[&Item]) -> Self {
+        let mut paths = HashMap::<String, Path>::new();
 
-/// The structure which holds and processes the callbacks.
-///
-/// Default callbacks are 1:1 named after their [syn](syn) counterparts, with
-/// the exception of [`Moisture::stmts`](Moisture::stmts), which is a helper
-/// callback for a series of statements.
-#[derive(Clone)]
-pub struct Moisture {
-    callbacks: HashMap<CallbackType, Callback>,
-}
-impl Moisture {
-    /// Create a new `Moisture` object with default callbacks registered.
-    pub fn new() -> Self {
-        let mut result = Self {
-            callbacks: HashMap::<CallbackType, Callback>::new(),
-        };
-        result.load_defaults();
-        result
+        for item in items {
+            item_paths(item, &self.module_path, &mut paths);
+        }
+
+        let mut context = self.clone();
+        context.names = Some(Rc::new(Names {
+            paths,
+            outer: self.names.clone(),
+        }));
+        context
     }
-    fn load_defaults(&mut self) {
-        self.register_callback(CallbackType::File, Moisture::file);
-
-        self.register_callback(CallbackType::Item, Moisture::item);
-        self.register_callback(CallbackType::ItemConst, Moisture::item_const);
-        self.register_callback(CallbackType::ItemEnum, Moisture::item_enum);
-        self.register_callback(CallbackType::ItemExternCrate, Moisture::item_extern_crate);
-        self.register_callback(CallbackType::ItemFn, Moisture::item_fn);
-        self.register_callback(CallbackType::ItemForeignMod, Moisture::item_foreign_mod);
-        self.register_callback(CallbackType::ItemImpl, Moisture::item_impl);
-        self.register_callback(CallbackType::ItemMacro, Moisture::item_macro);
-        self.register_callback(CallbackType::ItemMacro2, Moisture::item_macro2);
-        self.register_callback(CallbackType::ItemMod, Moisture::item_mod);
-        self.register_callback(CallbackType::ItemStatic, Moisture::item_static);
-        self.register_callback(CallbackType::ItemStruct, Moisture::item_struct);
-        self.register_callback(CallbackType::ItemTrait, Moisture::item_trait);
-        self.register_callback(CallbackType::ItemTraitAlias, Moisture::item_trait_alias);
-        self.register_callback(CallbackType::ItemType, Moisture::item_type);
-        self.register_callback(CallbackType::ItemUnion, Moisture::item_union);
-        self.register_callback(CallbackType::ItemUse, Moisture::item_use);
-
-        self.register_callback(CallbackType::Variant, Moisture::variant);
-
-        self.register_callback(CallbackType::ForeignItem, Moisture::foreign_item);
-        self.register_callback(CallbackType::ForeignItemFn, Moisture::foreign_item_fn);
-        self.register_callback(
-            CallbackType::ForeignItemStatic,
-            Moisture::foreign_item_static,
-        );
-        self.register_callback(CallbackType::ForeignItemType, Moisture::foreign_item_type);
-        self.register_callback(CallbackType::ForeignItemMacro, Moisture::foreign_item_macro);
-
-        self.register_callback(CallbackType::ImplItem, Moisture::impl_item);
-        self.register_callback(CallbackType::ImplItemConst, Moisture::impl_item_const);
-        self.register_callback(CallbackType::ImplItemMethod, Moisture::impl_item_method);
-        self.register_callback(CallbackType::ImplItemType, Moisture::impl_item_type);
-        self.register_callback(CallbackType::ImplItemMacro, Moisture::impl_item_macro);
-
-        self.register_callback(CallbackType::TraitItem, Moisture::trait_item);
-        self.register_callback(CallbackType::TraitItemConst, Moisture::trait_item_const);
-        self.register_callback(CallbackType::TraitItemMethod, Moisture::trait_item_method);
-        self.register_callback(CallbackType::TraitItemType, Moisture::trait_item_type);
-        self.register_callback(CallbackType::TraitItemMacro, Moisture::trait_item_macro);
-
-        self.register_callback(CallbackType::Block, Moisture::block);
-
-        self.register_callback(CallbackType::Stmts, Moisture::stmts);
-
-        self.register_callback(CallbackType::Stmt, Moisture::stmt);
-
-        self.register_callback(CallbackType::Local, Moisture::local);
-
-        self.register_callback(CallbackType::Pat, Moisture::pat);
-        self.register_callback(CallbackType::PatBox, Moisture::pat_box);
-        self.register_callback(CallbackType::PatIdent, Moisture::pat_ident);
-        self.register_callback(CallbackType::PatLit, Moisture::pat_lit);
-        self.register_callback(CallbackType::PatMacro, Moisture::pat_macro);
-        self.register_callback(CallbackType::PatOr, Moisture::pat_or);
-        self.register_callback(CallbackType::PatPath, Moisture::pat_path);
-        self.register_callback(CallbackType::PatRange, Moisture::pat_range);
-        self.register_callback(CallbackType::PatReference, Moisture::pat_reference);
-        self.register_callback(CallbackType::PatRest, Moisture::pat_rest);
-        self.register_callback(CallbackType::PatSlice, Moisture::pat_slice);
-        self.register_callback(CallbackType::PatStruct, Moisture::pat_struct);
-        self.register_callback(CallbackType::PatTuple, Moisture::pat_tuple);
-        self.register_callback(CallbackType::PatTupleStruct, Moisture::pat_tuple_struct);
-        self.register_callback(CallbackType::PatType, Moisture::pat_type);
-        self.register_callback(CallbackType::PatWild, Moisture::pat_wild);
-
-        self.register_callback(CallbackType::Expr, Moisture::expr);
-        self.register_callback(CallbackType::ExprArray, Moisture::expr_array);
-        self.register_callback(CallbackType::ExprAssign, Moisture::expr_assign);
-        self.register_callback(CallbackType::ExprAssignOp, Moisture::expr_assign_op);
-        self.register_callback(CallbackType::ExprAsync, Moisture::expr_async);
-        self.register_callback(CallbackType::ExprAwait, Moisture::expr_await);
-        self.register_callback(CallbackType::ExprBinary, Moisture::expr_binary);
-        self.register_callback(CallbackType::ExprBlock, Moisture::expr_block);
-        self.register_callback(CallbackType::ExprBox, Moisture::expr_box);
-        self.register_callback(CallbackType::ExprBreak, Moisture::expr_break);
-        self.register_callback(CallbackType::ExprCall, Moisture::expr_call);
-        self.register_callback(CallbackType::ExprCast, Moisture::expr_cast);
-        self.register_callback(CallbackType::ExprClosure, Moisture::expr_closure);
-        self.register_callback(CallbackType::ExprContinue, Moisture::expr_continue);
-        self.register_callback(CallbackType::ExprField, Moisture::expr_field);
-        self.register_callback(CallbackType::ExprForLoop, Moisture::expr_for_loop);
-        self.register_callback(CallbackType::ExprGroup, Moisture::expr_group);
-        self.register_callback(CallbackType::ExprIf, Moisture::expr_if);
-        self.register_callback(CallbackType::ExprIndex, Moisture::expr_index);
-        self.register_callback(CallbackType::ExprLet, Moisture::expr_let);
-        self.register_callback(CallbackType::ExprLit, Moisture::expr_lit);
-        self.register_callback(CallbackType::ExprLoop, Moisture::expr_loop);
-        self.register_callback(CallbackType::ExprMacro, Moisture::expr_macro);
-        self.register_callback(CallbackType::ExprMatch, Moisture::expr_match);
-        self.register_callback(CallbackType::ExprMethodCall, Moisture::expr_method_call);
-        self.register_callback(CallbackType::ExprParen, Moisture::expr_paren);
-        self.register_callback(CallbackType::ExprPath, Moisture::expr_path);
-        self.register_callback(CallbackType::ExprRange, Moisture::expr_range);
-        self.register_callback(CallbackType::ExprReference, Moisture::expr_reference);
-        self.register_callback(CallbackType::ExprRepeat, Moisture::expr_repeat);
-        self.register_callback(CallbackType::ExprReturn, Moisture::expr_return);
-        self.register_callback(CallbackType::ExprStruct, Moisture::expr_struct);
-        self.register_callback(CallbackType::ExprTry, Moisture::expr_try);
-        self.register_callback(CallbackType::ExprTryBlock, Moisture::expr_try_block);
-        self.register_callback(CallbackType::ExprTuple, Moisture::expr_tuple);
-        self.register_callback(CallbackType::ExprType, Moisture::expr_type);
-        self.register_callback(CallbackType::ExprUnary, Moisture::expr_unary);
-        self.register_callback(CallbackType::ExprUnsafe, Moisture::expr_unsafe);
-        self.register_callback(CallbackType::ExprWhile, Moisture::expr_while);
-        self.register_callback(CallbackType::ExprYield, Moisture::expr_yield);
-
-        self.register_callback(CallbackType::Arm, Moisture::arm);
-
-        self.register_callback(CallbackType::Lit, Moisture::lit);
-        self.register_callback(CallbackType::LitStr, Moisture::lit_str);
-        self.register_callback(CallbackType::LitByteStr, Moisture::lit_byte_str);
-        self.register_callback(CallbackType::LitByte, Moisture::lit_byte);
-        self.register_callback(CallbackType::LitChar, Moisture::lit_char);
-        self.register_callback(CallbackType::LitInt, Moisture::lit_int);
-        self.register_callback(CallbackType::LitFloat, Moisture::lit_float);
-        self.register_callback(CallbackType::LitBool, Moisture::lit_bool);
-
-        self.register_callback(CallbackType::FieldValue, Moisture::field_value);
-
-        self.register_callback(CallbackType::Verbatim, Moisture::verbatim);
+    /// Enter the items of an inline module, which don't see the names declared
+    /// around the module.
+    fn enter_mod(&self, ident: &Ident, items: &[Item]) -> Self {
+        let mut context = self.clone();
+        context.module_path.push(ident.clone());
+        context.names = None;
+        context.declare(&items.iter().collect::<Vec<_>>())
     }
-    /// Register a [`Callback`](Callback) for the given
-    /// [`CallbackType`](CallbackType).
+    /// Enter an item, which can't refer to the function or `impl` block it's
+    /// nested in.
+    fn enter_item(&self) -> Self {
+        let mut context = self.clone();
+        context.signature = None;
+        context.item_impl = None;
+        context.scope_start = context.scope.borrow().len();
+        context
+    }
+    /// Get the arguments of the attribute macro the callbacks are issued for.
     ///
-    /// This overwrites the default callback in the structure when an item is
-    /// parsed.
-    pub fn register_callback(&mut self, ty: CallbackType, callback: Callback) {
-        self.callbacks.insert(ty, callback);
+    /// This is set by [`Moisture::run_attribute`](Moisture::run_attribute),
+    /// such as to `skip` for `#[my_attribute(skip)]`, and is `None` otherwise.
+    pub fn attribute_args(&self) -> Option<TokenStream> {
+        self.attribute_args.clone()
     }
-    /// Issue a callback to the given [`CallbackType`](CallbackType) to parse
-    /// the given [`TokenStream`](TokenStream).
+    /// Emit generated items, such as the `impl` blocks of a derive macro.
     ///
-    /// This essentially creates a new context stack, pushes the new context
-    /// onto the new stack, then issues the call to the registered callback.
-    pub fn callback(
-        &self,
-        context: &Context,
-        ty: CallbackType,
-        tokens: TokenStream,
-    ) -> Result<TokenStream> {
-        if let Some(callback) = self.callbacks.get(&ty) {
-            let mut new_context = context.clone();
-            new_context.push(ty, tokens.clone());
+    /// Emitted items are collected for every context derived from the same
+    /// root, and are returned by [`Moisture::derive`](Moisture::derive) or
+    /// appended to the output of [`Moisture::run`](Moisture::run) and
+    /// [`Moisture::run_attribute`](Moisture::run_attribute).
+    pub fn emit(&self, tokens: TokenStream) {
+        self.emitted.borrow_mut().extend(tokens);
+    }
+    /// Take the items emitted so far with [`Context::emit`](Context::emit).
+    pub fn take_emitted(&self) -> TokenStream {
+        std::mem::take(&mut *self.emitted.borrow_mut())
+    }
+    /// Generate a fresh identifier starting with *prefix*, with
+    /// [`Span::mixed_site`](Span::mixed_site) hygiene.
+    ///
+    /// The identifier doesn't clash with any identifier in the tree the
+    /// traversal started from, nor with any other identifier generated during
+    /// the traversal, which shares its generator with every context derived
+    /// from the same root. Identifiers have the form `__{prefix}_{n}`, such as
+    /// `__tmp_0` and `__tmp_1`, where *n* counts up from 0 in the order
+    /// identifiers are generated and skips any number whose identifier is
+    /// taken, so expansions are stable across builds. Characters of *prefix*
+    /// that can't occur in an identifier, such as the `-` of `my-tmp`, are
+    /// replaced with `_`.
+    ///
+    /// ```rust
+    /// use proc_macro2::TokenStream;
+    /// use quote::quote;
+    /// use syn::Result;
+    ///
+    /// use moisture::*;
+    ///
+    /// fn expr_try(_: &Moisture, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    ///     let tmp = context.gensym("tmp");
+    ///     Ok(quote! { { let #tmp = #tokens; #tmp } })
+    /// }
+    ///
+    /// let mut moisture = Moisture::new();
+    /// moisture.register_callback(CallbackType::ExprTry, expr_try);
+    ///
+    /// let tokens = quote! { fn f(__tmp_0: u8) -> Result<(), ()> { a()?; b()?; Ok(()) } };
+    /// let new_tokens = run_moisture!(moisture, CallbackType::Item, tokens);
+    /// let output = new_tokens.to_string();
+    ///
+    /// assert!(output.contains("let __tmp_1 = a () ?"));
+    /// assert!(output.contains("let __tmp_2 = b () ?"));
+    /// ```
+    pub fn gensym(&self, prefix: &str) -> Ident {
+        self.gensym_spanned(prefix, Span::mixed_site())
+    }
+    /// Generate a fresh identifier like [`Context::gensym`](Context::gensym),
+    /// with the hygiene and location of *span*, such as
+    /// [`Span::call_site`](Span::call_site) for an identifier the caller of the
+    /// macro can refer to.
+    pub fn gensym_spanned(&self, prefix: &str, span: Span) -> Ident {
+        let mut symbols = self.symbols.borrow_mut();
+
+        loop {
+            let name = format!("__{}_{}", sanitize_prefix(prefix), symbols.next);
+            symbols.next += 1;
+
+            if symbols.taken.insert(name.clone()) {
+                return Ident::new(&name, span);
+            }
+        }
+    }
+    /// Record *error* as a failure of the given kind raised by the callback on
+    /// top of the stack, and return it.
+    fn raise(&self, kind: ErrorKind, error: syn::Error) -> syn::Error {
+        let path = self.stack.iter().map(|(ty, _)| *ty).collect();
+        *self.failure.borrow_mut() = Some(MoistureError::new(kind, error.clone(), path));
+
+        error
+    }
+    /// Record *error* as a failure of the given kind raised by the callback on
+    /// top of the stack, unless it is already recorded by a deeper callback
+    /// it passed through.
+    fn track(&self, kind: ErrorKind, error: &syn::Error) {
+        if !self.is_recorded(error) {
+            self.raise(kind, error.clone());
+        }
+    }
+    fn is_recorded(&self, error: &syn::Error) -> bool {
+        match &*self.failure.borrow() {
+            Some(failure) => failure.error.to_string() == error.to_string(),
+            None => false,
+        }
+    }
+    /// Forget the recorded failure, which a callback recovered from.
+    fn clear_failure(&self) {
+        self.failure.borrow_mut().take();
+    }
+    /// Take the recorded failure of *error*, or treat it as an error raised
+    /// outside of a callback if it isn't recorded.
+    fn take_failure(&self, error: syn::Error) -> MoistureError {
+        match self.failure.borrow_mut().take() {
+            Some(failure) if failure.error.to_string() == error.to_string() => failure,
+            _ => MoistureError::from(error),
+        }
+    }
+}
+impl Default for Context {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-            let result = callback(self, &new_context, tokens)?;
+/// Replace the characters of *prefix* that can't continue an identifier with
+/// `_`.
+fn sanitize_prefix(prefix: &str) -> String {
+    prefix
+        .chars()
+        .map(|c| match parse_str::<Ident>(&format!("_{}", c)) {
+            Ok(_) => c,
+            Err(_) => '_',
+        })
+        .collect()
+}
 
-            Ok(result)
+/// The kind of a [`MoistureError`](MoistureError).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// Tokens couldn't be parsed as the node they were given for, such as a
+    /// default callback given the output of a callback that isn't its node.
+    Parse,
+    /// No callback is registered for a [`CallbackType`](CallbackType).
+    MissingCallback,
+    /// An error returned by a registered callback or a
+    /// [`Wildcard`](Wildcard) callback.
+    User,
+    /// The output of a callback isn't its node, found with
+    /// [`Moisture::set_validation`](Moisture::set_validation), or a
+    /// [`CallbackType::BinOp`](CallbackType::BinOp) callback turned a compound
+    /// assignment into another kind of operator or the other way around.
+    Validation,
+}
+
+/// An error raised while issuing callbacks, as returned by
+/// [`Moisture::traverse`](Moisture::traverse).
+///
+/// Callbacks return plain [`syn::Error`](syn::Error)s, which moisture tracks
+/// as they pass up through the traversal: the error knows its
+/// [`ErrorKind`](ErrorKind) and the path of callbacks that raised it, such as
+/// `File > Item > ItemImpl > ImplItem > ImplItemMethod > Block > ExprCall`,
+/// which is attached to its message as a note.
+#[derive(Clone, Debug)]
+pub struct MoistureError {
+    kind: ErrorKind,
+    error: syn::Error,
+    path: Vec<CallbackType>,
+}
+impl MoistureError {
+    fn new(kind: ErrorKind, error: syn::Error, path: Vec<CallbackType>) -> Self {
+        Self { kind, error, path }
+    }
+    /// Get the kind of the error.
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+    /// Get the path of callbacks that raised the error, from the root to the
+    /// callback that raised it.
+    ///
+    /// This is empty for errors raised outside of a callback.
+    pub fn path(&self) -> &[CallbackType] {
+        &self.path
+    }
+    /// Get the span of the error.
+    pub fn span(&self) -> Span {
+        self.error.span()
+    }
+    fn path_note(&self) -> Option<String> {
+        if self.path.is_empty() {
+            return None;
+        }
+
+        let path = self
+            .path
+            .iter()
+            .map(|ty| ty.name())
+            .collect::<Vec<_>>()
+            .join(" > ");
+
+        Some(format!("note: in {}", path))
+    }
+    /// Convert the error into a [`syn::Error`](syn::Error), with the path of
+    /// callbacks attached to every message.
+    pub fn into_syn_error(self) -> syn::Error {
+        let note = match self.path_note() {
+            Some(note) => note,
+            None => return self.error,
+        };
+        let mut messages = self
+            .error
+            .into_iter()
+            .map(|error| syn::Error::new(error.span(), format!("{}\n{}", error, note)));
+        let mut result = messages.next().expect("syn errors have a message");
+
+        for message in messages {
+            result.combine(message);
+        }
+
+        result
+    }
+    /// Render the error as a `compile_error!` invocation.
+    pub fn to_compile_error(&self) -> TokenStream {
+        self.clone().into_syn_error().to_compile_error()
+    }
+}
+impl fmt::Display for MoistureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.error)?;
+
+        if let Some(note) = self.path_note() {
+            write!(f, "\n{}", note)?;
+        }
+
+        Ok(())
+    }
+}
+impl std::error::Error for MoistureError {}
+impl From<syn::Error> for MoistureError {
+    fn from(error: syn::Error) -> Self {
+        Self::new(ErrorKind::User, error, Vec::<CallbackType>::new())
+    }
+}
+impl From<MoistureError> for syn::Error {
+    fn from(error: MoistureError) -> Self {
+        error.into_syn_error()
+    }
+}
+
+/// The result of [`Moisture::traverse`](Moisture::traverse).
+pub type MoistureResult<T> = std::result::Result<T, MoistureError>;
+
+/// The callback function to register with the [`Moisture`](Moisture) structure.
+pub type Callback = fn(&Moisture, &Context, TokenStream) -> Result<TokenStream>;
+
+/// The catch-all callback to register with
+/// [`Moisture::register_wildcard`](Moisture::register_wildcard), which is also
+/// given the [`CallbackType`](CallbackType) being issued.
+pub type Wildcard = fn(&Moisture, &Context, CallbackType, TokenStream) -> Result<TokenStream>;
+
+/// A [syn](syn) node with its own [`CallbackType`](CallbackType).
+///
+/// Nodes can be given to [`Moisture::transform`](Moisture::transform) and
+/// [`Moisture::rebuild`](Moisture::rebuild), which return a node rather than a
+/// [`TokenStream`](TokenStream).
+pub trait Node: ToTokens + Sized {
+    /// The [`CallbackType`](CallbackType) of the node.
+    const CALLBACK_TYPE: CallbackType;
+
+    /// Parse the node out of the output of a callback.
+    fn parse_node(tokens: TokenStream) -> Result<Self>;
+}
+
+/// A qualified path, such as `<Vec<T> as IntoIterator>::Item`, which is the
+/// [`Node`](Node) of [`CallbackType::QSelf`](CallbackType::QSelf).
+///
+/// [syn](syn) only knows qualified paths as part of a
+/// [`TypePath`](syn::TypePath) or an [`ExprPath`](syn::ExprPath), and its
+/// [`QSelf`](syn::QSelf) can't be turned into tokens on its own.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct QualifiedPath {
+    /// The qualified self type and the position of the trait in *path*.
+    pub qself: QSelf,
+    /// The path, including the segments of the trait.
+    pub path: Path,
+}
+impl ToTokens for QualifiedPath {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        TypePath {
+            qself: Some(self.qself.clone()),
+            path: self.path.clone(),
+        }
+        .to_tokens(tokens);
+    }
+}
+
+/// Parse *tokens* as zero or more *T*.
+fn parse_many<T: parse::Parse>(tokens: TokenStream) -> Result<()> {
+    let parser = |input: parse::ParseStream| -> Result<()> {
+        while !input.is_empty() {
+            input.parse::<T>()?;
+        }
+
+        Ok(())
+    };
+
+    parser.parse2(tokens)
+}
+
+/// Parse the output of a callback as what its parent splices in.
+///
+/// The kinds of a dispatching node can be replaced with any other kind, so
+/// their output is parsed as their category, and items and statements can be
+/// replaced with any number of them. Operator callbacks can replace the
+/// entire operation, so their output is any expression. Let-else statements
+/// aren't a [`Local`](Local) in [syn](syn).
+fn validate_output(ty: CallbackType, tokens: TokenStream) -> Option<Result<()>> {
+    match ty.category().unwrap_or(ty) {
+        CallbackType::Item => Some(parse_many::<Item>(tokens)),
+        CallbackType::ForeignItem => Some(parse_many::<ForeignItem>(tokens)),
+        CallbackType::ImplItem => Some(parse_many::<ImplItem>(tokens)),
+        CallbackType::TraitItem => Some(parse_many::<TraitItem>(tokens)),
+        CallbackType::Stmt | CallbackType::Stmts => {
+            Some(Block::parse_within.parse2(tokens).map(|_| ()))
+        }
+        CallbackType::Local => Some(get_stmt(tokens).map(|_| ())),
+        CallbackType::LocalElse => Some(parse_local_else(tokens).map(|_| ())),
+        CallbackType::PatIdentSubpat => Some(get_subpat(tokens).map(|_| ())),
+        CallbackType::BinOp | CallbackType::UnOp | CallbackType::LetChain => {
+            Some(parse2::<Expr>(tokens).map(|_| ()))
+        }
+        category => parse_node_of(category, tokens),
+    }
+}
+
+/// Parse the `else` block of a let-else statement.
+fn parse_local_else(tokens: TokenStream) -> Result<(Token![else], Block)> {
+    let parser = |input: parse::ParseStream| -> Result<(Token![else], Block)> {
+        Ok((input.parse()?, input.parse()?))
+    };
+
+    parser.parse2(tokens)
+}
+
+/// The macro to use in procedural macros when parsing with
+/// [`Moisture`](Moisture).
+///
+/// *moisture* is the [`Moisture`](Moisture) structure, *callback_ty* is the
+/// [`CallbackType`](CallbackType) to issue, and *tokens* is a
+/// [`TokenStream`](TokenStream). This effectively calls
+/// [`Moisture::traverse`](Moisture::traverse) and returns a compile error,
+/// noting the path of callbacks that raised it, if an error occurred in
+/// parsing. Every argument can be an expression.
+///
+/// An initial [`Context`](Context) can be given before *callback_ty*, otherwise
+/// the callback is issued with an empty one. To work with
+/// [`proc_macro`](proc_macro) token streams directly, see
+/// [`Moisture::run`](Moisture::run).
+#[macro_export]
+macro_rules! run_moisture {
+    ($moisture:expr, $callback_ty:expr, $tokens:expr) => {
+        $crate::run_moisture!($moisture, &$crate::Context::new(), $callback_ty, $tokens)
+    };
+    ($moisture:expr, $context:expr, $callback_ty:expr, $tokens:expr) => {
+        match $moisture.traverse($context, $callback_ty, $tokens) {
+            Ok(new_tokens) => new_tokens,
+            Err(e) => e.to_compile_error(),
+        }
+    };
+}
+
+/// Get a single outer or inner [`Attribute`](syn::Attribute) object from a
+/// [`TokenStream`](TokenStream).
+pub fn get_attribute(tokens: TokenStream) -> Result<Attribute> {
+    let parser = |input: parse::ParseStream| -> Result<Vec<Attribute>> {
+        if input.peek(Token![#]) && input.peek2(Token![!]) {
+            Attribute::parse_inner(input)
         } else {
-            Err(Error::new(
-                Span::call_site(),
-                format!("couldn't find function for callback type {:?}", ty),
-            ))
+            Attribute::parse_outer(input)
         }
+    };
+    let mut attrs = parser.parse2(tokens.clone())?;
+
+    if attrs.len() == 1 {
+        Ok(attrs.remove(0))
+    } else {
+        Err(Error::new(tokens.span(), "expected a single attribute"))
     }
-    pub fn file(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<File>(tokens)?;
-        let mut tokens = TokenStream::new();
+}
+
+/// Put the outer attributes *attrs* in front of the attributes of *pat*.
+///
+/// [syn](syn) only parses the attributes of patterns that are the inputs of a
+/// closure, such as `#[cfg(test)] x` in `|#[cfg(test)] x| x`.
+fn with_pat_attrs(mut pat: Pat, attrs: Vec<Attribute>, span: Span) -> Result<Pat> {
+    if attrs.is_empty() {
+        return Ok(pat);
+    }
+
+    let pat_attrs = match &mut pat {
+        Pat::Box(pat) => &mut pat.attrs,
+        Pat::Ident(pat) => &mut pat.attrs,
+        Pat::Lit(pat) => &mut pat.attrs,
+        Pat::Macro(pat) => &mut pat.attrs,
+        Pat::Or(pat) => &mut pat.attrs,
+        Pat::Path(pat) => &mut pat.attrs,
+        Pat::Range(pat) => &mut pat.attrs,
+        Pat::Reference(pat) => &mut pat.attrs,
+        Pat::Rest(pat) => &mut pat.attrs,
+        Pat::Slice(pat) => &mut pat.attrs,
+        Pat::Struct(pat) => &mut pat.attrs,
+        Pat::Tuple(pat) => &mut pat.attrs,
+        Pat::TupleStruct(pat) => &mut pat.attrs,
+        Pat::Type(pat) => &mut pat.attrs,
+        Pat::Wild(pat) => &mut pat.attrs,
+        _ => return Err(Error::new(span, "unexpected attributes on pattern")),
+    };
+    pat_attrs.splice(0..0, attrs);
 
-        let mut new_attrs = Vec::<TokenStream>::new();
+    Ok(pat)
+}
+
+/// Get a [`PatType`](syn::PatType) object from a [`TokenStream`](TokenStream).
+///
+/// Because [syn](syn) doesn't provide this parsing functionality, the pattern
+/// is parsed as the pattern of a stub `let` statement. Outer attributes, which
+/// [syn](syn) only parses on the inputs of closures, are accepted as well.
+pub fn get_pat_type(tokens: TokenStream) -> Result<PatType> {
+    let parser = |input: parse::ParseStream| -> Result<(Vec<Attribute>, TokenStream)> {
+        Ok((input.call(Attribute::parse_outer)?, input.parse()?))
+    };
+    let (attrs, tokens) = parser.parse2(tokens)?;
+    let stub = quote! { let #tokens ; };
+    let stmt = parse2::<Stmt>(stub)?;
+    let stmt_span = stmt.span();
+    let local_data;
+
+    if let Stmt::Local(local) = stmt {
+        local_data = local;
+    } else {
+        return Err(Error::new(
+            stmt_span,
+            "expected Local statement in PatType interpretation",
+        ));
+    }
+
+    if let Pat::Type(mut pat) = local_data.pat {
+        pat.attrs = attrs;
+        Ok(pat)
+    } else {
+        Err(Error::new(
+            stmt_span,
+            "expected PatType object in Local statement pattern",
+        ))
+    }
+}
+
+/// Get a [`Stmt`](syn::Stmt) object from a [`TokenStream`](TokenStream).
+///
+/// [syn](syn)'s [`Stmt`](syn::Stmt) parser requires expressions to end with a
+/// semicolon, so the tokens are parsed the way the statements of a block are
+/// in order to accept trailing expressions such as the `a` in `{ a }`.
+pub fn get_stmt(tokens: TokenStream) -> Result<Stmt> {
+    let mut stmts = Block::parse_within.parse2(tokens.clone())?;
+
+    if stmts.len() != 1 {
+        return Err(Error::new(tokens.span(), "expected a single Stmt object"));
+    }
+
+    Ok(stmts.remove(0))
+}
 
-        for attr in &parsed.attrs {
-            new_attrs.push(attr.to_token_stream());
+/// Parse a pattern which may be a top-level or-pattern, such as the pattern of
+/// a match arm or a `let` statement.
+fn multi_pat(input: parse::ParseStream) -> Result<Pat> {
+    let attrs = input.call(Attribute::parse_outer)?;
+    let pat = multi_pat_cases(input)?;
+
+    with_pat_attrs(pat, attrs, input.span())
+}
+
+/// Parse the cases of a pattern which may be a top-level or-pattern.
+fn multi_pat_cases(input: parse::ParseStream) -> Result<Pat> {
+    let leading_vert: Option<Token![|]> = input.parse()?;
+    let mut cases = punctuated::Punctuated::<Pat, Token![|]>::new();
+
+    cases.push_value(input.parse()?);
+
+    while input.peek(Token![|]) && !input.peek(Token![||]) && !input.peek(Token![|=]) {
+        cases.push_punct(input.parse()?);
+        cases.push_value(input.parse()?);
+    }
+
+    if leading_vert.is_none() && cases.len() == 1 {
+        Ok(cases.into_iter().next().unwrap())
+    } else {
+        Ok(Pat::Or(PatOr {
+            attrs: Vec::new(),
+            leading_vert,
+            cases,
+        }))
+    }
+}
+
+/// Get a [`Pat`](syn::Pat) object from a [`TokenStream`](TokenStream).
+///
+/// Unlike parsing a [`Pat`](syn::Pat) directly, this accepts top-level
+/// or-patterns such as `Some(0) | None`, typed patterns such as `x: u8` and
+/// outer attributes, which [syn](syn) only parses as part of their parent.
+pub fn get_pat(tokens: TokenStream) -> Result<Pat> {
+    match multi_pat.parse2(tokens.clone()) {
+        Ok(pat) => Ok(pat),
+        Err(error) => get_pat_type(tokens).map(Pat::Type).map_err(|_| error),
+    }
+}
+
+/// Get a [`FieldPat`](syn::FieldPat) object from a
+/// [`TokenStream`](TokenStream).
+///
+/// Because [syn](syn) doesn't provide this parsing functionality, the field is
+/// parsed inside of a stub struct pattern.
+pub fn get_field_pat(tokens: TokenStream) -> Result<FieldPat> {
+    let tokens_span = tokens.span();
+    let stub = quote! { Stub { #tokens } };
+
+    if let Pat::Struct(PatStruct {
+        fields,
+        dot2_token: None,
+        ..
+    }) = get_pat(stub)?
+    {
+        if fields.len() == 1 {
+            return Ok(fields.into_iter().next().unwrap());
         }
+    }
 
-        tokens.extend(new_attrs.into_iter());
+    Err(Error::new(tokens_span, "expected a single FieldPat object"))
+}
 
-        let mut new_items = Vec::<TokenStream>::new();
+/// Get the `@` token and the pattern of an identifier's subpattern from a
+/// [`TokenStream`](TokenStream), such as `@ Some(_)` in `x @ Some(_)`.
+pub fn get_subpat(tokens: TokenStream) -> Result<(Token![@], Pat)> {
+    let parser = |input: parse::ParseStream| -> Result<(Token![@], Pat)> {
+        Ok((input.parse()?, input.parse()?))
+    };
 
-        for item in &parsed.items {
-            let item_stream = self.callback(context, CallbackType::Item, item.to_token_stream())?;
-            new_items.push(item_stream);
+    parser.parse2(tokens)
+}
+
+/// Get a let-else statement from a [`TokenStream`](TokenStream), such as
+/// `let Some(x) = opt else { return };`.
+///
+/// [syn](syn) doesn't understand let-else statements, so the statement is
+/// split into a [`Local`](syn::Local) without its `else` block, the `else`
+/// token and the diverging block.
+pub fn get_let_else(tokens: TokenStream) -> Result<(Local, Token![else], Block)> {
+    let parser = |input: parse::ParseStream| -> Result<(Local, Token![else], Block)> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let let_token: Token![let] = input.parse()?;
+        let mut pat = multi_pat(input)?;
+
+        if input.peek(Token![:]) {
+            pat = Pat::Type(PatType {
+                attrs: Vec::new(),
+                pat: Box::new(pat),
+                colon_token: input.parse()?,
+                ty: input.parse()?,
+            });
         }
 
-        tokens.extend(new_items.into_iter());
+        let eq_token: Token![=] = input.parse()?;
+        let init: Expr = input.parse()?;
+        let else_token: Token![else] = input.parse()?;
+        let block: Block = input.parse()?;
+        let semi_token: Token![;] = input.parse()?;
 
-        Ok(tokens)
+        let local = Local {
+            attrs,
+            let_token,
+            pat,
+            init: Some((eq_token, Box::new(init))),
+            semi_token,
+        };
+
+        Ok((local, else_token, block))
+    };
+
+    parser.parse2(tokens)
+}
+
+/// Get the conditions of a `let` chain from a [`TokenStream`](TokenStream),
+/// such as `let Some(x) = a` and `x > 1` in `let Some(x) = a && x > 1`.
+///
+/// A let chain is a series of conditions joined by `&&`, at least one of
+/// which is a `let` expression.
+pub fn get_let_chain(tokens: TokenStream) -> Result<Vec<Expr>> {
+    let parsed = parse2::<Expr>(tokens)?;
+
+    if !is_let_chain(&parsed) {
+        return Err(Error::new(parsed.span(), "expected let chain"));
     }
-    pub fn item(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<Item>(tokens)?;
 
-        let result = match parsed {
-            Item::Const(ref const_) => {
-                self.callback(context, CallbackType::ItemConst, const_.to_token_stream())
-            }
-            Item::Enum(ref enum_) => {
-                self.callback(context, CallbackType::ItemEnum, enum_.to_token_stream())
-            }
-            Item::ExternCrate(ref crate_) => self.callback(
-                context,
-                CallbackType::ItemExternCrate,
-                crate_.to_token_stream(),
-            ),
-            Item::Fn(ref fn_) => {
-                self.callback(context, CallbackType::ItemFn, fn_.to_token_stream())
-            }
-            Item::ForeignMod(ref mod_) => self.callback(
-                context,
-                CallbackType::ItemForeignMod,
-                mod_.to_token_stream(),
-            ),
-            Item::Impl(ref impl_) => {
-                self.callback(context, CallbackType::ItemImpl, impl_.to_token_stream())
-            }
-            Item::Macro(ref macro_) => {
-                self.callback(context, CallbackType::ItemMacro, macro_.to_token_stream())
-            }
-            Item::Macro2(ref macro_) => {
-                self.callback(context, CallbackType::ItemMacro2, macro_.to_token_stream())
-            }
-            Item::Mod(ref mod_) => {
-                self.callback(context, CallbackType::ItemMod, mod_.to_token_stream())
-            }
-            Item::Static(ref static_) => {
-                self.callback(context, CallbackType::ItemStatic, static_.to_token_stream())
-            }
-            Item::Struct(ref struct_) => {
-                self.callback(context, CallbackType::ItemStruct, struct_.to_token_stream())
+    let mut conditions = Vec::<Expr>::new();
+    flatten_let_chain(parsed, &mut conditions);
+
+    Ok(conditions)
+}
+
+fn is_let_chain(expr: &Expr) -> bool {
+    fn contains_let(expr: &Expr) -> bool {
+        match expr {
+            Expr::Let(_) => true,
+            Expr::Binary(ExprBinary {
+                left,
+                op: BinOp::And(_),
+                right,
+                ..
+            }) => contains_let(left) || contains_let(right),
+            _ => false,
+        }
+    }
+
+    matches!(
+        expr,
+        Expr::Binary(ExprBinary {
+            op: BinOp::And(_),
+            ..
+        })
+    ) && contains_let(expr)
+}
+
+fn flatten_let_chain(expr: Expr, conditions: &mut Vec<Expr>) {
+    match expr {
+        Expr::Binary(ExprBinary {
+            left,
+            op: BinOp::And(_),
+            right,
+            ..
+        }) => {
+            flatten_let_chain(*left, conditions);
+            flatten_let_chain(*right, conditions);
+        }
+        condition => conditions.push(condition),
+    }
+}
+
+/// Get the callback type for the condition of an `if` or `while` expression.
+fn cond_type(cond: &Expr) -> CallbackType {
+    if is_let_chain(cond) {
+        CallbackType::LetChain
+    } else {
+        CallbackType::Expr
+    }
+}
+
+/// Collect the variables bound by a pattern, with the declared type of the
+/// pattern if there's one.
+fn pat_bindings(pat: &Pat, ty: Option<&Type>, site: BindingSite, bindings: &mut Vec<LocalBinding>) {
+    match pat {
+        Pat::Ident(PatIdent {
+            mutability,
+            ident,
+            subpat,
+            ..
+        }) => {
+            bindings.push(LocalBinding {
+                ident: ident.clone(),
+                mutable: mutability.is_some(),
+                ty: ty.cloned(),
+                site,
+            });
+
+            if let Some((_, subpat)) = subpat {
+                pat_bindings(subpat, None, site, bindings);
             }
-            Item::Trait(ref trait_) => {
-                self.callback(context, CallbackType::ItemTrait, trait_.to_token_stream())
+        }
+        Pat::Type(PatType { pat, ty, .. }) => pat_bindings(pat, Some(ty), site, bindings),
+        Pat::Box(PatBox { pat, .. }) | Pat::Reference(PatReference { pat, .. }) => {
+            pat_bindings(pat, None, site, bindings)
+        }
+        // every case of an or-pattern binds the same variables
+        Pat::Or(PatOr { cases, .. }) => {
+            if let Some(case) = cases.first() {
+                pat_bindings(case, None, site, bindings);
             }
-            Item::TraitAlias(ref alias) => self.callback(
-                context,
-                CallbackType::ItemTraitAlias,
-                alias.to_token_stream(),
-            ),
-            Item::Type(ref type_) => {
-                self.callback(context, CallbackType::ItemType, type_.to_token_stream())
+        }
+        Pat::Slice(PatSlice { elems, .. }) | Pat::Tuple(PatTuple { elems, .. }) => {
+            for elem in elems {
+                pat_bindings(elem, None, site, bindings);
             }
-            Item::Union(ref union) => {
-                self.callback(context, CallbackType::ItemUnion, union.to_token_stream())
+        }
+        Pat::TupleStruct(PatTupleStruct { pat, .. }) => {
+            for elem in &pat.elems {
+                pat_bindings(elem, None, site, bindings);
             }
-            Item::Use(ref use_) => {
-                self.callback(context, CallbackType::ItemUse, use_.to_token_stream())
+        }
+        Pat::Struct(PatStruct { fields, .. }) => {
+            for field in fields {
+                pat_bindings(&field.pat, None, site, bindings);
             }
+        }
+        _ => (),
+    }
+}
 
-            Item::Verbatim(ref vert_tokens) => {
-                self.callback(context, CallbackType::Verbatim, vert_tokens.clone())
-            }
-            _ => Ok(parsed.to_token_stream()),
-        }?;
+/// Get the patterns of the `let` expressions of an `if` or `while` condition.
+fn let_patterns(cond: &Expr) -> Vec<Pat> {
+    match cond {
+        Expr::Let(ExprLet { pat, .. }) => vec![pat.clone()],
+        _ if is_let_chain(cond) => get_let_chain(cond.to_token_stream())
+            .unwrap_or_default()
+            .iter()
+            .flat_map(let_patterns)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Replace the `self` and `super` segments at the start of a path with the
+/// path of the module they refer to, given the path of the current module.
+fn absolute_path(path: &Path, module_path: &[Ident]) -> Option<Path> {
+    let mut module_path = module_path.to_vec();
+    let mut segments = path.segments.iter().peekable();
+
+    if segments.peek()?.ident == "self" {
+        segments.next();
+    }
+
+    while segments
+        .next_if(|segment| segment.ident == "super")
+        .is_some()
+    {
+        // super of the root module is outside of the tree
+        module_path.pop()?;
+    }
+
+    let mut new_path = Path::from(Ident::new("crate", Span::call_site()));
+
+    for ident in module_path {
+        new_path.segments.push(PathSegment::from(ident));
+    }
+
+    for segment in segments {
+        new_path.segments.push(segment.clone());
+    }
+
+    Some(new_path)
+}
+
+/// Collect the names declared by an item with the paths they stand for.
+fn item_paths(item: &Item, module_path: &[Ident], paths: &mut HashMap<String, Path>) {
+    let ident = match item {
+        Item::Const(ItemConst { ident, .. })
+        | Item::Enum(ItemEnum { ident, .. })
+        | Item::Fn(ItemFn {
+            sig: Signature { ident, .. },
+            ..
+        })
+        | Item::Mod(ItemMod { ident, .. })
+        | Item::Static(ItemStatic { ident, .. })
+        | Item::Struct(ItemStruct { ident, .. })
+        | Item::Trait(ItemTrait { ident, .. })
+        | Item::TraitAlias(ItemTraitAlias { ident, .. })
+        | Item::Type(ItemType { ident, .. })
+        | Item::Union(ItemUnion { ident, .. }) => ident,
+        Item::ExternCrate(ItemExternCrate { ident, rename, .. }) => {
+            let name = rename.as_ref().map_or(ident, |(_, rename)| rename);
+            let mut path = Path::from(ident.clone());
+            path.leading_colon = Some(Default::default());
+            paths.insert(name.unraw().to_string(), path);
+            return;
+        }
+        Item::Use(ItemUse {
+            leading_colon,
+            tree,
+            ..
+        }) => {
+            let prefix = Path {
+                leading_colon: *leading_colon,
+                segments: punctuated::Punctuated::new(),
+            };
+            use_paths(prefix, tree, module_path, paths);
+            return;
+        }
+        _ => return,
+    };
+
+    let mut path = Path::from(Ident::new("crate", Span::call_site()));
+
+    for segment in module_path.iter().chain(Some(ident)) {
+        path.segments.push(PathSegment::from(segment.clone()));
+    }
+
+    paths.insert(ident.unraw().to_string(), path);
+}
+
+/// Collect the names declared by a use tree with the paths they stand for,
+/// given the path leading to the tree.
+fn use_paths(
+    prefix: Path,
+    tree: &UseTree,
+    module_path: &[Ident],
+    paths: &mut HashMap<String, Path>,
+) {
+    let (ident, name) = match tree {
+        UseTree::Path(UsePath { ident, tree, .. }) => {
+            let mut prefix = prefix;
+            prefix.segments.push(PathSegment::from(ident.clone()));
+            use_paths(prefix, tree, module_path, paths);
+            return;
+        }
+        UseTree::Group(UseGroup { items, .. }) => {
+            for item in items {
+                use_paths(prefix.clone(), item, module_path, paths);
+            }
+            return;
+        }
+        UseTree::Name(UseName { ident }) => (ident, ident),
+        UseTree::Rename(UseRename { ident, rename, .. }) => (ident, rename),
+        // the names of glob imports aren't known
+        UseTree::Glob(_) => return,
+    };
+
+    let mut path = prefix;
+
+    // `self` in a group imports the module leading to the group
+    if ident != "self" {
+        path.segments.push(PathSegment::from(ident.clone()));
+    }
+
+    let name = if name == "self" {
+        match path.segments.last() {
+            Some(segment) => segment.ident.clone(),
+            None => return,
+        }
+    } else {
+        name.clone()
+    };
+
+    if name == "_" {
+        return;
+    }
+
+    let path = match path.segments.first() {
+        Some(first) if first.ident == "self" || first.ident == "super" => {
+            match absolute_path(&path, module_path) {
+                Some(path) => path,
+                None => return,
+            }
+        }
+        _ => path,
+    };
+
+    paths.insert(name.unraw().to_string(), path);
+}
+
+/// Get the callback type for an expression [syn](syn) only knows as verbatim
+/// tokens.
+fn verbatim_expr_type(tokens: &TokenStream) -> CallbackType {
+    let mut iter = tokens.clone().into_iter();
+
+    match (iter.next(), iter.next(), iter.next()) {
+        (Some(TokenTree::Ident(ident)), None, None) if ident == "_" => CallbackType::ExprInfer,
+        (Some(TokenTree::Ident(ident)), Some(TokenTree::Group(group)), None)
+            if ident == "const" && group.delimiter() == Delimiter::Brace =>
+        {
+            CallbackType::ExprConst
+        }
+        (Some(TokenTree::Ident(ident)), Some(TokenTree::Punct(punct)), _)
+            if ident == "for" && punct.as_char() == '<' =>
+        {
+            CallbackType::ExprClosure
+        }
+        _ => CallbackType::Verbatim,
+    }
+}
+
+/// Get a [`Path`](syn::Path) object from a [`TokenStream`](TokenStream).
+///
+/// [syn](syn)'s [`Path`](syn::Path) parser doesn't understand parenthesized
+/// arguments such as `Fn(u8) -> u8`, so the tokens are parsed as a
+/// [`TypePath`](syn::TypePath) instead. An error is returned if the path is
+/// qualified.
+pub fn get_path(tokens: TokenStream) -> Result<Path> {
+    let TypePath { qself, path } = parse2::<TypePath>(tokens)?;
+
+    if let Some(qself) = qself {
+        Err(Error::new(
+            qself.lt_token.span(),
+            "expected Path object, got qualified path",
+        ))
+    } else {
+        Ok(path)
+    }
+}
+
+/// Get a [`PathSegment`](syn::PathSegment) object from a
+/// [`TokenStream`](TokenStream).
+///
+/// Like [`get_path`](get_path), this accepts parenthesized arguments.
+pub fn get_path_segment(tokens: TokenStream) -> Result<PathSegment> {
+    let path = get_path(tokens)?;
+    let path_span = path.span();
+
+    if path.leading_colon.is_some() || path.segments.len() != 1 {
+        return Err(Error::new(
+            path_span,
+            "expected a single PathSegment object",
+        ));
+    }
+
+    Ok(path.segments.into_iter().next().unwrap())
+}
+
+/// Get a [`Field`](syn::Field) object from a [`TokenStream`](TokenStream).
+///
+/// Because [syn](syn) doesn't implement [`Parse`](syn::parse::Parse) for
+/// fields, the tokens are first parsed as a named field and then as an unnamed
+/// field.
+pub fn get_field(tokens: TokenStream) -> Result<Field> {
+    Field::parse_named
+        .parse2(tokens.clone())
+        .or_else(|_| Field::parse_unnamed.parse2(tokens))
+}
+
+/// Get a [`Fields`](syn::Fields) object from a [`TokenStream`](TokenStream).
+///
+/// Empty tokens are the fields of a unit struct or variant, braced tokens are
+/// named fields and parenthesized tokens are unnamed fields.
+pub fn get_fields(tokens: TokenStream) -> Result<Fields> {
+    if tokens.is_empty() {
+        Ok(Fields::Unit)
+    } else if let Ok(named) = parse2::<FieldsNamed>(tokens.clone()) {
+        Ok(Fields::Named(named))
+    } else {
+        Ok(Fields::Unnamed(parse2::<FieldsUnnamed>(tokens)?))
+    }
+}
+
+/// Get a [`Label`](syn::Label) object from a [`TokenStream`](TokenStream).
+///
+/// The colon of the label is optional, since
+/// [`CallbackType::Label`](CallbackType::Label) callbacks are given the name
+/// of the label without it.
+pub fn get_label(tokens: TokenStream) -> Result<Label> {
+    let (name, colon_token) = label_name.parse2(tokens)?;
+
+    Ok(Label {
+        name,
+        colon_token: colon_token.unwrap_or_default(),
+    })
+}
+
+/// Parse the name of a label followed by its optional colon.
+fn label_name(input: parse::ParseStream) -> Result<(Lifetime, Option<Token![:]>)> {
+    Ok((input.parse()?, input.parse()?))
+}
+
+/// Get a [`QualifiedPath`](QualifiedPath) object from a
+/// [`TokenStream`](TokenStream). An error is returned if the path isn't
+/// qualified.
+pub fn get_qualified_path(tokens: TokenStream) -> Result<QualifiedPath> {
+    let TypePath { qself, path } = parse2::<TypePath>(tokens)?;
+
+    match qself {
+        Some(qself) => Ok(QualifiedPath { qself, path }),
+        None => Err(Error::new(
+            path.span(),
+            "expected qualified path, got Path object",
+        )),
+    }
+}
+
+/// Get the operands and operator of a binary operation from a
+/// [`TokenStream`](TokenStream).
+///
+/// This accepts both binary expressions such as `a + b` and compound
+/// assignments such as `a += b`, which is what
+/// [`CallbackType::BinOp`](CallbackType::BinOp) callbacks receive.
+pub fn get_bin_op(tokens: TokenStream) -> Result<(Expr, BinOp, Expr)> {
+    let parsed = parse2::<Expr>(tokens)?;
+
+    match parsed {
+        Expr::Binary(ExprBinary {
+            left, op, right, ..
+        }) => Ok((*left, op, *right)),
+        Expr::AssignOp(ExprAssignOp {
+            left, op, right, ..
+        }) => Ok((*left, op, *right)),
+        _ => Err(Error::new(parsed.span(), "expected binary operation")),
+    }
+}
+
+/// Get the operator and operand of a unary operation from a
+/// [`TokenStream`](TokenStream).
+pub fn get_un_op(tokens: TokenStream) -> Result<(UnOp, Expr)> {
+    let parsed = parse2::<Expr>(tokens)?;
+
+    if let Expr::Unary(ExprUnary { op, expr, .. }) = parsed {
+        Ok((op, *expr))
+    } else {
+        Err(Error::new(parsed.span(), "expected unary operation"))
+    }
+}
+
+/// The precedence of an expression, from the loosest to the tightest binding.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence {
+    Jump,
+    Assign,
+    Range,
+    Or,
+    And,
+    Compare,
+    BitOr,
+    BitXor,
+    BitAnd,
+    Shift,
+    Arithmetic,
+    Term,
+    Cast,
+    Prefix,
+    Postfix,
+}
+impl Precedence {
+    fn of_op(op: &BinOp) -> Self {
+        match op {
+            BinOp::Add(_) | BinOp::Sub(_) => Precedence::Arithmetic,
+            BinOp::Mul(_) | BinOp::Div(_) | BinOp::Rem(_) => Precedence::Term,
+            BinOp::And(_) => Precedence::And,
+            BinOp::Or(_) => Precedence::Or,
+            BinOp::BitXor(_) => Precedence::BitXor,
+            BinOp::BitAnd(_) => Precedence::BitAnd,
+            BinOp::BitOr(_) => Precedence::BitOr,
+            BinOp::Shl(_) | BinOp::Shr(_) => Precedence::Shift,
+            BinOp::Eq(_)
+            | BinOp::Lt(_)
+            | BinOp::Le(_)
+            | BinOp::Ne(_)
+            | BinOp::Ge(_)
+            | BinOp::Gt(_) => Precedence::Compare,
+            _ => Precedence::Assign,
+        }
+    }
+    fn of_expr(expr: &Expr) -> Self {
+        match expr {
+            Expr::Closure(_) | Expr::Return(_) | Expr::Break(_) | Expr::Yield(_) => {
+                Precedence::Jump
+            }
+            Expr::Assign(_) | Expr::AssignOp(_) => Precedence::Assign,
+            Expr::Range(_) => Precedence::Range,
+            Expr::Binary(binary) => Precedence::of_op(&binary.op),
+            Expr::Cast(_) | Expr::Type(_) => Precedence::Cast,
+            Expr::Unary(_) | Expr::Reference(_) | Expr::Box(_) => Precedence::Prefix,
+            // `let` only occurs in conditions, where parentheses would change
+            // its meaning
+            _ => Precedence::Postfix,
+        }
+    }
+    /// The next tighter precedence, required by operands on the side an
+    /// operator doesn't associate to.
+    fn tighter(self) -> Self {
+        match self {
+            Precedence::Jump => Precedence::Assign,
+            Precedence::Assign => Precedence::Range,
+            Precedence::Range => Precedence::Or,
+            Precedence::Or => Precedence::And,
+            Precedence::And => Precedence::Compare,
+            Precedence::Compare => Precedence::BitOr,
+            Precedence::BitOr => Precedence::BitXor,
+            Precedence::BitXor => Precedence::BitAnd,
+            Precedence::BitAnd => Precedence::Shift,
+            Precedence::Shift => Precedence::Arithmetic,
+            Precedence::Arithmetic => Precedence::Term,
+            Precedence::Term => Precedence::Cast,
+            Precedence::Cast => Precedence::Prefix,
+            Precedence::Prefix | Precedence::Postfix => Precedence::Postfix,
+        }
+    }
+    /// The precedences required by the left and right operands of *op*.
+    fn of_operands(op: &BinOp) -> (Self, Self) {
+        match Precedence::of_op(op) {
+            // assignments associate to the right
+            Precedence::Assign => (Precedence::Range, Precedence::Assign),
+            // comparisons don't associate at all
+            Precedence::Compare => (Precedence::BitOr, Precedence::BitOr),
+            precedence => (precedence, precedence.tighter()),
+        }
+    }
+}
+
+/// Wrap the expression in *tokens* in parentheses if it binds more loosely
+/// than *precedence*.
+///
+/// Tokens that don't parse as an expression are returned unchanged.
+fn parenthesize(tokens: TokenStream, precedence: Precedence) -> TokenStream {
+    match parse2::<Expr>(tokens.clone()) {
+        Ok(expr) if Precedence::of_expr(&expr) < precedence => quote! { ( #tokens ) },
+        _ => tokens,
+    }
+}
+
+/// Whether *a* and *b* are the same tokens, ignoring their spans.
+fn same_tokens(a: &TokenStream, b: &TokenStream) -> bool {
+    let mut a = a.clone().into_iter();
+    let mut b = b.clone().into_iter();
+
+    loop {
+        match (a.next(), b.next()) {
+            (None, None) => return true,
+            (Some(TokenTree::Group(a)), Some(TokenTree::Group(b)))
+                if a.delimiter() == b.delimiter() && same_tokens(&a.stream(), &b.stream()) => {}
+            (Some(TokenTree::Ident(a)), Some(TokenTree::Ident(b))) if a == b => {}
+            (Some(TokenTree::Punct(a)), Some(TokenTree::Punct(b)))
+                if a.as_char() == b.as_char() && a.spacing() == b.spacing() => {}
+            // literals have no structure beyond their text
+            (Some(TokenTree::Literal(a)), Some(TokenTree::Literal(b)))
+                if a.to_string() == b.to_string() => {}
+            _ => return false,
+        }
+    }
+}
+
+/// Rebuild a punctuated sequence out of transformed *items*, reusing the
+/// punctuation of the *original* sequence so that trailing punctuation is
+/// preserved.
+fn zip_punctuated<T, P: ToTokens>(
+    original: &punctuated::Punctuated<T, P>,
+    items: Vec<TokenStream>,
+) -> TokenStream {
+    let mut tokens = TokenStream::new();
+
+    for (pair, item) in original.pairs().zip(items) {
+        tokens.extend(item);
+
+        if let Some(punct) = pair.punct() {
+            punct.to_tokens(&mut tokens);
+        }
+    }
+
+    tokens
+}
+
+/// Put *inner* attributes back at the start of a transformed block.
+///
+/// If the block is no longer a single braced group, it is returned unchanged.
+fn inner_block(inner: TokenStream, block: TokenStream) -> TokenStream {
+    if inner.is_empty() {
+        return block;
+    }
+
+    let mut trees = block.clone().into_iter();
+
+    match (trees.next(), trees.next()) {
+        (Some(TokenTree::Group(group)), None) if group.delimiter() == Delimiter::Brace => {
+            let stream = group.stream();
+            let mut new_group = Group::new(Delimiter::Brace, quote! { #inner #stream });
+            new_group.set_span(group.span());
+
+            new_group.to_token_stream()
+        }
+        _ => block,
+    }
+}
+
+/// Render the path of a macro invocation for matching against the routes of
+/// [`Moisture::route_macro`](Moisture::route_macro).
+fn macro_path_string(path: &Path) -> String {
+    let segments = path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect::<Vec<_>>()
+        .join("::");
+
+    if path.leading_colon.is_some() {
+        format!("::{}", segments)
+    } else {
+        segments
+    }
+}
+
+/// Wrap the *tokens* of a macro's body in the macro's original delimiter.
+fn macro_body(mac: &Macro, tokens: TokenStream) -> TokenStream {
+    let (delimiter, span) = match mac.delimiter {
+        MacroDelimiter::Paren(ref paren) => (Delimiter::Parenthesis, paren.span),
+        MacroDelimiter::Brace(ref brace) => (Delimiter::Brace, brace.span),
+        MacroDelimiter::Bracket(ref bracket) => (Delimiter::Bracket, bracket.span),
+    };
+    let mut group = Group::new(delimiter, tokens);
+    group.set_span(span);
+
+    group.to_token_stream()
+}
+
+/// The structure which holds and processes the callbacks.
+///
+/// Default callbacks are 1:1 named after their [syn](syn) counterparts, with
+/// the exception of [`Moisture::stmts`](Moisture::stmts), which is a helper
+/// callback for a series of statements.
+#[derive(Clone)]
+pub struct Moisture {
+    callbacks: HashMap<CallbackType, Callback>,
+    custom_defaults: HashMap<&'static str, Callback>,
+    wildcard: Option<Wildcard>,
+    macro_routes: HashMap<String, &'static str>,
+    registered: HashSet<CallbackType>,
+    validate: bool,
+}
+impl Moisture {
+    /// Create a new `Moisture` object with default callbacks registered.
+    pub fn new() -> Self {
+        let mut result = Self {
+            callbacks: HashMap::<CallbackType, Callback>::new(),
+            custom_defaults: HashMap::<&'static str, Callback>::new(),
+            wildcard: None,
+            macro_routes: HashMap::<String, &'static str>::new(),
+            registered: HashSet::<CallbackType>::new(),
+            validate: false,
+        };
+        result.load_defaults();
+        result
+    }
+    /// Register a [`Callback`](Callback) for the given
+    /// [`CallbackType`](CallbackType).
+    ///
+    /// This overwrites the default callback in the structure when an item is
+    /// parsed.
+    pub fn register_callback(&mut self, ty: CallbackType, callback: Callback) {
+        self.callbacks.insert(ty, callback);
+        self.registered.insert(ty);
+    }
+    /// Register a custom node kind named *name*, with *default* as its
+    /// default callback.
+    ///
+    /// The default callback parses the node and traverses it. It is registered
+    /// as the callback for [`CallbackType::Custom`](CallbackType::Custom), and
+    /// remains available through [`Moisture::custom`](Moisture::custom) when
+    /// another callback is registered over it.
+    pub fn register_custom(&mut self, name: &'static str, default: Callback) {
+        self.custom_defaults.insert(name, default);
+        self.callbacks.insert(CallbackType::Custom(name), default);
+        self.registered.remove(&CallbackType::Custom(name));
+    }
+    /// Register a [`Wildcard`](Wildcard) callback, which is issued in place of
+    /// every callback.
+    ///
+    /// The wildcard is given the [`CallbackType`](CallbackType) being issued,
+    /// with the context stack already pushed. It can either hand the node to the
+    /// callback registered for the type with
+    /// [`Moisture::delegate`](Moisture::delegate), or replace it entirely.
+    pub fn register_wildcard(&mut self, wildcard: Wildcard) {
+        self.wildcard = Some(wildcard);
+    }
+    /// Remove the [`Wildcard`](Wildcard) callback, if one is registered.
+    pub fn clear_wildcard(&mut self) {
+        self.wildcard = None;
+    }
+    /// Enable or disable validation of the output of every callback.
+    ///
+    /// When enabled, the output of each callback is parsed as what its parent
+    /// splices in, and a callback producing anything else fails with an error
+    /// naming the callback type, the context stack and the tokens it produced.
+    /// A kind such as [`CallbackType::ExprCall`](CallbackType::ExprCall) can
+    /// produce any node of its category, such as any expression, while items
+    /// and statements can be replaced with any number of items or statements
+    /// of the same category. Empty output, which removes a node, is always
+    /// accepted. The output of [`CallbackType::Verbatim`](CallbackType::Verbatim)
+    /// and of [`CallbackType::Custom`](CallbackType::Custom) node kinds isn't
+    /// checked.
+    ///
+    /// ```rust
+    /// use proc_macro2::TokenStream;
+    /// use quote::quote;
+    /// use syn::Result;
+    ///
+    /// use moisture::*;
+    ///
+    /// fn expr_call(_: &Moisture, _: &Context, _: TokenStream) -> Result<TokenStream> {
+    ///     Ok(quote! { let x = 1; })
+    /// }
+    ///
+    /// let mut moisture = Moisture::new();
+    /// moisture.register_callback(CallbackType::ExprCall, expr_call);
+    /// moisture.set_validation(true);
+    ///
+    /// let error = moisture
+    ///     .callback(&Context::new(), CallbackType::Expr, quote! { a * f(b) })
+    ///     .unwrap_err();
+    ///
+    /// assert!(error.to_string().starts_with("callback for ExprCall produced an invalid Expr:"));
+    /// ```
+    pub fn set_validation(&mut self, validate: bool) {
+        self.validate = validate;
+    }
+    /// Move every token of *tokens* to the location of *span*, such as the span
+    /// of the node a callback replaces.
+    ///
+    /// Tokens built by a callback, such as with `quote!`, have the call-site
+    /// span, so diagnostics on them point at the macro invocation. Respanning
+    /// them with [`Span::located_at`](Span::located_at) keeps their hygiene, so
+    /// only the location reported by diagnostics changes. Tokens taken from
+    /// the input already have their own location and are best interpolated
+    /// after respanning the new ones.
+    ///
+    /// ```rust
+    /// use proc_macro2::TokenStream;
+    /// use quote::quote;
+    /// use syn::Result;
+    /// use syn::spanned::Spanned;
+    ///
+    /// use moisture::*;
+    ///
+    /// fn expr_call(_: &Moisture, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    ///     Ok(Moisture::respan(quote! { b + c }, tokens.span()))
+    /// }
+    ///
+    /// let mut moisture = Moisture::new();
+    /// moisture.register_callback(CallbackType::ExprCall, expr_call);
+    ///
+    /// let tokens = quote! { a * f(x) };
+    /// let new_tokens = run_moisture!(moisture, CallbackType::Expr, tokens);
+    ///
+    /// assert_eq!(new_tokens.to_string(), quote! { a * (b + c) }.to_string());
+    /// ```
+    pub fn respan(tokens: TokenStream, span: Span) -> TokenStream {
+        tokens
+            .into_iter()
+            .map(|tree| match tree {
+                TokenTree::Group(group) => {
+                    let mut new_group =
+                        Group::new(group.delimiter(), Self::respan(group.stream(), span));
+                    new_group.set_span(group.span().located_at(span));
+                    TokenTree::Group(new_group)
+                }
+                mut tree => {
+                    tree.set_span(tree.span().located_at(span));
+                    tree
+                }
+            })
+            .collect()
+    }
+    /// Give the body of every invocation of the macro at *path* to the custom
+    /// node kind named *name*.
+    ///
+    /// *path* is matched against the path of the invocation as written, such
+    /// as `"route"` or `"web::route"`, so `"::web::route"` only matches
+    /// invocations with a leading `::`. Whitespace around the segments is
+    /// ignored. The body is given without its delimiter.
+    pub fn route_macro(&mut self, path: &str, name: &'static str) {
+        let path = path
+            .split("::")
+            .map(str::trim)
+            .collect::<Vec<_>>()
+            .join("::");
+        self.macro_routes.insert(path, name);
+    }
+    /// Issue a callback to the given [`CallbackType`](CallbackType) to parse
+    /// the given [`TokenStream`](TokenStream).
+    ///
+    /// This essentially creates a new context stack, pushes the new context
+    /// onto the new stack, then issues the call to the registered callback.
+    /// Errors are passed on as they are; see
+    /// [`Moisture::traverse`](Moisture::traverse) for their kind and the path
+    /// of callbacks that raised them.
+    pub fn callback(
+        &self,
+        context: &Context,
+        ty: CallbackType,
+        tokens: TokenStream,
+    ) -> Result<TokenStream> {
+        self.callback_with_role(context, ty, tokens, None, Slot::None)
+    }
+    /// Issue a callback like [`Moisture::callback`](Moisture::callback) from
+    /// outside of a callback, such as the entry point of a procedural macro.
+    ///
+    /// Errors are returned as [`MoistureError`](MoistureError)s, which tell
+    /// the [`ErrorKind`](ErrorKind) of the error and the path of callbacks that
+    /// raised it.
+    ///
+    /// ```rust
+    /// use proc_macro2::TokenStream;
+    /// use quote::quote;
+    /// use syn::{Error, Result};
+    ///
+    /// use moisture::*;
+    ///
+    /// fn expr_call(_: &Moisture, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    ///     Err(Error::new_spanned(tokens, "calls aren't allowed"))
+    /// }
+    ///
+    /// let mut moisture = Moisture::new();
+    /// moisture.register_callback(CallbackType::ExprCall, expr_call);
+    ///
+    /// let error = moisture
+    ///     .traverse(&Context::new(), CallbackType::Expr, quote! { f(b) })
+    ///     .unwrap_err();
+    ///
+    /// assert_eq!(error.kind(), ErrorKind::User);
+    /// assert_eq!(error.path(), &[CallbackType::Expr, CallbackType::ExprCall]);
+    /// ```
+    pub fn traverse(
+        &self,
+        context: &Context,
+        ty: CallbackType,
+        tokens: TokenStream,
+    ) -> MoistureResult<TokenStream> {
+        self.callback(context, ty, tokens)
+            .map_err(|e| context.take_failure(e))
+    }
+    fn callback_in(
+        &self,
+        context: &Context,
+        ty: CallbackType,
+        tokens: TokenStream,
+        field: &'static str,
+    ) -> Result<TokenStream> {
+        self.callback_with_role(context, ty, tokens, None, Slot::Field(field, None))
+    }
+    fn callback_at(
+        &self,
+        context: &Context,
+        ty: CallbackType,
+        tokens: TokenStream,
+        field: &'static str,
+        index: usize,
+    ) -> Result<TokenStream> {
+        let slot = Slot::Field(field, Some(index));
+        self.callback_with_role(context, ty, tokens, None, slot)
+    }
+    fn dispatch(
+        &self,
+        context: &Context,
+        ty: CallbackType,
+        tokens: TokenStream,
+    ) -> Result<TokenStream> {
+        self.callback_with_role(context, ty, tokens, None, Slot::Dispatch)
+    }
+    fn callback_with_role(
+        &self,
+        context: &Context,
+        ty: CallbackType,
+        tokens: TokenStream,
+        role: Option<IdentRole>,
+        slot: Slot,
+    ) -> Result<TokenStream> {
+        let position = match slot {
+            Slot::None => None,
+            Slot::Field(field, index) => context.stack.last().map(|(parent, _)| Position {
+                parent: *parent,
+                field,
+                index,
+            }),
+            Slot::Dispatch => context.position(),
+        };
+        let mut new_context = context.clone();
+        new_context.push_at(ty, tokens.clone(), position);
+        new_context.ident_role = role;
+
+        let output = match self.wildcard {
+            Some(wildcard) => wildcard(self, &new_context, ty, tokens.clone()),
+            None => self.delegate(&new_context, ty, tokens.clone()),
+        };
+        let output = match output {
+            Ok(output) => output,
+            Err(e) => {
+                new_context.track(ErrorKind::User, &e);
+                return Err(e);
+            }
+        };
+
+        if self.validate && !output.is_empty() {
+            if let Some(Err(e)) = validate_output(ty, output.clone()) {
+                let message = format!(
+                    "callback for {} produced an invalid {}: {}\n  output: {}",
+                    ty.name(),
+                    ty.category().unwrap_or(ty).name(),
+                    e,
+                    output
+                );
+                let error = Error::new_spanned(tokens, message);
+
+                return Err(new_context.raise(ErrorKind::Validation, error));
+            }
+        }
+
+        context.clear_failure();
+        Ok(output)
+    }
+    /// Issue a callback from the entry point of a function-like procedural
+    /// macro.
+    ///
+    /// This is [`run_moisture!`](run_moisture) for [`proc_macro`](proc_macro)
+    /// token streams: errors are returned as `compile_error!` invocations.
+    /// Items emitted with [`Context::emit`](Context::emit) are appended to the
+    /// output.
+    ///
+    /// ```rust,ignore
+    /// #[proc_macro]
+    /// pub fn my_macro(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    ///     let mut moisture = Moisture::new();
+    ///     moisture.register_callback(CallbackType::ExprCall, expr_call);
+    ///
+    ///     moisture.run(&Context::new(), CallbackType::Expr, tokens)
+    /// }
+    /// ```
+    pub fn run(
+        &self,
+        context: &Context,
+        ty: CallbackType,
+        tokens: proc_macro::TokenStream,
+    ) -> proc_macro::TokenStream {
+        let mut result = run_moisture!(self, context, ty, tokens.into());
+        result.extend(context.take_emitted());
+
+        result.into()
+    }
+    /// Issue a callback on the annotated item from the entry point of an
+    /// attribute macro.
+    ///
+    /// The arguments of the attribute are available to every callback through
+    /// [`Context::attribute_args`](Context::attribute_args). The output of the
+    /// callback replaces the item.
+    ///
+    /// ```rust,ignore
+    /// #[proc_macro_attribute]
+    /// pub fn my_attribute(
+    ///     args: proc_macro::TokenStream,
+    ///     item: proc_macro::TokenStream,
+    /// ) -> proc_macro::TokenStream {
+    ///     Moisture::new().run_attribute(&Context::new(), CallbackType::Item, args, item)
+    /// }
+    /// ```
+    pub fn run_attribute(
+        &self,
+        context: &Context,
+        ty: CallbackType,
+        args: proc_macro::TokenStream,
+        item: proc_macro::TokenStream,
+    ) -> proc_macro::TokenStream {
+        let mut new_context = context.clone();
+        new_context.attribute_args = Some(args.into());
+
+        self.run(&new_context, ty, item)
+    }
+    /// Run [`Moisture::derive`](Moisture::derive) from the entry point of a
+    /// derive macro.
+    ///
+    /// ```rust,ignore
+    /// #[proc_macro_derive(MyDerive, attributes(my_derive))]
+    /// pub fn my_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    ///     let mut moisture = Moisture::new();
+    ///     moisture.register_callback(CallbackType::ItemStruct, item_struct);
+    ///
+    ///     moisture.run_derive(&Context::new(), input)
+    /// }
+    /// ```
+    pub fn run_derive(
+        &self,
+        context: &Context,
+        input: proc_macro::TokenStream,
+    ) -> proc_macro::TokenStream {
+        let result = parse2::<DeriveInput>(input.into())
+            .map_err(MoistureError::from)
+            .and_then(|derive_input| self.derive(context, &derive_input));
+
+        match result {
+            Ok(tokens) => tokens,
+            Err(e) => e.to_compile_error(),
+        }
+        .into()
+    }
+    /// Traverse the input of a derive macro and return the items emitted by its
+    /// callbacks.
+    ///
+    /// The input is given to the [`CallbackType::DeriveInput`](CallbackType::DeriveInput)
+    /// callback, which traverses its fields, variants and their
+    /// [`CallbackType::Attribute`](CallbackType::Attribute)s. The output of the
+    /// callbacks is discarded, since a derive macro can't change its input;
+    /// instead, callbacks generate items with [`Context::emit`](Context::emit).
+    ///
+    /// ```rust
+    /// use proc_macro2::TokenStream;
+    /// use quote::{quote, ToTokens};
+    /// use syn::{parse2, DeriveInput, ItemStruct, Result};
+    ///
+    /// use moisture::*;
+    ///
+    /// fn attribute(_: &Moisture, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    ///     let attr = get_attribute(tokens.clone())?;
+    ///
+    ///     if attr.path.is_ident("skip") {
+    ///         let (_, field) = context.peek(1).unwrap();
+    ///         let name = get_field(field)?.ident;
+    ///         context.emit(quote! { const SKIPPED: &str = stringify!(#name); });
+    ///     }
+    ///
+    ///     Ok(tokens)
+    /// }
+    ///
+    /// fn item_struct(moisture: &Moisture, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    ///     let name = parse2::<ItemStruct>(tokens.clone())?.ident;
+    ///     context.emit(quote! { impl #name { fn hello() {} } });
+    ///
+    ///     moisture.item_struct(context, tokens)
+    /// }
+    ///
+    /// let mut moisture = Moisture::new();
+    /// moisture.register_callback(CallbackType::Attribute, attribute);
+    /// moisture.register_callback(CallbackType::ItemStruct, item_struct);
+    ///
+    /// let input = parse2::<DeriveInput>(quote! { struct S { a: u8, #[skip] b: u8 } }).unwrap();
+    /// let output = moisture.derive(&Context::new(), &input).unwrap();
+    ///
+    /// assert_eq!(
+    ///     output.to_string(),
+    ///     quote! { impl S { fn hello() {} } const SKIPPED: &str = stringify!(b); }.to_string()
+    /// );
+    /// ```
+    pub fn derive(&self, context: &Context, input: &DeriveInput) -> MoistureResult<TokenStream> {
+        self.traverse(context, CallbackType::DeriveInput, input.to_token_stream())?;
+
+        Ok(context.take_emitted())
+    }
+    /// Issue the callback of a [`Node`](Node) and parse its output back into a
+    /// node of the same type.
+    pub fn transform<T: Node>(&self, context: &Context, node: &T) -> Result<T> {
+        let tokens = self.callback(context, T::CALLBACK_TYPE, node.to_token_stream())?;
+
+        T::parse_node(tokens)
+    }
+    /// Rebuild a [`Node`](Node) with its children transformed by the registered
+    /// callbacks.
+    ///
+    /// This runs the default callback of the node, regardless of the callback
+    /// registered for it, so it can be used from within that callback to
+    /// adjust the node before or after its children are transformed. *context*
+    /// is the context of the node itself, such as the one given to its callback.
+    ///
+    /// ```rust
+    /// use proc_macro2::TokenStream;
+    /// use quote::{format_ident, quote, ToTokens};
+    /// use syn::{ItemFn, Result, parse2};
+    ///
+    /// use moisture::*;
+    ///
+    /// fn item_fn(moisture: &Moisture, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    ///     let item_fn = parse2::<ItemFn>(tokens)?;
+    ///     let mut new_fn = moisture.rebuild(context, &item_fn)?;
+    ///     new_fn.sig.ident = format_ident!("renamed_{}", new_fn.sig.ident);
+    ///
+    ///     Ok(new_fn.to_token_stream())
+    /// }
+    ///
+    /// let mut moisture = Moisture::new();
+    /// moisture.register_callback(CallbackType::ItemFn, item_fn);
+    ///
+    /// let tokens = quote! { fn f() { fn g() {} } };
+    /// let new_tokens = run_moisture!(moisture, CallbackType::Item, tokens);
+    ///
+    /// assert_eq!(new_tokens.to_string(), quote! { fn renamed_f() { fn renamed_g() {} } }.to_string());
+    /// ```
+    pub fn rebuild<T: Node>(&self, context: &Context, node: &T) -> Result<T> {
+        let tokens = match self.default_callback(T::CALLBACK_TYPE) {
+            Some(callback) => callback(self, context, node.to_token_stream())?,
+            None => {
+                let message = format!(
+                    "couldn't find default function for callback type {:?}",
+                    T::CALLBACK_TYPE
+                );
+                let error = Error::new_spanned(node, message);
+
+                return Err(context.raise(ErrorKind::MissingCallback, error));
+            }
+        };
+
+        T::parse_node(tokens)
+    }
+    /// Issue the callback registered for the given
+    /// [`CallbackType`](CallbackType) without pushing onto the context stack.
+    ///
+    /// This is meant for [`Wildcard`](Wildcard) callbacks, which are given a
+    /// context that already has the node on top of its stack.
+    pub fn delegate(
+        &self,
+        context: &Context,
+        ty: CallbackType,
+        tokens: TokenStream,
+    ) -> Result<TokenStream> {
+        if let Some(callback) = self.callbacks.get(&ty) {
+            callback(self, context, tokens).inspect_err(|e| {
+                // errors of the default callbacks come from parsing their node
+                let kind = if self.registered.contains(&ty) {
+                    ErrorKind::User
+                } else {
+                    ErrorKind::Parse
+                };
+                context.track(kind, e);
+            })
+        } else {
+            let message = format!("couldn't find function for callback type {:?}", ty);
+            let error = Error::new_spanned(tokens, message);
+
+            Err(context.raise(ErrorKind::MissingCallback, error))
+        }
+    }
+    fn ident_callback(
+        &self,
+        context: &Context,
+        role: IdentRole,
+        ident: &Ident,
+        field: &'static str,
+    ) -> Result<TokenStream> {
+        self.callback_with_role(
+            context,
+            CallbackType::Ident,
+            ident.to_token_stream(),
+            Some(role),
+            Slot::Field(field, None),
+        )
+    }
+    fn member(&self, context: &Context, member: &Member) -> Result<TokenStream> {
+        match member {
+            Member::Named(ident) => {
+                self.ident_callback(context, IdentRole::Member, ident, "member")
+            }
+            Member::Unnamed(index) => Ok(index.to_token_stream()),
+        }
+    }
+    fn loop_label(&self, context: &Context, label: &Label) -> Result<TokenStream> {
+        let new_name = self.callback_in(
+            context,
+            CallbackType::Label,
+            label.name.to_token_stream(),
+            "label",
+        )?;
+        let colon_token = label.colon_token;
+
+        Ok(quote! { #new_name #colon_token })
+    }
+    fn lifetime_opt(&self, context: &Context, lifetime: &Option<Lifetime>) -> Result<TokenStream> {
+        if let Some(lifetime) = lifetime {
+            self.callback_in(
+                context,
+                CallbackType::Lifetime,
+                lifetime.to_token_stream(),
+                "lifetime",
+            )
+        } else {
+            Ok(TokenStream::new())
+        }
+    }
+    fn lifetime_bounds<P: ToTokens>(
+        &self,
+        context: &Context,
+        bounds: &punctuated::Punctuated<Lifetime, P>,
+    ) -> Result<TokenStream> {
+        let mut new_bounds = Vec::<TokenStream>::new();
+
+        for (index, bound) in bounds.iter().enumerate() {
+            let new_bound = self.callback_at(
+                context,
+                CallbackType::Lifetime,
+                bound.to_token_stream(),
+                "bounds",
+                index,
+            )?;
+            new_bounds.push(new_bound);
+        }
+
+        Ok(zip_punctuated(bounds, new_bounds))
+    }
+    fn lifetime_def(&self, context: &Context, lifetime_def: &LifetimeDef) -> Result<TokenStream> {
+        let LifetimeDef {
+            attrs,
+            lifetime,
+            colon_token,
+            bounds,
+        } = lifetime_def;
+        let new_attrs = self.attributes(context, attrs)?;
+
+        let new_lifetime = self.callback_in(
+            context,
+            CallbackType::Lifetime,
+            lifetime.to_token_stream(),
+            "lifetime",
+        )?;
+        let new_bounds = self.lifetime_bounds(context, bounds)?;
+
+        Ok(quote! { #new_attrs #new_lifetime #colon_token #new_bounds })
+    }
+    fn bound_lifetimes_opt(
+        &self,
+        context: &Context,
+        lifetimes: &Option<BoundLifetimes>,
+    ) -> Result<TokenStream> {
+        if let Some(lifetimes) = lifetimes {
+            self.callback_in(
+                context,
+                CallbackType::BoundLifetimes,
+                lifetimes.to_token_stream(),
+                "lifetimes",
+            )
+        } else {
+            Ok(TokenStream::new())
+        }
+    }
+    fn use_tree(&self, context: &Context, tree: &UseTree) -> Result<TokenStream> {
+        // the segments leading up to a name, group or glob are a path of their
+        // own, such as `std::collections` in `std::collections::{HashMap, HashSet}`
+        let mut path = Path {
+            leading_colon: None,
+            segments: punctuated::Punctuated::new(),
+        };
+        let mut colon = None;
+        let mut tree = tree;
+
+        while let UseTree::Path(UsePath {
+            ident,
+            colon2_token,
+            tree: next,
+        }) = tree
+        {
+            if let Some(colon) = colon.take() {
+                path.segments.push_punct(colon);
+            }
+
+            path.segments.push_value(PathSegment::from(ident.clone()));
+            colon = Some(*colon2_token);
+            tree = next;
+        }
+
+        if let UseTree::Name(UseName { ident }) | UseTree::Rename(UseRename { ident, .. }) = tree {
+            if let Some(colon) = colon.take() {
+                path.segments.push_punct(colon);
+            }
+
+            path.segments.push_value(PathSegment::from(ident.clone()));
+        }
+
+        let new_path = if path.segments.is_empty() {
+            TokenStream::new()
+        } else {
+            self.callback_in(context, CallbackType::Path, path.to_token_stream(), "tree")?
+        };
+        let rest = match tree {
+            UseTree::Rename(UseRename {
+                as_token, rename, ..
+            }) => {
+                let new_rename = self.ident_callback(context, IdentRole::Item, rename, "rename")?;
+                quote! { #as_token #new_rename }
+            }
+            UseTree::Glob(glob) => glob.to_token_stream(),
+            UseTree::Group(UseGroup {
+                brace_token: _,
+                items,
+            }) => {
+                let mut new_items = Vec::<TokenStream>::new();
+
+                for item in items {
+                    new_items.push(self.use_tree(context, item)?);
+                }
+
+                let new_items = zip_punctuated(items, new_items);
+                quote! { { #new_items } }
+            }
+            _ => TokenStream::new(),
+        };
+
+        Ok(quote! { #new_path #colon #rest })
+    }
+    /// Issue the [`CallbackType::Expr`](CallbackType::Expr) callback for an
+    /// operand which has to bind at least as tightly as *precedence*.
+    ///
+    /// A replaced expression binding more loosely is wrapped in parentheses, so
+    /// that replacing `f(x)` in `a * f(x)` with `b + c` yields `a * (b + c)`.
+    /// Unchanged expressions are kept as they are.
+    fn operand_callback(
+        &self,
+        context: &Context,
+        expr: &Expr,
+        precedence: Precedence,
+        field: &'static str,
+    ) -> Result<TokenStream> {
+        let tokens = expr.to_token_stream();
+        let new_expr = self.callback_in(context, CallbackType::Expr, tokens.clone(), field)?;
+
+        if same_tokens(&new_expr, &tokens) {
+            Ok(new_expr)
+        } else {
+            Ok(parenthesize(new_expr, precedence))
+        }
+    }
+    fn bin_op_callback(
+        &self,
+        context: &Context,
+        left: &Expr,
+        op: &BinOp,
+        right: &Expr,
+    ) -> Result<TokenStream> {
+        let (left_precedence, right_precedence) = Precedence::of_operands(op);
+        let new_left = self.operand_callback(context, left, left_precedence, "left")?;
+        let new_right = self.operand_callback(context, right, right_precedence, "right")?;
+        let operation = quote! { #new_left #op #new_right };
+        let new_operation = self.callback_in(context, CallbackType::BinOp, operation, "op")?;
+
+        // a lone operator replaces the original operator, anything else replaces
+        // the whole operation
+        if let Ok(new_op) = parse2::<BinOp>(new_operation.clone()) {
+            if new_op == *op {
+                return Ok(quote! { #new_left #new_op #new_right });
+            }
+
+            // a compound assignment can't become a binary expression or the
+            // other way around, since the left side of an assignment is a place
+            let is_assign = Precedence::of_op(op) == Precedence::Assign;
+
+            if is_assign != (Precedence::of_op(&new_op) == Precedence::Assign) {
+                let expected = if is_assign {
+                    "a compound assignment operator"
+                } else {
+                    "a binary operator"
+                };
+                let message = format!(
+                    "callback for BinOp replaced `{}` with `{}`, expected {}",
+                    op.to_token_stream(),
+                    new_op.to_token_stream(),
+                    expected
+                );
+                let error = Error::new_spanned(new_operation, message);
+
+                return Err(context.raise(ErrorKind::Validation, error));
+            }
+
+            // the operands have to bind as tightly as the new operator requires
+            let (left_precedence, right_precedence) = Precedence::of_operands(&new_op);
+            let new_left = parenthesize(new_left, left_precedence);
+            let new_right = parenthesize(new_right, right_precedence);
+
+            Ok(quote! { #new_left #new_op #new_right })
+        } else {
+            Ok(new_operation)
+        }
+    }
+    fn attributes(&self, context: &Context, attrs: &[Attribute]) -> Result<TokenStream> {
+        let (mut outer, inner) = self.split_attributes(context, attrs)?;
+        outer.extend(inner);
+
+        Ok(outer)
+    }
+    /// Issue the attribute callbacks of *attrs*, returning the new outer and
+    /// inner attributes.
+    ///
+    /// [syn](syn) keeps the inner attributes of items and expressions with a
+    /// body, such as `#![allow(unused)]` inside of a module, in the same list as
+    /// their outer attributes.
+    fn split_attributes(
+        &self,
+        context: &Context,
+        attrs: &[Attribute],
+    ) -> Result<(TokenStream, TokenStream)> {
+        let mut outer = TokenStream::new();
+        let mut inner = TokenStream::new();
+
+        for (index, attr) in attrs.iter().enumerate() {
+            let new_attr = self.callback_at(
+                context,
+                CallbackType::Attribute,
+                attr.to_token_stream(),
+                "attrs",
+                index,
+            )?;
+
+            match attr.style {
+                AttrStyle::Outer => outer.extend(new_attr),
+                AttrStyle::Inner(_) => inner.extend(new_attr),
+            }
+        }
+
+        Ok((outer, inner))
+    }
+    fn const_argument(&self, context: &Context, expr: &Expr, slot: Slot) -> Result<TokenStream> {
+        let new_expr = self.callback_with_role(
+            context,
+            CallbackType::Expr,
+            expr.to_token_stream(),
+            None,
+            slot,
+        )?;
+
+        // const arguments other than literals and single-segment paths have to
+        // be wrapped in a block
+        match parse2::<Expr>(new_expr.clone()) {
+            Ok(Expr::Lit(_)) | Ok(Expr::Block(_)) => Ok(new_expr),
+            Ok(Expr::Path(ExprPath {
+                qself: None, path, ..
+            })) if path.get_ident().is_some() => Ok(new_expr),
+            _ => Ok(quote! { { #new_expr } }),
+        }
+    }
+    fn qualified_path(
+        &self,
+        context: &Context,
+        qself: Option<QSelf>,
+        path: Path,
+    ) -> Result<TokenStream> {
+        if qself.is_some() {
+            let qualified = TypePath { qself, path };
+            self.callback_in(
+                context,
+                CallbackType::QSelf,
+                qualified.to_token_stream(),
+                "path",
+            )
+        } else {
+            self.callback_in(context, CallbackType::Path, path.to_token_stream(), "path")
+        }
+    }
+    fn return_type(&self, context: &Context, output: &ReturnType) -> Result<TokenStream> {
+        if let ReturnType::Type(arrow, ty) = output {
+            let new_ty =
+                self.callback_in(context, CallbackType::Type, ty.to_token_stream(), "output")?;
+            Ok(quote! { #arrow #new_ty })
+        } else {
+            Ok(TokenStream::new())
+        }
+    }
+    fn type_param_bounds<P: ToTokens>(
+        &self,
+        context: &Context,
+        bounds: &punctuated::Punctuated<TypeParamBound, P>,
+        field: &'static str,
+    ) -> Result<TokenStream> {
+        let mut new_bounds = Vec::<TokenStream>::new();
+
+        for (index, bound) in bounds.iter().enumerate() {
+            let new_bound = self.callback_at(
+                context,
+                CallbackType::TypeParamBound,
+                bound.to_token_stream(),
+                field,
+                index,
+            )?;
+            new_bounds.push(new_bound);
+        }
+
+        Ok(zip_punctuated(bounds, new_bounds))
+    }
+    fn where_clause_opt(
+        &self,
+        context: &Context,
+        where_clause: &Option<WhereClause>,
+    ) -> Result<TokenStream> {
+        if let Some(WhereClause {
+            where_token,
+            predicates,
+        }) = where_clause
+        {
+            // WhereClause doesn't emit its where token when it has no predicates,
+            // so quote it ourselves
+            self.callback_in(
+                context,
+                CallbackType::WhereClause,
+                quote! { #where_token #predicates },
+                "where_clause",
+            )
+        } else {
+            Ok(TokenStream::new())
+        }
+    }
+    pub fn file(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let parsed = parse2::<File>(tokens)?;
+        let mut tokens = TokenStream::new();
+
+        let new_attrs = self.attributes(context, &parsed.attrs)?;
+        tokens.extend(new_attrs);
+
+        let context = &context.declare(&parsed.items.iter().collect::<Vec<_>>());
+        let mut new_items = Vec::<TokenStream>::new();
+
+        for (index, item) in parsed.items.iter().enumerate() {
+            let item_stream = self.callback_at(
+                context,
+                CallbackType::Item,
+                item.to_token_stream(),
+                "items",
+                index,
+            )?;
+            new_items.push(item_stream);
+        }
+
+        tokens.extend(new_items);
+
+        Ok(tokens)
+    }
+    pub fn item(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let parsed = parse2::<Item>(tokens)?;
+        let context = &context.enter_item();
+
+        match parsed.callback_type() {
+            Some(ty) => self.dispatch(context, ty, parsed.to_token_stream()),
+            None => Ok(parsed.to_token_stream()),
+        }
+    }
+    pub fn item_const(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ItemConst {
+            attrs,
+            vis,
+            const_token,
+            ident,
+            colon_token,
+            ty,
+            eq_token,
+            expr,
+            semi_token,
+        } = parse2::<ItemConst>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
+        let mut result = TokenStream::new();
+        let mut tokens = Vec::<TokenStream>::new();
+
+        let new_vis = self.callback_in(
+            context,
+            CallbackType::Visibility,
+            vis.to_token_stream(),
+            "vis",
+        )?;
+        let new_ident = self.ident_callback(context, IdentRole::Item, &ident, "ident")?;
+        tokens.push(quote! {
+            #new_attrs
+            #new_vis #const_token #new_ident #colon_token
+        });
+
+        let new_ty = self.callback_in(context, CallbackType::Type, ty.to_token_stream(), "ty")?;
+        tokens.push(quote! { #new_ty #eq_token });
+
+        let filtered_expr =
+            self.callback_in(context, CallbackType::Expr, expr.to_token_stream(), "expr")?;
+        tokens.push(filtered_expr);
+
+        tokens.push(quote! { #semi_token });
+
+        result.extend(tokens);
+        Ok(result)
+    }
+    pub fn item_enum(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ItemEnum {
+            attrs,
+            vis,
+            enum_token,
+            ident,
+            generics,
+            brace_token: _,
+            variants,
+        } = parse2::<ItemEnum>(tokens)?;
+        let mut result = TokenStream::new();
+        let mut tokens = Vec::<TokenStream>::new();
+
+        let new_attrs = self.attributes(context, &attrs)?;
+        let new_vis = self.callback_in(
+            context,
+            CallbackType::Visibility,
+            vis.to_token_stream(),
+            "vis",
+        )?;
+        let new_ident = self.ident_callback(context, IdentRole::Item, &ident, "ident")?;
+        tokens.push(quote! {
+            #new_attrs
+            #new_vis #enum_token #new_ident
+        });
+
+        let new_generics = self.callback_in(
+            context,
+            CallbackType::Generics,
+            generics.to_token_stream(),
+            "generics",
+        )?;
+        tokens.push(new_generics);
+        tokens.push(self.where_clause_opt(context, &generics.where_clause)?);
+
+        let mut filtered_variants = Vec::<TokenStream>::new();
+
+        for (index, variant) in variants.iter().enumerate() {
+            let new_variant = self.callback_at(
+                context,
+                CallbackType::Variant,
+                variant.to_token_stream(),
+                "variants",
+                index,
+            )?;
+            filtered_variants.push(new_variant);
+        }
+
+        let filtered_variants = zip_punctuated(&variants, filtered_variants);
+        tokens.push(quote! {
+            {
+                #filtered_variants
+            }
+        });
+
+        result.extend(tokens);
+        Ok(result)
+    }
+    pub fn variant(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let Variant {
+            attrs,
+            ident,
+            fields,
+            discriminant,
+        } = parse2::<Variant>(tokens)?;
+        let mut result = TokenStream::new();
+        let mut tokens = Vec::<TokenStream>::new();
+
+        let new_attrs = self.attributes(context, &attrs)?;
+        let new_ident = self.ident_callback(context, IdentRole::Item, &ident, "ident")?;
+        tokens.push(quote! {
+            #new_attrs
+            #new_ident
+        });
+
+        let new_fields = self.callback_in(
+            context,
+            CallbackType::Fields,
+            fields.to_token_stream(),
+            "fields",
+        )?;
+        tokens.push(new_fields);
+
+        if let Some((eq_token, expr)) = discriminant {
+            let filtered_expr = self.callback_in(
+                context,
+                CallbackType::Expr,
+                expr.to_token_stream(),
+                "discriminant",
+            )?;
+
+            tokens.push(quote! { #eq_token #filtered_expr });
+        }
+
+        result.extend(tokens);
+        Ok(result)
+    }
+    pub fn item_extern_crate(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ItemExternCrate {
+            attrs,
+            vis,
+            extern_token,
+            crate_token,
+            ident,
+            rename,
+            semi_token,
+        } = parse2::<ItemExternCrate>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
+        let mut result = TokenStream::new();
+        let mut tokens = Vec::<TokenStream>::new();
+
+        let new_vis = self.callback_in(
+            context,
+            CallbackType::Visibility,
+            vis.to_token_stream(),
+            "vis",
+        )?;
+        tokens.push(quote! { #new_attrs #new_vis #extern_token #crate_token });
+
+        if let Some((as_token, rename_ident)) = rename {
+            // the crate name is only declared when it isn't renamed
+            let new_ident = self.ident_callback(context, IdentRole::Path, &ident, "ident")?;
+            let new_rename =
+                self.ident_callback(context, IdentRole::Item, &rename_ident, "rename")?;
+            tokens.push(quote! { #new_ident #as_token #new_rename });
+        } else {
+            tokens.push(self.ident_callback(context, IdentRole::Item, &ident, "ident")?);
+        }
+
+        tokens.push(quote! { #semi_token });
+
+        result.extend(tokens);
+        Ok(result)
+    }
+    pub fn item_fn(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ItemFn {
+            attrs,
+            vis,
+            sig,
+            block,
+        } = parse2::<ItemFn>(tokens)?;
+        let context = &context.enter_fn(&sig);
+        let (outer_attrs, inner_attrs) = self.split_attributes(context, &attrs)?;
+        let mut result = TokenStream::new();
+        let mut tokens = Vec::<TokenStream>::new();
+
+        let new_vis = self.callback_in(
+            context,
+            CallbackType::Visibility,
+            vis.to_token_stream(),
+            "vis",
+        )?;
+        tokens.push(quote! {
+            #outer_attrs
+            #new_vis
+        });
+
+        let new_sig = self.callback_in(
+            context,
+            CallbackType::Signature,
+            sig.to_token_stream(),
+            "sig",
+        )?;
+        tokens.push(new_sig);
+
+        let _scope = context.enter_scope();
+        context.bind_inputs(&sig);
+
+        let filtered_block = self.callback_in(
+            context,
+            CallbackType::Block,
+            block.to_token_stream(),
+            "block",
+        )?;
+        tokens.push(inner_block(inner_attrs, filtered_block));
+
+        result.extend(tokens);
+        Ok(result)
+    }
+    pub fn item_foreign_mod(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ItemForeignMod {
+            attrs,
+            abi,
+            brace_token: _,
+            items,
+        } = parse2::<ItemForeignMod>(tokens)?;
+        let (outer_attrs, inner_attrs) = self.split_attributes(context, &attrs)?;
+        let mut result = TokenStream::new();
+        let mut tokens = Vec::<TokenStream>::new();
+
+        tokens.push(quote! {
+            #outer_attrs
+            #abi
+        });
+
+        let mut filtered_items = Vec::<TokenStream>::new();
+
+        for (index, item) in items.iter().enumerate() {
+            let filtered = self.callback_at(
+                context,
+                CallbackType::ForeignItem,
+                item.to_token_stream(),
+                "items",
+                index,
+            )?;
+            filtered_items.push(filtered);
+        }
+
+        tokens.push(quote! {
+            {
+                #inner_attrs
+                #(#filtered_items)*
+            }
+        });
+
+        result.extend(tokens);
+        Ok(result)
+    }
+    pub fn item_impl(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        // ItemImpl's own parser rejects negative impls without a `for`
+        let parsed = parse2::<Item>(tokens)?;
+        let item_impl;
+
+        if let Item::Impl(impl_) = parsed {
+            item_impl = impl_;
+        } else {
+            return Err(Error::new(
+                parsed.span(),
+                "expected ItemImpl object in item",
+            ));
+        }
+
+        let context = &context.enter_impl(&item_impl);
+        let ItemImpl {
+            attrs,
+            defaultness,
+            unsafety,
+            impl_token,
+            generics,
+            trait_,
+            self_ty,
+            brace_token: _,
+            items,
+        } = item_impl;
+        let (outer_attrs, inner_attrs) = self.split_attributes(context, &attrs)?;
+        let mut result = TokenStream::new();
+        let mut tokens = Vec::<TokenStream>::new();
+
+        tokens.push(quote! { #outer_attrs });
+
+        if let Some(default_token) = defaultness {
+            tokens.push(quote! { #default_token });
+        }
+
+        if let Some(unsafe_token) = unsafety {
+            tokens.push(quote! { #unsafe_token });
+        }
+
+        tokens.push(quote! { #impl_token });
+
+        let new_generics = self.callback_in(
+            context,
+            CallbackType::Generics,
+            generics.to_token_stream(),
+            "generics",
+        )?;
+        tokens.push(new_generics);
+
+        if let Some((bang_opt, path, for_)) = trait_ {
+            if let Some(bang_token) = bang_opt {
+                tokens.push(quote! { #bang_token });
+            }
+
+            let new_path = self.callback_in(
+                context,
+                CallbackType::Path,
+                path.to_token_stream(),
+                "trait_",
+            )?;
+            tokens.push(quote! { #new_path #for_ });
+        }
+
+        // a verbatim self type doesn't parse as a Type on its own
+        let new_self_ty = if let Type::Verbatim(ref verbatim) = *self_ty {
+            self.callback_in(context, CallbackType::Verbatim, verbatim.clone(), "self_ty")?
+        } else {
+            self.callback_in(
+                context,
+                CallbackType::Type,
+                self_ty.to_token_stream(),
+                "self_ty",
+            )?
+        };
+        tokens.push(new_self_ty);
+        tokens.push(self.where_clause_opt(context, &generics.where_clause)?);
+
+        let mut filtered_items = Vec::<TokenStream>::new();
+
+        for (index, item) in items.iter().enumerate() {
+            let filtered_item = self.callback_at(
+                context,
+                CallbackType::ImplItem,
+                item.to_token_stream(),
+                "items",
+                index,
+            )?;
+            filtered_items.push(filtered_item);
+        }
+
+        tokens.push(quote! {
+            {
+                #inner_attrs
+                #(#filtered_items)*
+            }
+        });
+
+        result.extend(tokens);
+        Ok(result)
+    }
+    pub fn item_macro(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ItemMacro {
+            attrs,
+            ident,
+            mac,
+            semi_token,
+        } = parse2::<ItemMacro>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
+
+        if let Some(macro_ident) = ident {
+            // macro_rules! definitions put the name of the new macro between the
+            // bang and the body, so the macro can't be handed off as a whole
+            let new_path = self.callback_in(
+                context,
+                CallbackType::Path,
+                mac.path.to_token_stream(),
+                "path",
+            )?;
+            let bang_token = mac.bang_token;
+            let new_ident =
+                self.ident_callback(context, IdentRole::Macro, &macro_ident, "ident")?;
+            let body = macro_body(&mac, mac.tokens.clone());
+
+            Ok(quote! { #new_attrs #new_path #bang_token #new_ident #body #semi_token })
+        } else {
+            let new_mac =
+                self.callback_in(context, CallbackType::Macro, mac.to_token_stream(), "mac")?;
+
+            Ok(quote! { #new_attrs #new_mac #semi_token })
+        }
+    }
+    pub fn item_macro2(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let parsed = parse2::<ItemMacro2>(tokens)?;
+        Ok(parsed.to_token_stream())
+    }
+    pub fn item_mod(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ItemMod {
+            attrs,
+            vis,
+            mod_token,
+            ident,
+            content,
+            semi,
+        } = parse2::<ItemMod>(tokens)?;
+        let (outer_attrs, inner_attrs) = self.split_attributes(context, &attrs)?;
+        let mut result = TokenStream::new();
+        let mut tokens = Vec::<TokenStream>::new();
+
+        let new_vis = self.callback_in(
+            context,
+            CallbackType::Visibility,
+            vis.to_token_stream(),
+            "vis",
+        )?;
+        let new_ident = self.ident_callback(context, IdentRole::Item, &ident, "ident")?;
+        tokens.push(quote! {
+            #outer_attrs
+            #new_vis #mod_token #new_ident
+        });
+
+        if let Some((_, items)) = content {
+            let context = &context.enter_mod(&ident, &items);
+            let mut filtered_items = Vec::<TokenStream>::new();
+
+            for (index, item) in items.iter().enumerate() {
+                let new_item = self.callback_at(
+                    context,
+                    CallbackType::Item,
+                    item.to_token_stream(),
+                    "items",
+                    index,
+                )?;
+                filtered_items.push(new_item);
+            }
+
+            tokens.push(quote! {
+                {
+                    #inner_attrs
+                    #(#filtered_items)*
+                }
+            });
+        }
+
+        if let Some(semi_token) = semi {
+            tokens.push(quote! { #semi_token });
+        }
+
+        result.extend(tokens);
+        Ok(result)
+    }
+    pub fn item_static(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ItemStatic {
+            attrs,
+            vis,
+            static_token,
+            mutability,
+            ident,
+            colon_token,
+            ty,
+            eq_token,
+            expr,
+            semi_token,
+        } = parse2::<ItemStatic>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
+        let mut result = TokenStream::new();
+        let mut tokens = Vec::<TokenStream>::new();
+
+        let new_vis = self.callback_in(
+            context,
+            CallbackType::Visibility,
+            vis.to_token_stream(),
+            "vis",
+        )?;
+        tokens.push(quote! {
+            #new_attrs
+            #new_vis #static_token
+        });
+
+        if let Some(mut_token) = mutability {
+            tokens.push(quote! { #mut_token });
+        }
+
+        let new_ident = self.ident_callback(context, IdentRole::Item, &ident, "ident")?;
+        tokens.push(quote! { #new_ident #colon_token });
+
+        let new_ty = self.callback_in(context, CallbackType::Type, ty.to_token_stream(), "ty")?;
+        tokens.push(quote! { #new_ty #eq_token });
+
+        let filtered_expr =
+            self.callback_in(context, CallbackType::Expr, expr.to_token_stream(), "expr")?;
+        tokens.push(quote! { #filtered_expr #semi_token });
+
+        result.extend(tokens);
+        Ok(result)
+    }
+    pub fn derive_input(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let parsed = parse2::<DeriveInput>(tokens)?;
+
+        self.dispatch(context, CallbackType::Item, parsed.to_token_stream())
+    }
+    pub fn attribute(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let Attribute {
+            pound_token,
+            style,
+            bracket_token,
+            path,
+            tokens,
+        } = get_attribute(tokens)?;
+
+        let bang_token = match style {
+            AttrStyle::Inner(bang_token) => Some(bang_token),
+            AttrStyle::Outer => None,
+        };
+        let new_path =
+            self.callback_in(context, CallbackType::Path, path.to_token_stream(), "path")?;
+        let mut group = Group::new(Delimiter::Bracket, quote! { #new_path #tokens });
+        group.set_span(bracket_token.span);
+
+        Ok(quote! { #pound_token #bang_token #group })
+    }
+    pub fn item_struct(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ItemStruct {
+            attrs,
+            vis,
+            struct_token,
+            ident,
+            generics,
+            fields,
+            semi_token,
+        } = parse2::<ItemStruct>(tokens)?;
+        let mut result = TokenStream::new();
+        let mut tokens = Vec::<TokenStream>::new();
+
+        let new_attrs = self.attributes(context, &attrs)?;
+        let new_vis = self.callback_in(
+            context,
+            CallbackType::Visibility,
+            vis.to_token_stream(),
+            "vis",
+        )?;
+        let new_ident = self.ident_callback(context, IdentRole::Item, &ident, "ident")?;
+        tokens.push(quote! {
+            #new_attrs
+            #new_vis #struct_token #new_ident
+        });
+
+        let new_generics = self.callback_in(
+            context,
+            CallbackType::Generics,
+            generics.to_token_stream(),
+            "generics",
+        )?;
+        tokens.push(new_generics);
+
+        let new_where = self.where_clause_opt(context, &generics.where_clause)?;
+        let new_fields = self.callback_in(
+            context,
+            CallbackType::Fields,
+            fields.to_token_stream(),
+            "fields",
+        )?;
+
+        // the where clause comes after the fields of tuple structs
+        if let Fields::Unnamed(_) = fields {
+            tokens.push(quote! { #new_fields #new_where #semi_token });
+        } else {
+            tokens.push(quote! { #new_where #new_fields #semi_token });
+        }
+
+        result.extend(tokens);
+        Ok(result)
+    }
+    pub fn item_trait(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ItemTrait {
+            attrs,
+            vis,
+            unsafety,
+            auto_token,
+            trait_token,
+            ident,
+            generics,
+            colon_token,
+            supertraits,
+            brace_token: _,
+            items,
+        } = parse2::<ItemTrait>(tokens)?;
+        let (outer_attrs, inner_attrs) = self.split_attributes(context, &attrs)?;
+        let mut result = TokenStream::new();
+        let mut tokens = Vec::<TokenStream>::new();
+
+        let new_vis = self.callback_in(
+            context,
+            CallbackType::Visibility,
+            vis.to_token_stream(),
+            "vis",
+        )?;
+        tokens.push(quote! { #outer_attrs #new_vis });
+
+        if let Some(unsafe_keyword) = unsafety {
+            tokens.push(quote! { #unsafe_keyword });
+        }
+
+        if let Some(auto) = auto_token {
+            tokens.push(quote! { #auto });
+        }
+
+        let new_ident = self.ident_callback(context, IdentRole::Item, &ident, "ident")?;
+        tokens.push(quote! { #trait_token #new_ident });
+
+        let new_generics = self.callback_in(
+            context,
+            CallbackType::Generics,
+            generics.to_token_stream(),
+            "generics",
+        )?;
+        tokens.push(new_generics);
+
+        if let Some(colon) = colon_token {
+            tokens.push(quote! { #colon });
+        }
+
+        tokens.push(self.type_param_bounds(context, &supertraits, "supertraits")?);
+        tokens.push(self.where_clause_opt(context, &generics.where_clause)?);
+
+        let mut trait_items = Vec::<TokenStream>::new();
+
+        for (index, item) in items.iter().enumerate() {
+            let new_item = self.callback_at(
+                context,
+                CallbackType::TraitItem,
+                item.to_token_stream(),
+                "items",
+                index,
+            )?;
+            trait_items.push(new_item);
+        }
+
+        tokens.push(quote! {
+            {
+                #inner_attrs
+                #(#trait_items)*
+            }
+        });
+
+        result.extend(tokens);
+        Ok(result)
+    }
+    pub fn item_trait_alias(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ItemTraitAlias {
+            attrs,
+            vis,
+            trait_token,
+            ident,
+            generics,
+            eq_token,
+            bounds,
+            semi_token,
+        } = parse2::<ItemTraitAlias>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
+        let mut result = TokenStream::new();
+        let mut tokens = Vec::<TokenStream>::new();
+
+        let new_vis = self.callback_in(
+            context,
+            CallbackType::Visibility,
+            vis.to_token_stream(),
+            "vis",
+        )?;
+        let new_ident = self.ident_callback(context, IdentRole::Item, &ident, "ident")?;
+        tokens.push(quote! { #new_attrs #new_vis #trait_token #new_ident });
+
+        let new_generics = self.callback_in(
+            context,
+            CallbackType::Generics,
+            generics.to_token_stream(),
+            "generics",
+        )?;
+        tokens.push(quote! { #new_generics #eq_token });
+
+        tokens.push(self.type_param_bounds(context, &bounds, "bounds")?);
+        tokens.push(self.where_clause_opt(context, &generics.where_clause)?);
+        tokens.push(quote! { #semi_token });
+
+        result.extend(tokens);
+        Ok(result)
+    }
+    pub fn item_type(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ItemType {
+            attrs,
+            vis,
+            type_token,
+            ident,
+            generics,
+            eq_token,
+            ty,
+            semi_token,
+        } = parse2::<ItemType>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
+        let mut result = TokenStream::new();
+        let mut tokens = Vec::<TokenStream>::new();
+
+        let new_vis = self.callback_in(
+            context,
+            CallbackType::Visibility,
+            vis.to_token_stream(),
+            "vis",
+        )?;
+        let new_ident = self.ident_callback(context, IdentRole::Item, &ident, "ident")?;
+        tokens.push(quote! { #new_attrs #new_vis #type_token #new_ident });
+
+        let new_generics = self.callback_in(
+            context,
+            CallbackType::Generics,
+            generics.to_token_stream(),
+            "generics",
+        )?;
+        tokens.push(new_generics);
+        tokens.push(self.where_clause_opt(context, &generics.where_clause)?);
+
+        let new_ty = self.callback_in(context, CallbackType::Type, ty.to_token_stream(), "ty")?;
+        tokens.push(quote! { #eq_token #new_ty #semi_token });
+
+        result.extend(tokens);
+        Ok(result)
+    }
+    pub fn item_union(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ItemUnion {
+            attrs,
+            vis,
+            union_token,
+            ident,
+            generics,
+            fields,
+        } = parse2::<ItemUnion>(tokens)?;
+        let mut result = TokenStream::new();
+        let mut tokens = Vec::<TokenStream>::new();
+
+        let new_attrs = self.attributes(context, &attrs)?;
+        let new_vis = self.callback_in(
+            context,
+            CallbackType::Visibility,
+            vis.to_token_stream(),
+            "vis",
+        )?;
+        let new_ident = self.ident_callback(context, IdentRole::Item, &ident, "ident")?;
+        tokens.push(quote! { #new_attrs #new_vis #union_token #new_ident });
+
+        let new_generics = self.callback_in(
+            context,
+            CallbackType::Generics,
+            generics.to_token_stream(),
+            "generics",
+        )?;
+        tokens.push(new_generics);
+        tokens.push(self.where_clause_opt(context, &generics.where_clause)?);
+
+        let new_fields = self.callback_in(
+            context,
+            CallbackType::Fields,
+            fields.to_token_stream(),
+            "fields",
+        )?;
+        tokens.push(new_fields);
+
+        result.extend(tokens);
+        Ok(result)
+    }
+    pub fn item_use(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ItemUse {
+            attrs,
+            vis,
+            use_token,
+            leading_colon,
+            tree,
+            semi_token,
+        } = parse2::<ItemUse>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
+
+        let new_vis = self.callback_in(
+            context,
+            CallbackType::Visibility,
+            vis.to_token_stream(),
+            "vis",
+        )?;
+        let new_tree = self.use_tree(context, &tree)?;
+
+        Ok(quote! { #new_attrs #new_vis #use_token #leading_colon #new_tree #semi_token })
+    }
+    pub fn foreign_item(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let parsed = parse2::<ForeignItem>(tokens)?;
+
+        match parsed.callback_type() {
+            Some(ty) => self.dispatch(context, ty, parsed.to_token_stream()),
+            None => Ok(parsed.to_token_stream()),
+        }
+    }
+    pub fn foreign_item_fn(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ForeignItemFn {
+            attrs,
+            vis,
+            sig,
+            semi_token,
+        } = parse2::<ForeignItemFn>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
+
+        let new_vis = self.callback_in(
+            context,
+            CallbackType::Visibility,
+            vis.to_token_stream(),
+            "vis",
+        )?;
+        let new_sig = self.callback_in(
+            context,
+            CallbackType::Signature,
+            sig.to_token_stream(),
+            "sig",
+        )?;
+
+        Ok(quote! { #new_attrs #new_vis #new_sig #semi_token })
+    }
+    pub fn foreign_item_static(
+        &self,
+        context: &Context,
+        tokens: TokenStream,
+    ) -> Result<TokenStream> {
+        let ForeignItemStatic {
+            attrs,
+            vis,
+            static_token,
+            mutability,
+            ident,
+            colon_token,
+            ty,
+            semi_token,
+        } = parse2::<ForeignItemStatic>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
+
+        let new_vis = self.callback_in(
+            context,
+            CallbackType::Visibility,
+            vis.to_token_stream(),
+            "vis",
+        )?;
+        let new_ident = self.ident_callback(context, IdentRole::Item, &ident, "ident")?;
+        let new_ty = self.callback_in(context, CallbackType::Type, ty.to_token_stream(), "ty")?;
+        Ok(
+            quote! { #new_attrs #new_vis #static_token #mutability #new_ident #colon_token #new_ty #semi_token },
+        )
+    }
+    pub fn foreign_item_type(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ForeignItemType {
+            attrs,
+            vis,
+            type_token,
+            ident,
+            semi_token,
+        } = parse2::<ForeignItemType>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
+
+        let new_vis = self.callback_in(
+            context,
+            CallbackType::Visibility,
+            vis.to_token_stream(),
+            "vis",
+        )?;
+        let new_ident = self.ident_callback(context, IdentRole::Item, &ident, "ident")?;
+
+        Ok(quote! { #new_attrs #new_vis #type_token #new_ident #semi_token })
+    }
+    pub fn foreign_item_macro(
+        &self,
+        context: &Context,
+        tokens: TokenStream,
+    ) -> Result<TokenStream> {
+        let ForeignItemMacro {
+            attrs,
+            mac,
+            semi_token,
+        } = parse2::<ForeignItemMacro>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
+
+        let new_mac =
+            self.callback_in(context, CallbackType::Macro, mac.to_token_stream(), "mac")?;
+
+        Ok(quote! { #new_attrs #new_mac #semi_token })
+    }
+    pub fn impl_item(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let parsed = parse2::<ImplItem>(tokens)?;
+
+        match parsed.callback_type() {
+            Some(ty) => self.dispatch(context, ty, parsed.to_token_stream()),
+            None => Ok(parsed.to_token_stream()),
+        }
+    }
+    pub fn impl_item_const(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ImplItemConst {
+            attrs,
+            vis,
+            defaultness,
+            const_token,
+            ident,
+            colon_token,
+            ty,
+            eq_token,
+            expr,
+            semi_token,
+        } = parse2::<ImplItemConst>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
+        let mut result = TokenStream::new();
+        let mut tokens = Vec::<TokenStream>::new();
+
+        let new_vis = self.callback_in(
+            context,
+            CallbackType::Visibility,
+            vis.to_token_stream(),
+            "vis",
+        )?;
+        tokens.push(quote! { #new_attrs #new_vis });
+
+        if let Some(default_token) = defaultness {
+            tokens.push(quote! { #default_token });
+        }
+
+        let new_ident = self.ident_callback(context, IdentRole::Item, &ident, "ident")?;
+        tokens.push(quote! { #const_token #new_ident #colon_token });
+
+        let new_ty = self.callback_in(context, CallbackType::Type, ty.to_token_stream(), "ty")?;
+        tokens.push(quote! { #new_ty #eq_token });
+
+        let filtered_expr =
+            self.callback_in(context, CallbackType::Expr, expr.to_token_stream(), "expr")?;
+
+        tokens.push(quote! { #filtered_expr #semi_token });
+
+        result.extend(tokens);
+        Ok(result)
+    }
+    pub fn impl_item_method(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ImplItemMethod {
+            attrs,
+            vis,
+            defaultness,
+            sig,
+            block,
+        } = parse2::<ImplItemMethod>(tokens)?;
+        let context = &context.enter_fn(&sig);
+        let (outer_attrs, inner_attrs) = self.split_attributes(context, &attrs)?;
+        let mut result = TokenStream::new();
+        let mut tokens = Vec::<TokenStream>::new();
+
+        let new_vis = self.callback_in(
+            context,
+            CallbackType::Visibility,
+            vis.to_token_stream(),
+            "vis",
+        )?;
+        tokens.push(quote! { #outer_attrs #new_vis });
+
+        if let Some(default_token) = defaultness {
+            tokens.push(quote! { #default_token });
+        }
 
+        let new_sig = self.callback_in(
+            context,
+            CallbackType::Signature,
+            sig.to_token_stream(),
+            "sig",
+        )?;
+        tokens.push(new_sig);
+
+        let _scope = context.enter_scope();
+        context.bind_inputs(&sig);
+
+        let filtered_block = self.callback_in(
+            context,
+            CallbackType::Block,
+            block.to_token_stream(),
+            "block",
+        )?;
+        tokens.push(inner_block(inner_attrs, filtered_block));
+
+        result.extend(tokens);
         Ok(result)
     }
-    pub fn item_const(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ItemConst {
+    pub fn impl_item_type(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ImplItemType {
             attrs,
             vis,
-            const_token,
+            defaultness,
+            type_token,
             ident,
-            colon_token,
-            ty,
+            generics,
             eq_token,
-            expr,
+            ty,
             semi_token,
-        } = parse2::<ItemConst>(tokens)?;
+        } = parse2::<ImplItemType>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
         let mut result = TokenStream::new();
         let mut tokens = Vec::<TokenStream>::new();
 
-        tokens.push(quote! {
-            #(#attrs)*
-            #vis #const_token #ident #colon_token #ty #eq_token
-        });
-
-        let filtered_expr = self.callback(context, CallbackType::Expr, expr.to_token_stream())?;
-        tokens.push(filtered_expr);
-
+        let new_vis = self.callback_in(
+            context,
+            CallbackType::Visibility,
+            vis.to_token_stream(),
+            "vis",
+        )?;
+        let new_ident = self.ident_callback(context, IdentRole::Item, &ident, "ident")?;
+        tokens.push(quote! { #new_attrs #new_vis #defaultness #type_token #new_ident });
+
+        let new_generics = self.callback_in(
+            context,
+            CallbackType::Generics,
+            generics.to_token_stream(),
+            "generics",
+        )?;
+        tokens.push(quote! { #new_generics #eq_token });
+
+        let new_ty = self.callback_in(context, CallbackType::Type, ty.to_token_stream(), "ty")?;
+        tokens.push(new_ty);
+        tokens.push(self.where_clause_opt(context, &generics.where_clause)?);
         tokens.push(quote! { #semi_token });
 
-        result.extend(tokens.into_iter());
+        result.extend(tokens);
         Ok(result)
     }
-    pub fn item_enum(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ItemEnum {
+    pub fn impl_item_macro(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ImplItemMacro {
             attrs,
-            vis,
-            enum_token,
-            ident,
-            generics,
-            brace_token: _,
-            variants,
-        } = parse2::<ItemEnum>(tokens)?;
-        let mut result = TokenStream::new();
-        let mut tokens = Vec::<TokenStream>::new();
+            mac,
+            semi_token,
+        } = parse2::<ImplItemMacro>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
 
-        tokens.push(quote! {
-            #(#attrs)*
-            #vis #enum_token #ident #generics
-        });
+        let new_mac =
+            self.callback_in(context, CallbackType::Macro, mac.to_token_stream(), "mac")?;
 
-        let mut filtered_variants = Vec::<TokenStream>::new();
+        Ok(quote! { #new_attrs #new_mac #semi_token })
+    }
+    pub fn trait_item(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let parsed = parse2::<TraitItem>(tokens)?;
 
-        for variant in &variants {
-            let new_variant =
-                self.callback(context, CallbackType::Variant, variant.to_token_stream())?;
-            filtered_variants.push(new_variant);
+        match parsed.callback_type() {
+            Some(ty) => self.dispatch(context, ty, parsed.to_token_stream()),
+            None => Ok(parsed.to_token_stream()),
         }
-
-        tokens.push(quote! {
-            {
-                #(#filtered_variants),*
-            }
-        });
-
-        result.extend(tokens.into_iter());
-        Ok(result)
     }
-    pub fn variant(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let Variant {
+    pub fn trait_item_const(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let TraitItemConst {
             attrs,
+            const_token,
             ident,
-            fields,
-            discriminant,
-        } = parse2::<Variant>(tokens)?;
+            colon_token,
+            ty,
+            default,
+            semi_token,
+        } = parse2::<TraitItemConst>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
         let mut result = TokenStream::new();
         let mut tokens = Vec::<TokenStream>::new();
 
+        let new_ident = self.ident_callback(context, IdentRole::Item, &ident, "ident")?;
         tokens.push(quote! {
-            #(#attrs)*
-            #ident #fields
+            #new_attrs #const_token #new_ident #colon_token
         });
 
-        if let Some((eq_token, expr)) = discriminant {
-            let filtered_expr =
-                self.callback(context, CallbackType::Expr, expr.to_token_stream())?;
+        let new_ty = self.callback_in(context, CallbackType::Type, ty.to_token_stream(), "ty")?;
+        tokens.push(new_ty);
 
+        if let Some((eq_token, expr)) = default {
+            let filtered_expr =
+                self.callback_in(context, CallbackType::Expr, expr.to_token_stream(), "expr")?;
             tokens.push(quote! { #eq_token #filtered_expr });
         }
 
-        result.extend(tokens.into_iter());
+        tokens.push(quote! { #semi_token });
+
+        result.extend(tokens);
         Ok(result)
     }
-    pub fn item_extern_crate(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        // nothing to expand for now, essentially a no-op to parse the type and turn
-        // it back into tokens
-        let parsed = parse2::<ItemExternCrate>(tokens)?;
-        Ok(parsed.to_token_stream())
-    }
-    pub fn item_fn(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ItemFn {
+    pub fn trait_item_method(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let TraitItemMethod {
             attrs,
-            vis,
             sig,
-            block,
-        } = parse2::<ItemFn>(tokens)?;
+            default,
+            semi_token,
+        } = parse2::<TraitItemMethod>(tokens)?;
+        let context = &context.enter_fn(&sig);
+        let (outer_attrs, inner_attrs) = self.split_attributes(context, &attrs)?;
         let mut result = TokenStream::new();
         let mut tokens = Vec::<TokenStream>::new();
 
-        tokens.push(quote! {
-            #(#attrs)*
-            #vis #sig
-        });
+        tokens.push(quote! { #outer_attrs });
+
+        let new_sig = self.callback_in(
+            context,
+            CallbackType::Signature,
+            sig.to_token_stream(),
+            "sig",
+        )?;
+        tokens.push(new_sig);
+
+        if let Some(block) = default {
+            let _scope = context.enter_scope();
+            context.bind_inputs(&sig);
+
+            let new_block = self.callback_in(
+                context,
+                CallbackType::Block,
+                block.to_token_stream(),
+                "block",
+            )?;
+            tokens.push(inner_block(inner_attrs, new_block));
+        }
 
-        let filtered_block =
-            self.callback(context, CallbackType::Block, block.to_token_stream())?;
-        tokens.push(filtered_block);
+        if let Some(semi) = semi_token {
+            tokens.push(quote! { #semi });
+        }
 
-        result.extend(tokens.into_iter());
+        result.extend(tokens);
         Ok(result)
     }
-    pub fn item_foreign_mod(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ItemForeignMod {
+    pub fn trait_item_type(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let TraitItemType {
             attrs,
-            abi,
-            brace_token: _,
-            items,
-        } = parse2::<ItemForeignMod>(tokens)?;
+            type_token,
+            ident,
+            generics,
+            colon_token,
+            bounds,
+            default,
+            semi_token,
+        } = parse2::<TraitItemType>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
         let mut result = TokenStream::new();
         let mut tokens = Vec::<TokenStream>::new();
 
-        tokens.push(quote! {
-            #(#attrs)*
-            #abi
-        });
+        let new_ident = self.ident_callback(context, IdentRole::Item, &ident, "ident")?;
+        tokens.push(quote! { #new_attrs #type_token #new_ident });
 
-        let mut filtered_items = Vec::<TokenStream>::new();
+        let new_generics = self.callback_in(
+            context,
+            CallbackType::Generics,
+            generics.to_token_stream(),
+            "generics",
+        )?;
+        tokens.push(quote! { #new_generics #colon_token });
+        tokens.push(self.type_param_bounds(context, &bounds, "bounds")?);
 
-        for item in &items {
-            let filtered =
-                self.callback(context, CallbackType::ForeignItem, item.to_token_stream())?;
-            filtered_items.push(filtered);
+        if let Some((eq_token, default_ty)) = default {
+            let new_default = self.callback_in(
+                context,
+                CallbackType::Type,
+                default_ty.to_token_stream(),
+                "default",
+            )?;
+            tokens.push(quote! { #eq_token #new_default });
         }
 
-        tokens.push(quote! { #(#filtered_items)* });
+        tokens.push(self.where_clause_opt(context, &generics.where_clause)?);
+        tokens.push(quote! { #semi_token });
 
-        result.extend(tokens.into_iter());
+        result.extend(tokens);
         Ok(result)
     }
-    pub fn item_impl(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ItemImpl {
+    pub fn trait_item_macro(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let TraitItemMacro {
             attrs,
-            defaultness,
-            unsafety,
-            impl_token,
-            generics,
-            trait_,
-            self_ty,
-            brace_token: _,
-            items,
-        } = parse2::<ItemImpl>(tokens)?;
-        let mut result = TokenStream::new();
-        let mut tokens = Vec::<TokenStream>::new();
+            mac,
+            semi_token,
+        } = parse2::<TraitItemMacro>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
 
-        tokens.push(quote! { #(#attrs)* });
+        let new_mac =
+            self.callback_in(context, CallbackType::Macro, mac.to_token_stream(), "mac")?;
 
-        if let Some(default_token) = defaultness {
-            tokens.push(quote! { #default_token });
-        }
+        Ok(quote! { #new_attrs #new_mac #semi_token })
+    }
+    pub fn block(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let parsed = parse2::<Block>(tokens)?;
+        let stmts = parsed.stmts.clone();
+        let stmt_tokens =
+            self.callback_in(context, CallbackType::Stmts, quote! { #(#stmts)* }, "stmts")?;
 
-        if let Some(unsafe_token) = unsafety {
-            tokens.push(quote! { #unsafe_token });
-        }
+        Ok(quote! {
+            {
+                #stmt_tokens
+            }
+        })
+    }
+    /// Parse the given [`TokenStream`](TokenStream) as if it were a series of
+    /// statements.
+    ///
+    /// This essentially calls
+    /// [`syn::Block::parse_within`](syn::Block::parse_within) to parse the
+    /// statements and send the individual statements to the proper handler.
+    pub fn stmts(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let statements = Block::parse_within.parse2(tokens)?;
+        let mut new_statements = Vec::<TokenStream>::new();
 
-        tokens.push(quote! {
-            #impl_token #generics
-        });
+        // the items of a block are in scope throughout the block
+        let items = statements
+            .iter()
+            .filter_map(|stmt| match stmt {
+                Stmt::Item(item) => Some(item),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let context = context.declare(&items);
+        let _scope = context.enter_scope();
+
+        for (index, stmt) in statements.iter().enumerate() {
+            let new_stmt = self.callback_at(
+                &context,
+                CallbackType::Stmt,
+                stmt.to_token_stream(),
+                "stmts",
+                index,
+            )?;
+            new_statements.push(new_stmt);
 
-        if let Some((bang_opt, path, for_)) = trait_ {
-            if let Some(bang_token) = bang_opt {
-                tokens.push(quote! { #bang_token });
+            // the variables of a let statement are in scope from the next
+            // statement on
+            let local = match stmt {
+                Stmt::Local(local) => Some(local.clone()),
+                Stmt::Semi(Expr::Verbatim(_), _) => get_let_else(stmt.to_token_stream())
+                    .ok()
+                    .map(|(local, _, _)| local),
+                _ => None,
+            };
+
+            if let Some(local) = local {
+                context.bind(&local.pat, BindingSite::Local);
             }
-
-            tokens.push(quote! { #path #for_ });
         }
 
-        tokens.push(quote! { #self_ty });
-
-        let mut filtered_items = Vec::<TokenStream>::new();
-
-        for item in &items {
-            let filtered_item =
-                self.callback(context, CallbackType::ImplItem, item.to_token_stream())?;
-            filtered_items.push(filtered_item);
+        Ok(quote! { #(#new_statements)* })
+    }
+    pub fn stmt(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let parsed = get_stmt(tokens)?;
+
+        // let-else statements are only known to syn as verbatim expressions
+        if let Stmt::Semi(Expr::Verbatim(_), _) = parsed {
+            if get_let_else(parsed.to_token_stream()).is_ok() {
+                return self.callback_in(
+                    context,
+                    CallbackType::Local,
+                    parsed.to_token_stream(),
+                    "local",
+                );
+            }
         }
 
-        tokens.push(quote! {
-            {
-                #(#filtered_items)*
+        let result = match parsed {
+            Stmt::Local(ref local) => self.callback_in(
+                context,
+                CallbackType::Local,
+                local.to_token_stream(),
+                "local",
+            ),
+            Stmt::Item(ref item) => {
+                self.callback_in(context, CallbackType::Item, item.to_token_stream(), "item")
             }
-        });
+            Stmt::Expr(ref expr) => {
+                self.callback_in(context, CallbackType::Expr, expr.to_token_stream(), "expr")
+            }
+            Stmt::Semi(ref expr, _) => {
+                self.callback_in(context, CallbackType::Expr, expr.to_token_stream(), "semi")
+            }
+        }?;
 
-        result.extend(tokens.into_iter());
-        Ok(result)
-    }
-    pub fn item_macro(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<ItemMacro>(tokens)?;
-        Ok(parsed.to_token_stream())
-    }
-    pub fn item_macro2(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<ItemMacro2>(tokens)?;
-        Ok(parsed.to_token_stream())
+        if let Stmt::Semi(_, _) = parsed {
+            Ok(quote! { #result ; })
+        } else {
+            Ok(result)
+        }
     }
-    pub fn item_mod(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ItemMod {
+    pub fn local(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        // Local doesn't implement Parse, so we need to go one level higher to get a
+        // Local struct
+        let stmt = get_stmt(tokens.clone())?;
+        let local;
+        let mut diverge = None;
+
+        match stmt {
+            Stmt::Local(local_obj) => local = local_obj,
+            // let-else statements have to be parsed by hand
+            Stmt::Semi(Expr::Verbatim(_), _) => {
+                let (local_obj, else_token, block) = get_let_else(tokens)?;
+                local = local_obj;
+                diverge = Some(quote! { #else_token #block });
+            }
+            _ => {
+                return Err(Error::new(
+                    stmt.span(),
+                    "expected Local declaration in statement",
+                ))
+            }
+        }
+
+        let Local {
             attrs,
-            vis,
-            mod_token,
-            ident,
-            content,
-            semi,
-        } = parse2::<ItemMod>(tokens)?;
+            let_token,
+            pat,
+            init,
+            semi_token,
+        } = local;
+        let new_attrs = self.attributes(context, &attrs)?;
         let mut result = TokenStream::new();
         let mut tokens = Vec::<TokenStream>::new();
 
         tokens.push(quote! {
-            #(#attrs)*
-            #vis #mod_token #ident
+            #new_attrs
+            #let_token
         });
 
-        if let Some((_, items)) = content {
-            let mut filtered_items = Vec::<TokenStream>::new();
+        let new_pat;
 
-            for item in &items {
-                let new_item =
-                    self.callback(context, CallbackType::Item, item.to_token_stream())?;
-                filtered_items.push(new_item);
-            }
+        // PatType is a special case that only occurs within local statements, the
+        // Pat parser doesn't interpret this case as a result.
+        if let Pat::Type(pat_type) = pat {
+            new_pat = self.callback_in(
+                context,
+                CallbackType::PatType,
+                pat_type.to_token_stream(),
+                "pat",
+            )?;
+        } else {
+            new_pat = self.callback_in(context, CallbackType::Pat, pat.to_token_stream(), "pat")?;
+        }
+
+        tokens.push(quote! { #new_pat });
 
-            tokens.push(quote! { #(#filtered_items)* });
+        if let Some((eq_token, expr)) = init {
+            let new_expr =
+                self.callback_in(context, CallbackType::Expr, expr.to_token_stream(), "init")?;
+            tokens.push(quote! { #eq_token #new_expr });
         }
 
-        if let Some(semi_token) = semi {
-            tokens.push(quote! { #semi_token });
+        if let Some(else_tokens) = diverge {
+            let new_else =
+                self.callback_in(context, CallbackType::LocalElse, else_tokens, "diverge")?;
+            tokens.push(new_else);
         }
 
-        result.extend(tokens.into_iter());
+        tokens.push(quote! { #semi_token });
+
+        result.extend(tokens);
         Ok(result)
     }
-    pub fn item_static(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ItemStatic {
+    pub fn local_else(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let (else_token, block) = parse_local_else(tokens)?;
+
+        let new_block = self.callback_in(
+            context,
+            CallbackType::Block,
+            block.to_token_stream(),
+            "block",
+        )?;
+
+        Ok(quote! { #else_token #new_block })
+    }
+    pub fn pat(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let parsed = get_pat(tokens)?;
+
+        match parsed.callback_type() {
+            Some(ty) => self.dispatch(context, ty, parsed.to_token_stream()),
+            None => Ok(parsed.to_token_stream()),
+        }
+    }
+    pub fn pat_box(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let pat_box = PatBox::parse_node(tokens)?;
+
+        let PatBox {
             attrs,
-            vis,
-            static_token,
+            box_token,
+            pat,
+        } = pat_box;
+        let new_attrs = self.attributes(context, &attrs)?;
+        let new_pat = self.callback_in(context, CallbackType::Pat, pat.to_token_stream(), "pat")?;
+
+        Ok(quote! { #new_attrs #box_token #new_pat })
+    }
+    pub fn pat_ident(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let pat_ident = PatIdent::parse_node(tokens)?;
+
+        let PatIdent {
+            attrs,
+            by_ref,
             mutability,
             ident,
-            colon_token,
-            ty,
-            eq_token,
-            expr,
-            semi_token,
-        } = parse2::<ItemStatic>(tokens)?;
+            subpat,
+        } = pat_ident;
+        let new_attrs = self.attributes(context, &attrs)?;
         let mut result = TokenStream::new();
         let mut tokens = Vec::<TokenStream>::new();
 
-        tokens.push(quote! {
-            #(#attrs)*
-            #vis #static_token
-        });
+        tokens.push(quote! { #new_attrs });
+
+        if let Some(ref_token) = by_ref {
+            tokens.push(quote! { #ref_token });
+        }
 
         if let Some(mut_token) = mutability {
             tokens.push(quote! { #mut_token });
         }
 
-        tokens.push(quote! { #ident #colon_token #ty #eq_token });
-
-        let filtered_expr = self.callback(context, CallbackType::Expr, expr.to_token_stream())?;
-        tokens.push(quote! { #filtered_expr #semi_token });
+        tokens.push(self.ident_callback(context, IdentRole::Binding, &ident, "ident")?);
 
-        result.extend(tokens.into_iter());
+        let subpat_tokens = match subpat {
+            Some((at_token, pat)) => quote! { #at_token #pat },
+            None => TokenStream::new(),
+        };
+        let new_subpat = self.callback_in(
+            context,
+            CallbackType::PatIdentSubpat,
+            subpat_tokens,
+            "subpat",
+        )?;
+        tokens.push(new_subpat);
+
+        result.extend(tokens);
         Ok(result)
     }
-    pub fn item_struct(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<ItemStruct>(tokens)?;
-        Ok(parsed.to_token_stream())
-    }
-    pub fn item_trait(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ItemTrait {
-            attrs,
-            vis,
-            unsafety,
-            auto_token,
-            trait_token,
-            ident,
-            generics,
-            colon_token,
-            supertraits,
-            brace_token: _,
-            items,
-        } = parse2::<ItemTrait>(tokens)?;
-        let mut result = TokenStream::new();
-        let mut tokens = Vec::<TokenStream>::new();
+    pub fn pat_lit(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let pat_lit = PatLit::parse_node(tokens)?;
 
-        tokens.push(quote! { #(#attrs)* #vis });
+        let PatLit { attrs, expr } = pat_lit;
+        let new_attrs = self.attributes(context, &attrs)?;
+        let new_expr =
+            self.callback_in(context, CallbackType::Expr, expr.to_token_stream(), "expr")?;
 
-        if let Some(unsafe_keyword) = unsafety {
-            tokens.push(quote! { #unsafe_keyword });
-        }
+        Ok(quote! { #new_attrs #new_expr })
+    }
+    pub fn pat_macro(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let pat_macro = PatMacro::parse_node(tokens)?;
 
-        if let Some(auto) = auto_token {
-            tokens.push(quote! { #auto });
-        }
+        let PatMacro { attrs, mac } = pat_macro;
+        let new_attrs = self.attributes(context, &attrs)?;
+        let new_mac =
+            self.callback_in(context, CallbackType::Macro, mac.to_token_stream(), "mac")?;
 
-        tokens.push(quote! { #trait_token #ident #generics });
+        Ok(quote! { #new_attrs #new_mac })
+    }
+    pub fn pat_or(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let pat_or = PatOr::parse_node(tokens)?;
 
-        if let Some(colon) = colon_token {
-            tokens.push(quote! { #colon });
-        }
+        let PatOr {
+            attrs,
+            leading_vert,
+            cases,
+        } = pat_or;
+        let new_attrs = self.attributes(context, &attrs)?;
+        let mut result = TokenStream::new();
+        let mut tokens = Vec::<TokenStream>::new();
 
-        let mut traits = Vec::<TokenStream>::new();
+        tokens.push(quote! { #new_attrs });
 
-        for trait_ in &supertraits {
-            traits.push(trait_.to_token_stream());
+        if let Some(lead) = leading_vert {
+            tokens.push(quote! { #lead });
         }
 
-        tokens.push(quote! { #(#traits)+* });
-
-        let mut trait_items = Vec::<TokenStream>::new();
+        let mut new_patterns = Vec::<TokenStream>::new();
 
-        for item in &items {
-            let new_item =
-                self.callback(context, CallbackType::TraitItem, item.to_token_stream())?;
-            trait_items.push(new_item);
+        for (index, case) in cases.iter().enumerate() {
+            let new_pat = self.callback_at(
+                context,
+                CallbackType::Pat,
+                case.to_token_stream(),
+                "cases",
+                index,
+            )?;
+            new_patterns.push(new_pat);
         }
 
-        tokens.push(quote! {
-            {
-                #(#trait_items)*
-            }
-        });
+        tokens.push(quote! { #(#new_patterns)|* });
 
-        result.extend(tokens.into_iter());
+        result.extend(tokens);
         Ok(result)
     }
-    pub fn item_trait_alias(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<ItemTraitAlias>(tokens)?;
-        Ok(parsed.to_token_stream())
-    }
-    pub fn item_type(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<ItemType>(tokens)?;
-        Ok(parsed.to_token_stream())
-    }
-    pub fn item_union(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<ItemUnion>(tokens)?;
-        Ok(parsed.to_token_stream())
-    }
-    pub fn item_use(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<ItemUse>(tokens)?;
-        Ok(parsed.to_token_stream())
-    }
-    pub fn foreign_item(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<ForeignItem>(tokens)?;
+    pub fn pat_path(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let pat_path = PatPath::parse_node(tokens)?;
 
-        let result = match parsed {
-            ForeignItem::Fn(ref fn_) => {
-                self.callback(context, CallbackType::ForeignItemFn, fn_.to_token_stream())
-            }
-            ForeignItem::Static(ref static_) => self.callback(
-                context,
-                CallbackType::ForeignItemStatic,
-                static_.to_token_stream(),
-            ),
-            ForeignItem::Type(ref type_) => self.callback(
-                context,
-                CallbackType::ForeignItemType,
-                type_.to_token_stream(),
-            ),
-            ForeignItem::Macro(ref macro_) => self.callback(
-                context,
-                CallbackType::ForeignItemMacro,
-                macro_.to_token_stream(),
-            ),
-            ForeignItem::Verbatim(ref verbatim) => {
-                self.callback(context, CallbackType::Verbatim, verbatim.clone())
-            }
-            _ => Ok(parsed.to_token_stream()),
-        }?;
-        Ok(result)
-    }
-    pub fn foreign_item_fn(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<ForeignItemFn>(tokens)?;
-        Ok(parsed.to_token_stream())
-    }
-    pub fn foreign_item_static(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<ForeignItemStatic>(tokens)?;
-        Ok(parsed.to_token_stream())
-    }
-    pub fn foreign_item_type(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<ForeignItemType>(tokens)?;
-        Ok(parsed.to_token_stream())
-    }
-    pub fn foreign_item_macro(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<ForeignItemMacro>(tokens)?;
-        Ok(parsed.to_token_stream())
+        let PatPath { attrs, qself, path } = pat_path;
+        let new_attrs = self.attributes(context, &attrs)?;
+        let new_path = self.qualified_path(context, qself, path)?;
+
+        Ok(quote! { #new_attrs #new_path })
     }
-    pub fn impl_item(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<ImplItem>(tokens)?;
+    pub fn pat_range(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let pat_range = PatRange::parse_node(tokens)?;
 
-        let result = match parsed {
-            ImplItem::Const(ref const_) => self.callback(
-                context,
-                CallbackType::ImplItemConst,
-                const_.to_token_stream(),
-            ),
-            ImplItem::Method(ref method) => self.callback(
-                context,
-                CallbackType::ImplItemMethod,
-                method.to_token_stream(),
-            ),
-            ImplItem::Type(ref type_) => {
-                self.callback(context, CallbackType::ImplItemType, type_.to_token_stream())
-            }
-            ImplItem::Macro(ref macro_) => self.callback(
-                context,
-                CallbackType::ImplItemMacro,
-                macro_.to_token_stream(),
-            ),
-            ImplItem::Verbatim(ref verbatim) => {
-                self.callback(context, CallbackType::Verbatim, verbatim.clone())
-            }
-            _ => Ok(parsed.to_token_stream()),
-        }?;
-        Ok(result)
+        let PatRange {
+            attrs,
+            lo,
+            limits,
+            hi,
+        } = pat_range;
+        let new_attrs = self.attributes(context, &attrs)?;
+
+        let new_lo = self.callback_in(context, CallbackType::Expr, lo.to_token_stream(), "lo")?;
+        let new_hi = self.callback_in(context, CallbackType::Expr, hi.to_token_stream(), "hi")?;
+
+        Ok(quote! { #new_attrs #new_lo #limits #new_hi })
     }
-    pub fn impl_item_const(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ImplItemConst {
+    pub fn pat_reference(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let pat_ref = PatReference::parse_node(tokens)?;
+
+        let PatReference {
             attrs,
-            vis,
-            defaultness,
-            const_token,
-            ident,
-            colon_token,
-            ty,
-            eq_token,
-            expr,
-            semi_token,
-        } = parse2::<ImplItemConst>(tokens)?;
+            and_token,
+            mutability,
+            pat,
+        } = pat_ref;
+        let new_attrs = self.attributes(context, &attrs)?;
         let mut result = TokenStream::new();
         let mut tokens = Vec::<TokenStream>::new();
 
-        tokens.push(quote! { #(#attrs)* #vis });
+        tokens.push(quote! { #new_attrs #and_token });
 
-        if let Some(default_token) = defaultness {
-            tokens.push(quote! { #default_token });
+        if let Some(mut_token) = mutability {
+            tokens.push(quote! { #mut_token });
         }
 
-        tokens.push(quote! { #const_token #ident #colon_token #ty #eq_token });
+        let new_pat = self.callback_in(context, CallbackType::Pat, pat.to_token_stream(), "pat")?;
+        tokens.push(quote! { #new_pat });
 
-        let filtered_expr = self.callback(context, CallbackType::Expr, expr.to_token_stream())?;
+        result.extend(tokens);
+        Ok(result)
+    }
+    pub fn pat_rest(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        Ok(PatRest::parse_node(tokens)?.to_token_stream())
+    }
+    pub fn pat_slice(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let pat_slice = PatSlice::parse_node(tokens)?;
 
-        tokens.push(quote! { #filtered_expr #semi_token });
+        let PatSlice {
+            attrs,
+            bracket_token: _,
+            elems,
+        } = pat_slice;
+        let new_attrs = self.attributes(context, &attrs)?;
 
-        result.extend(tokens.into_iter());
-        Ok(result)
+        let mut new_elems = Vec::<TokenStream>::new();
+
+        for (index, elem) in elems.iter().enumerate() {
+            let new_elem = self.callback_at(
+                context,
+                CallbackType::Pat,
+                elem.to_token_stream(),
+                "elems",
+                index,
+            )?;
+            new_elems.push(new_elem);
+        }
+
+        let new_elems = zip_punctuated(&elems, new_elems);
+        Ok(quote! { #new_attrs [ #new_elems ] })
     }
-    pub fn impl_item_method(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ImplItemMethod {
+    pub fn pat_struct(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let pat_struct = PatStruct::parse_node(tokens)?;
+
+        let PatStruct {
             attrs,
-            vis,
-            defaultness,
-            sig,
-            block,
-        } = parse2::<ImplItemMethod>(tokens)?;
+            path,
+            brace_token: _,
+            fields,
+            dot2_token,
+        } = pat_struct;
+        let new_attrs = self.attributes(context, &attrs)?;
         let mut result = TokenStream::new();
         let mut tokens = Vec::<TokenStream>::new();
 
-        tokens.push(quote! { #(#attrs)* #vis });
+        let new_path =
+            self.callback_in(context, CallbackType::Path, path.to_token_stream(), "path")?;
+        tokens.push(quote! { #new_attrs #new_path });
 
-        if let Some(default_token) = defaultness {
-            tokens.push(quote! { #default_token });
+        let mut new_fields = Vec::<TokenStream>::new();
+
+        for (index, field) in fields.iter().enumerate() {
+            let new_field = self.callback_at(
+                context,
+                CallbackType::FieldPat,
+                field.to_token_stream(),
+                "fields",
+                index,
+            )?;
+
+            // fields are removed by returning an empty stream
+            if !new_field.is_empty() {
+                new_fields.push(new_field);
+            }
         }
 
-        tokens.push(quote! { #sig });
+        let comma = if !new_fields.is_empty() && (dot2_token.is_some() || fields.trailing_punct()) {
+            quote! { , }
+        } else {
+            TokenStream::new()
+        };
 
-        let filtered_block =
-            self.callback(context, CallbackType::Block, block.to_token_stream())?;
-        tokens.push(quote! { #filtered_block });
+        tokens.push(quote! { { #(#new_fields),* #comma #dot2_token } });
 
-        result.extend(tokens.into_iter());
+        result.extend(tokens);
         Ok(result)
     }
-    pub fn impl_item_type(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<ImplItemType>(tokens)?;
-        Ok(parsed.to_token_stream())
+    pub fn field_pat(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let FieldPat {
+            attrs,
+            member,
+            colon_token,
+            pat,
+        } = get_field_pat(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
+
+        let new_member = self.member(context, &member)?;
+        let new_pat = self.callback_in(context, CallbackType::Pat, pat.to_token_stream(), "pat")?;
+
+        let shorthand = colon_token.is_none()
+            && match get_pat(new_pat.clone()) {
+                Ok(Pat::Ident(PatIdent {
+                    ident,
+                    subpat: None,
+                    ..
+                })) => ident == new_member.to_string(),
+                _ => false,
+            };
+
+        // shorthand patterns like `Point { x }` stay shorthand only as long as the
+        // member still names the binding
+        if shorthand {
+            Ok(quote! { #new_attrs #new_pat })
+        } else {
+            let colon = colon_token.unwrap_or_default();
+            Ok(quote! { #new_attrs #new_member #colon #new_pat })
+        }
     }
-    pub fn impl_item_macro(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<ImplItemMacro>(tokens)?;
-        Ok(parsed.to_token_stream())
+    pub fn pat_ident_subpat(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        if tokens.is_empty() {
+            return Ok(tokens);
+        }
+
+        let (at_token, pat) = get_subpat(tokens)?;
+        let new_pat = self.callback_in(context, CallbackType::Pat, pat.to_token_stream(), "pat")?;
+
+        Ok(quote! { #at_token #new_pat })
     }
-    pub fn trait_item(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<TraitItem>(tokens)?;
+    pub fn pat_tuple(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let pat_tuple = PatTuple::parse_node(tokens)?;
 
-        let result = match parsed {
-            TraitItem::Const(ref const_) => self.callback(
-                context,
-                CallbackType::TraitItemConst,
-                const_.to_token_stream(),
-            ),
-            TraitItem::Method(ref method) => self.callback(
-                context,
-                CallbackType::TraitItemMethod,
-                method.to_token_stream(),
-            ),
-            TraitItem::Type(ref type_) => self.callback(
-                context,
-                CallbackType::TraitItemType,
-                type_.to_token_stream(),
-            ),
-            TraitItem::Macro(ref macro_) => self.callback(
+        let PatTuple {
+            attrs,
+            paren_token: _,
+            elems,
+        } = pat_tuple;
+        let new_attrs = self.attributes(context, &attrs)?;
+
+        let mut new_elems = Vec::<TokenStream>::new();
+
+        for (index, elem) in elems.iter().enumerate() {
+            let new_elem = self.callback_at(
                 context,
-                CallbackType::TraitItemMacro,
-                macro_.to_token_stream(),
-            ),
-            TraitItem::Verbatim(ref verbatim) => {
-                self.callback(context, CallbackType::Verbatim, verbatim.clone())
-            }
-            _ => Ok(parsed.to_token_stream()),
-        }?;
-        Ok(result)
+                CallbackType::Pat,
+                elem.to_token_stream(),
+                "elems",
+                index,
+            )?;
+            new_elems.push(new_elem);
+        }
+
+        let new_elems = zip_punctuated(&elems, new_elems);
+        Ok(quote! { #new_attrs ( #new_elems ) })
     }
-    pub fn trait_item_const(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let TraitItemConst {
+    pub fn pat_tuple_struct(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let pat_ts = PatTupleStruct::parse_node(tokens)?;
+
+        let PatTupleStruct { attrs, path, pat } = pat_ts;
+        let new_attrs = self.attributes(context, &attrs)?;
+
+        let new_path =
+            self.callback_in(context, CallbackType::Path, path.to_token_stream(), "path")?;
+        let new_pat = self.callback_in(
+            context,
+            CallbackType::PatTuple,
+            pat.to_token_stream(),
+            "pat",
+        )?;
+
+        Ok(quote! { #new_attrs #new_path #new_pat })
+    }
+    pub fn pat_type(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        // the Pat parser doesn't actually interpret this, we need to find a janky way
+        // to reinterpret this pattern cast, which is basically define a stub local
+        // statement, parse that, then get the PatType object out of it.
+        let PatType {
             attrs,
-            const_token,
-            ident,
+            pat,
             colon_token,
             ty,
-            default,
-            semi_token,
-        } = parse2::<TraitItemConst>(tokens)?;
+        } = get_pat_type(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
+
+        let new_pat = self.callback_in(context, CallbackType::Pat, pat.to_token_stream(), "pat")?;
+        let new_ty = self.callback_in(context, CallbackType::Type, ty.to_token_stream(), "ty")?;
+
+        Ok(quote! { #new_attrs #new_pat #colon_token #new_ty })
+    }
+    pub fn pat_wild(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        Ok(PatWild::parse_node(tokens)?.to_token_stream())
+    }
+    pub fn expr(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let parsed = parse2::<Expr>(tokens)?;
+
+        // newer syntax is only known to syn as verbatim tokens
+        if let Expr::Verbatim(ref verbatim) = parsed {
+            return self.dispatch(context, verbatim_expr_type(verbatim), verbatim.clone());
+        }
+
+        match parsed.callback_type() {
+            Some(ty) => self.dispatch(context, ty, parsed.to_token_stream()),
+            None => Ok(parsed.to_token_stream()),
+        }
+    }
+    pub fn expr_array(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let expr_array = ExprArray::parse_node(tokens)?;
+
+        let ExprArray {
+            attrs,
+            bracket_token: _,
+            elems,
+        } = expr_array;
+        let new_attrs = self.attributes(context, &attrs)?;
+
+        let mut new_elems = Vec::<TokenStream>::new();
+
+        for (index, elem) in elems.iter().enumerate() {
+            let new_elem = self.callback_at(
+                context,
+                CallbackType::Expr,
+                elem.to_token_stream(),
+                "elems",
+                index,
+            )?;
+            new_elems.push(new_elem);
+        }
+
+        let new_elems = zip_punctuated(&elems, new_elems);
+        Ok(quote! { #new_attrs [ #new_elems ] })
+    }
+    pub fn expr_assign(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ExprAssign {
+            attrs,
+            left,
+            eq_token,
+            right,
+        } = parse2::<ExprAssign>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
         let mut result = TokenStream::new();
         let mut tokens = Vec::<TokenStream>::new();
 
-        tokens.push(quote! {
-            #(#attrs)* #const_token #ident #colon_token #ty
-        });
+        tokens.push(quote! { #new_attrs });
 
-        if let Some((eq_token, expr)) = default {
-            let filtered_expr =
-                self.callback(context, CallbackType::Expr, expr.to_token_stream())?;
-            tokens.push(quote! { #eq_token #filtered_expr });
-        }
+        let new_left = self.operand_callback(context, &left, Precedence::Range, "left")?;
+        tokens.push(new_left);
 
-        tokens.push(quote! { #semi_token });
+        tokens.push(quote! { #eq_token });
+
+        let new_right = self.operand_callback(context, &right, Precedence::Assign, "right")?;
+        tokens.push(new_right);
 
-        result.extend(tokens.into_iter());
+        result.extend(tokens);
         Ok(result)
     }
-    pub fn trait_item_method(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let TraitItemMethod {
+    pub fn expr_assign_op(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ExprAssignOp {
             attrs,
-            sig,
-            default,
-            semi_token,
-        } = parse2::<TraitItemMethod>(tokens)?;
+            left,
+            op,
+            right,
+        } = parse2::<ExprAssignOp>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
         let mut result = TokenStream::new();
         let mut tokens = Vec::<TokenStream>::new();
 
-        tokens.push(quote! { #(#attrs)* #sig });
-
-        if let Some(block) = default {
-            let new_block = self.callback(context, CallbackType::Block, block.to_token_stream())?;
-            tokens.push(quote! { #new_block });
-        }
+        tokens.push(quote! { #new_attrs });
 
-        if let Some(semi) = semi_token {
-            tokens.push(quote! { #semi });
-        }
+        tokens.push(self.bin_op_callback(context, &left, &op, &right)?);
 
-        result.extend(tokens.into_iter());
+        result.extend(tokens);
         Ok(result)
     }
-    pub fn trait_item_type(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<TraitItemType>(tokens)?;
-        Ok(parsed.to_token_stream())
-    }
-    pub fn trait_item_macro(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<TraitItemMacro>(tokens)?;
-        Ok(parsed.to_token_stream())
-    }
-    pub fn block(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<Block>(tokens)?;
-        let stmts = parsed.stmts.clone();
-        let stmt_tokens = self.callback(context, CallbackType::Stmts, quote! { #(#stmts)* })?;
+    pub fn expr_async(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let expr_async = ExprAsync::parse_node(tokens)?;
 
-        Ok(quote! {
-            {
-                #stmt_tokens
-            }
-        })
-    }
-    /// Parse the given [`TokenStream`](TokenStream) as if it were a series of
-    /// statements.
-    ///
-    /// This essentially calls
-    /// [`syn::Block::parse_within`](syn::Block::parse_within) to parse the
-    /// statements and send the individual statements to the proper handler.
-    pub fn stmts(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let statements = Block::parse_within.parse2(tokens)?;
-        let mut new_statements = Vec::<TokenStream>::new();
+        let ExprAsync {
+            attrs,
+            async_token,
+            capture,
+            block,
+        } = expr_async;
+        let new_attrs = self.attributes(context, &attrs)?;
+        let mut result = TokenStream::new();
+        let mut tokens = Vec::<TokenStream>::new();
 
-        for stmt in &statements {
-            let new_stmt = self.callback(context, CallbackType::Stmt, stmt.to_token_stream())?;
-            new_statements.push(new_stmt);
-        }
+        tokens.push(quote! { #new_attrs #async_token });
 
-        Ok(quote! { #(#new_statements)* })
-    }
-    pub fn stmt(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<Stmt>(tokens)?;
+        if let Some(move_token) = capture {
+            tokens.push(quote! { #move_token });
+        }
 
-        let result = match parsed {
-            Stmt::Local(ref local) => {
-                self.callback(context, CallbackType::Local, local.to_token_stream())
-            }
-            Stmt::Item(ref item) => {
-                self.callback(context, CallbackType::Item, item.to_token_stream())
-            }
-            Stmt::Expr(ref expr) => {
-                self.callback(context, CallbackType::Expr, expr.to_token_stream())
-            }
-            Stmt::Semi(ref expr, _) => {
-                self.callback(context, CallbackType::Expr, expr.to_token_stream())
-            }
-        }?;
+        let new_block = self.callback_in(
+            context,
+            CallbackType::Block,
+            block.to_token_stream(),
+            "block",
+        )?;
+        tokens.push(new_block);
 
-        if let Stmt::Semi(_, _) = parsed {
-            Ok(quote! { #result ; })
-        } else {
-            Ok(result)
-        }
+        result.extend(tokens);
+        Ok(result)
     }
-    pub fn local(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        // Local doesn't implement Parse, so we need to go one level higher to get a
-        // Local struct
-        let stmt = parse2::<Stmt>(tokens)?;
-        let local;
+    pub fn expr_await(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ExprAwait {
+            attrs,
+            base,
+            dot_token,
+            await_token,
+        } = parse2::<ExprAwait>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
+        let mut result = TokenStream::new();
+        let mut tokens = Vec::<TokenStream>::new();
 
-        if let Stmt::Local(local_obj) = stmt {
-            local = local_obj;
-        } else {
-            return Err(Error::new(
-                stmt.span(),
-                "expected Local declaration in statement",
-            ));
-        }
+        tokens.push(quote! { #new_attrs });
 
-        let Local {
+        let new_expr = self.operand_callback(context, &base, Precedence::Postfix, "base")?;
+        tokens.push(quote! { #new_expr #dot_token #await_token });
+
+        result.extend(tokens);
+        Ok(result)
+    }
+    pub fn expr_binary(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ExprBinary {
             attrs,
-            let_token,
-            pat,
-            init,
-            semi_token,
-        } = local;
+            left,
+            op,
+            right,
+        } = parse2::<ExprBinary>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
         let mut result = TokenStream::new();
         let mut tokens = Vec::<TokenStream>::new();
 
-        tokens.push(quote! {
-            #(#attrs)*
-            #let_token
-        });
+        tokens.push(quote! { #new_attrs });
 
-        let new_pat;
+        tokens.push(self.bin_op_callback(context, &left, &op, &right)?);
 
-        // PatType is a special case that only occurs within local statements, the
-        // Pat parser doesn't interpret this case as a result.
-        if let Pat::Type(pat_type) = pat {
-            new_pat = self.callback(context, CallbackType::PatType, pat_type.to_token_stream())?;
-        } else {
-            new_pat = self.callback(context, CallbackType::Pat, pat.to_token_stream())?;
-        }
+        result.extend(tokens);
+        Ok(result)
+    }
+    pub fn expr_block(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ExprBlock {
+            attrs,
+            label,
+            block,
+        } = parse2::<ExprBlock>(tokens)?;
+        let (outer_attrs, inner_attrs) = self.split_attributes(context, &attrs)?;
+        let mut result = TokenStream::new();
+        let mut tokens = Vec::<TokenStream>::new();
 
-        tokens.push(quote! { #new_pat });
+        tokens.push(quote! { #outer_attrs });
 
-        if let Some((eq_token, expr)) = init {
-            let new_expr = self.callback(context, CallbackType::Expr, expr.to_token_stream())?;
-            tokens.push(quote! { #eq_token #new_expr });
+        if let Some(label_token) = label {
+            tokens.push(self.loop_label(context, &label_token)?);
         }
 
-        tokens.push(quote! { #semi_token });
+        let new_block = self.callback_in(
+            context,
+            CallbackType::Block,
+            block.to_token_stream(),
+            "block",
+        )?;
+        tokens.push(inner_block(inner_attrs, new_block));
 
-        result.extend(tokens.into_iter());
+        result.extend(tokens);
         Ok(result)
     }
-    pub fn pat(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<Pat>(tokens)?;
+    pub fn expr_box(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let expr_box = ExprBox::parse_node(tokens)?;
 
-        let result = match parsed {
-            Pat::Box(ref box_) => {
-                self.callback(context, CallbackType::PatBox, box_.to_token_stream())
-            }
-            Pat::Ident(ref ident) => {
-                self.callback(context, CallbackType::PatIdent, ident.to_token_stream())
-            }
-            Pat::Lit(ref lit) => {
-                self.callback(context, CallbackType::PatLit, lit.to_token_stream())
-            }
-            Pat::Macro(ref macro_) => {
-                self.callback(context, CallbackType::PatMacro, macro_.to_token_stream())
-            }
-            Pat::Or(ref or) => self.callback(context, CallbackType::PatOr, or.to_token_stream()),
-            Pat::Path(ref path) => {
-                self.callback(context, CallbackType::PatPath, path.to_token_stream())
-            }
-            Pat::Range(ref range) => {
-                self.callback(context, CallbackType::PatRange, range.to_token_stream())
-            }
-            Pat::Reference(ref ref_) => {
-                self.callback(context, CallbackType::PatReference, ref_.to_token_stream())
-            }
-            Pat::Rest(ref rest) => {
-                self.callback(context, CallbackType::PatRest, rest.to_token_stream())
-            }
-            Pat::Slice(ref slice) => {
-                self.callback(context, CallbackType::PatSlice, slice.to_token_stream())
-            }
-            Pat::Struct(ref struct_) => {
-                self.callback(context, CallbackType::PatStruct, struct_.to_token_stream())
-            }
-            Pat::Tuple(ref tuple) => {
-                self.callback(context, CallbackType::PatTuple, tuple.to_token_stream())
-            }
-            Pat::TupleStruct(ref struct_) => self.callback(
-                context,
-                CallbackType::PatTupleStruct,
-                struct_.to_token_stream(),
-            ),
-            Pat::Verbatim(ref verbatim) => {
-                self.callback(context, CallbackType::Verbatim, verbatim.clone())
-            }
-            Pat::Wild(ref wild) => {
-                self.callback(context, CallbackType::PatWild, wild.to_token_stream())
-            }
-            _ => Ok(parsed.to_token_stream()),
-        }?;
-        Ok(result)
-    }
-    pub fn pat_box(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<Pat>(tokens)?;
+        let ExprBox {
+            attrs,
+            box_token,
+            expr,
+        } = expr_box;
+        let new_attrs = self.attributes(context, &attrs)?;
 
-        if let Pat::Box(obj) = parsed {
-            Ok(obj.to_token_stream())
-        } else {
-            Err(Error::new(
-                parsed.span(),
-                "expected PatBox object in pattern",
-            ))
-        }
-    }
-    pub fn pat_ident(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<Pat>(tokens)?;
-        let pat_ident;
+        let new_expr = self.operand_callback(context, &expr, Precedence::Prefix, "expr")?;
 
-        if let Pat::Ident(ident) = parsed {
-            pat_ident = ident;
-        } else {
-            return Err(Error::new(
-                parsed.span(),
-                "expected PatIdent object in pattern",
-            ));
-        }
+        Ok(quote! { #new_attrs #box_token #new_expr })
+    }
+    pub fn expr_break(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let expr_break = ExprBreak::parse_node(tokens)?;
 
-        let PatIdent {
+        let ExprBreak {
             attrs,
-            by_ref,
-            mutability,
-            ident,
-            subpat,
-        } = pat_ident;
+            break_token,
+            label,
+            expr,
+        } = expr_break;
+        let new_attrs = self.attributes(context, &attrs)?;
         let mut result = TokenStream::new();
         let mut tokens = Vec::<TokenStream>::new();
 
-        tokens.push(quote! { #(#attrs)* });
-
-        if let Some(ref_token) = by_ref {
-            tokens.push(quote! { #ref_token });
-        }
+        tokens.push(quote! { #new_attrs #break_token });
 
-        if let Some(mut_token) = mutability {
-            tokens.push(quote! { #mut_token });
+        if let Some(label_token) = label {
+            let new_label = self.callback_in(
+                context,
+                CallbackType::Label,
+                label_token.to_token_stream(),
+                "label",
+            )?;
+            tokens.push(new_label);
         }
 
-        tokens.push(quote! { #ident });
-
-        if let Some((at_token, pat)) = subpat {
-            let new_pat = self.callback(context, CallbackType::Pat, pat.to_token_stream())?;
-            tokens.push(quote! { #at_token, #new_pat });
+        if let Some(expr_opt) = expr {
+            let new_expr = self.callback_in(
+                context,
+                CallbackType::Expr,
+                expr_opt.to_token_stream(),
+                "expr",
+            )?;
+            tokens.push(new_expr);
         }
 
-        result.extend(tokens.into_iter());
+        result.extend(tokens);
         Ok(result)
     }
-    pub fn pat_lit(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<Pat>(tokens)?;
-        let pat_lit;
-
-        if let Pat::Lit(lit) = parsed {
-            pat_lit = lit;
-        } else {
-            return Err(Error::new(
-                parsed.span(),
-                "expected PatLit object in pattern",
-            ));
-        }
-
-        let PatLit { attrs, expr } = pat_lit;
-        let new_expr = self.callback(context, CallbackType::Expr, expr.to_token_stream())?;
+    pub fn expr_call(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ExprCall {
+            attrs,
+            func,
+            paren_token: _,
+            args,
+        } = parse2::<ExprCall>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
 
-        Ok(quote! { #(#attrs)* #new_expr })
-    }
-    pub fn pat_macro(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<Pat>(tokens)?;
+        let new_func = self.operand_callback(context, &func, Precedence::Postfix, "func")?;
+        let mut new_args = Vec::<TokenStream>::new();
 
-        if let Pat::Macro(macro_) = parsed {
-            Ok(macro_.to_token_stream())
-        } else {
-            Err(Error::new(
-                parsed.span(),
-                "expected PatMacro object in pattern",
-            ))
+        for (index, arg) in args.iter().enumerate() {
+            let new_arg = self.callback_at(
+                context,
+                CallbackType::Expr,
+                arg.to_token_stream(),
+                "args",
+                index,
+            )?;
+            new_args.push(new_arg);
         }
+
+        let new_args = zip_punctuated(&args, new_args);
+        Ok(quote! { #new_attrs #new_func ( #new_args ) })
     }
-    pub fn pat_or(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<Pat>(tokens)?;
-        let pat_or;
+    pub fn expr_cast(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ExprCast {
+            attrs,
+            expr,
+            as_token,
+            ty,
+        } = parse2::<ExprCast>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
 
-        if let Pat::Or(or) = parsed {
-            pat_or = or;
-        } else {
-            return Err(Error::new(
-                parsed.span(),
-                "expected PatOr object in pattern",
-            ));
-        }
+        let new_expr = self.operand_callback(context, &expr, Precedence::Cast, "expr")?;
+        let new_ty = self.callback_in(context, CallbackType::Type, ty.to_token_stream(), "ty")?;
 
-        let PatOr {
+        Ok(quote! { #new_attrs #new_expr #as_token #new_ty })
+    }
+    pub fn expr_closure(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        // closures with binders are verbatim expressions, so the attributes,
+        // binder and closure are parsed separately
+        let parser = |input: parse::ParseStream| -> Result<(Vec<Attribute>, Option<BoundLifetimes>, ExprClosure)> {
+            Ok((input.call(Attribute::parse_outer)?, input.parse()?, input.parse()?))
+        };
+        let (leading_attrs, lifetimes, closure) = parser.parse2(tokens)?;
+        let ExprClosure {
             attrs,
-            leading_vert,
-            cases,
-        } = pat_or;
+            movability,
+            asyncness,
+            capture,
+            or1_token,
+            inputs,
+            or2_token,
+            output,
+            body,
+        } = closure;
+        let attrs = leading_attrs.into_iter().chain(attrs).collect::<Vec<_>>();
+        let new_attrs = self.attributes(context, &attrs)?;
         let mut result = TokenStream::new();
         let mut tokens = Vec::<TokenStream>::new();
 
-        tokens.push(quote! { #(#attrs)* });
-
-        if let Some(lead) = leading_vert {
-            tokens.push(quote! { #lead });
-        }
-
-        let mut new_patterns = Vec::<TokenStream>::new();
+        tokens.push(quote! { #new_attrs });
+        tokens.push(self.bound_lifetimes_opt(context, &lifetimes)?);
 
-        for case in &cases {
-            let new_pat = self.callback(context, CallbackType::Pat, case.to_token_stream())?;
-            new_patterns.push(new_pat);
+        if let Some(static_token) = movability {
+            tokens.push(quote! { #static_token });
         }
 
-        tokens.push(quote! { #(#new_patterns)|* });
-
-        result.extend(tokens.into_iter());
-        Ok(result)
-    }
-    pub fn pat_path(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<Pat>(tokens)?;
-
-        if let Pat::Path(path) = parsed {
-            Ok(path.to_token_stream())
-        } else {
-            Err(Error::new(
-                parsed.span(),
-                "expected PatPath object in pattern",
-            ))
+        if let Some(async_token) = asyncness {
+            tokens.push(quote! { #async_token });
         }
-    }
-    pub fn pat_range(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<Pat>(tokens)?;
-        let pat_range;
 
-        if let Pat::Range(range) = parsed {
-            pat_range = range;
-        } else {
-            return Err(Error::new(
-                parsed.span(),
-                "expected PatRange object in pattern",
-            ));
+        if let Some(move_token) = capture {
+            tokens.push(quote! { #move_token });
         }
 
-        let PatRange {
-            attrs,
-            lo,
-            limits,
-            hi,
-        } = pat_range;
-
-        let new_lo = self.callback(context, CallbackType::Expr, lo.to_token_stream())?;
-        let new_hi = self.callback(context, CallbackType::Expr, hi.to_token_stream())?;
-
-        Ok(quote! { #(#attrs)* #new_lo #limits #new_hi })
-    }
-    pub fn pat_reference(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<Pat>(tokens)?;
-        let pat_ref;
+        let mut new_inputs = Vec::<TokenStream>::new();
 
-        if let Pat::Reference(ref_) = parsed {
-            pat_ref = ref_;
-        } else {
-            return Err(Error::new(
-                parsed.span(),
-                "expected PatReference object in pattern",
-            ));
+        for (index, input) in inputs.iter().enumerate() {
+            // typed inputs are PatType objects, which the Pat parser doesn't understand
+            let new_input = if let Pat::Type(pat_type) = input {
+                self.callback_at(
+                    context,
+                    CallbackType::PatType,
+                    pat_type.to_token_stream(),
+                    "inputs",
+                    index,
+                )?
+            } else {
+                self.callback_at(
+                    context,
+                    CallbackType::Pat,
+                    input.to_token_stream(),
+                    "inputs",
+                    index,
+                )?
+            };
+            new_inputs.push(new_input);
         }
 
-        let PatReference {
-            attrs,
-            and_token,
-            mutability,
-            pat,
-        } = pat_ref;
-        let mut result = TokenStream::new();
-        let mut tokens = Vec::<TokenStream>::new();
+        let new_inputs = zip_punctuated(&inputs, new_inputs);
+        tokens.push(quote! { #or1_token #new_inputs #or2_token });
+        tokens.push(self.return_type(context, &output)?);
 
-        tokens.push(quote! { #(#attrs)* #and_token });
+        let _scope = context.enter_scope();
 
-        if let Some(mut_token) = mutability {
-            tokens.push(quote! { #mut_token });
+        for input in &inputs {
+            context.bind(input, BindingSite::ClosureParam);
         }
 
-        let new_pat = self.callback(context, CallbackType::Pat, pat.to_token_stream())?;
-        tokens.push(quote! { #new_pat });
+        let new_expr =
+            self.callback_in(context, CallbackType::Expr, body.to_token_stream(), "body")?;
+        tokens.push(new_expr);
 
-        result.extend(tokens.into_iter());
+        result.extend(tokens);
         Ok(result)
     }
-    pub fn pat_rest(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<Pat>(tokens)?;
+    pub fn expr_const(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let parser = |input: parse::ParseStream| -> Result<(Token![const], Block)> {
+            Ok((input.parse()?, input.parse()?))
+        };
+        let (const_token, block) = parser.parse2(tokens)?;
 
-        if let Pat::Rest(rest) = parsed {
-            Ok(rest.to_token_stream())
-        } else {
-            Err(Error::new(
-                parsed.span(),
-                "expected PatRest object in pattern",
-            ))
-        }
-    }
-    pub fn pat_slice(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<Pat>(tokens)?;
-        let pat_slice;
+        let new_block = self.callback_in(
+            context,
+            CallbackType::Block,
+            block.to_token_stream(),
+            "block",
+        )?;
 
-        if let Pat::Slice(slice) = parsed {
-            pat_slice = slice;
-        } else {
-            return Err(Error::new(
-                parsed.span(),
-                "expected PatSlice object in pattern",
-            ));
-        }
+        Ok(quote! { #const_token #new_block })
+    }
+    pub fn expr_continue(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let expr_continue = ExprContinue::parse_node(tokens)?;
 
-        let PatSlice {
+        let ExprContinue {
             attrs,
-            bracket_token: _,
-            elems,
-        } = pat_slice;
-
-        let mut new_elems = Vec::<TokenStream>::new();
+            continue_token,
+            label,
+        } = expr_continue;
+        let new_attrs = self.attributes(context, &attrs)?;
 
-        for elem in &elems {
-            let new_elem = self.callback(context, CallbackType::Pat, elem.to_token_stream())?;
-            new_elems.push(new_elem);
+        if let Some(label_token) = label {
+            let new_label = self.callback_in(
+                context,
+                CallbackType::Label,
+                label_token.to_token_stream(),
+                "label",
+            )?;
+            Ok(quote! { #new_attrs #continue_token #new_label })
+        } else {
+            Ok(quote! { #new_attrs #continue_token })
         }
-
-        Ok(quote! { #(#attrs)* [ #(#new_elems),* ] })
     }
-    pub fn pat_struct(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<Pat>(tokens)?;
-        let pat_struct;
+    pub fn expr_field(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ExprField {
+            attrs,
+            base,
+            dot_token,
+            member,
+        } = parse2::<ExprField>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
 
-        if let Pat::Struct(struct_) = parsed {
-            pat_struct = struct_;
-        } else {
-            return Err(Error::new(
-                parsed.span(),
-                "expected PatStruct object in pattern",
-            ));
-        }
+        let new_base = self.operand_callback(context, &base, Precedence::Postfix, "base")?;
+        let new_member = self.member(context, &member)?;
 
-        let PatStruct {
+        Ok(quote! { #new_attrs #new_base #dot_token #new_member })
+    }
+    pub fn expr_for_loop(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ExprForLoop {
             attrs,
-            path,
-            brace_token: _,
-            fields,
-            dot2_token,
-        } = pat_struct;
+            label,
+            for_token,
+            pat,
+            in_token,
+            expr,
+            body,
+        } = parse2::<ExprForLoop>(tokens)?;
+        let (outer_attrs, inner_attrs) = self.split_attributes(context, &attrs)?;
         let mut result = TokenStream::new();
         let mut tokens = Vec::<TokenStream>::new();
 
-        tokens.push(quote! { #(#attrs)* #path });
-
-        let mut new_fields = Vec::<TokenStream>::new();
-
-        for field in &fields {
-            let FieldPat {
-                attrs: field_attrs,
-                member,
-                colon_token,
-                pat,
-            } = field;
-            let mut field_result = TokenStream::new();
-            let mut field_tokens = Vec::<TokenStream>::new();
+        tokens.push(quote! { #outer_attrs });
 
-            field_tokens.push(quote! { #(#field_attrs)* #member });
+        if let Some(label_token) = label {
+            tokens.push(self.loop_label(context, &label_token)?);
+        }
 
-            if let Some(colon) = colon_token {
-                field_tokens.push(quote! { #colon });
-            }
+        let new_pat = self.callback_in(context, CallbackType::Pat, pat.to_token_stream(), "pat")?;
+        tokens.push(quote! { #for_token #new_pat #in_token });
 
-            let new_pat = self.callback(context, CallbackType::Pat, pat.to_token_stream())?;
-            field_tokens.push(quote! { #new_pat });
+        let new_expr =
+            self.callback_in(context, CallbackType::Expr, expr.to_token_stream(), "expr")?;
+        tokens.push(new_expr);
 
-            field_result.extend(field_tokens.into_iter());
-            new_fields.push(field_result);
-        }
+        let _scope = context.enter_scope();
+        context.bind(&pat, BindingSite::ForLoop);
 
-        if let Some(dot2) = dot2_token {
-            tokens.push(quote! { { #(#new_fields),* , #dot2 } });
-        } else {
-            tokens.push(quote! { { #(#new_fields),* } });
-        }
+        let new_block =
+            self.callback_in(context, CallbackType::Block, body.to_token_stream(), "body")?;
+        tokens.push(inner_block(inner_attrs, new_block));
 
-        result.extend(tokens.into_iter());
+        result.extend(tokens);
         Ok(result)
     }
-    pub fn pat_tuple(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<Pat>(tokens)?;
-        let pat_tuple;
-
-        if let Pat::Tuple(tuple) = parsed {
-            pat_tuple = tuple;
-        } else {
-            return Err(Error::new(
-                parsed.span(),
-                "expected PatTuple object in pattern",
-            ));
-        }
+    pub fn expr_group(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let expr_group = ExprGroup::parse_node(tokens)?;
 
-        let PatTuple {
+        let ExprGroup {
             attrs,
-            paren_token: _,
-            elems,
-        } = pat_tuple;
-
-        let mut new_elems = Vec::<TokenStream>::new();
+            group_token: _,
+            expr,
+        } = expr_group;
+        let new_attrs = self.attributes(context, &attrs)?;
 
-        for elem in &elems {
-            let new_elem = self.callback(context, CallbackType::Pat, elem.to_token_stream())?;
-            new_elems.push(new_elem);
-        }
+        let new_expr =
+            self.callback_in(context, CallbackType::Expr, expr.to_token_stream(), "expr")?;
 
-        Ok(quote! { #(#attrs)* ( #(#new_elems),* ) })
+        Ok(quote! { #new_attrs #new_expr })
     }
-    pub fn pat_tuple_struct(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<Pat>(tokens)?;
-        let pat_ts;
-
-        if let Pat::TupleStruct(ts) = parsed {
-            pat_ts = ts;
-        } else {
-            return Err(Error::new(
-                parsed.span(),
-                "expected PatTupleStruct object in pattern",
-            ));
-        }
+    pub fn expr_if(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ExprIf {
+            attrs,
+            if_token,
+            cond,
+            then_branch,
+            else_branch,
+        } = parse2::<ExprIf>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
+        let mut result = TokenStream::new();
+        let mut tokens = Vec::<TokenStream>::new();
 
-        let PatTupleStruct { attrs, path, pat } = pat_ts;
+        tokens.push(quote! { #new_attrs #if_token });
 
-        let new_pat = self.callback(context, CallbackType::PatTuple, pat.to_token_stream())?;
+        let new_expr =
+            self.callback_in(context, cond_type(&cond), cond.to_token_stream(), "cond")?;
+        tokens.push(new_expr);
 
-        Ok(quote! { #(#attrs)* #path #new_pat })
-    }
-    pub fn pat_type(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        // the Pat parser doesn't actually interpret this, we need to find a janky way
-        // to reinterpret this pattern cast, which is basically define a stub local
-        // statement, parse that, then get the PatType object out of it.
-        let PatType {
-            attrs,
-            pat,
-            colon_token,
-            ty,
-        } = get_pat_type(tokens)?;
+        let scope = context.enter_scope();
+        context.bind_cond(&cond, BindingSite::IfLet);
 
-        let new_pat = self.callback(context, CallbackType::Pat, pat.to_token_stream())?;
+        let new_block = self.callback_in(
+            context,
+            CallbackType::Block,
+            then_branch.to_token_stream(),
+            "then_branch",
+        )?;
+        tokens.push(new_block);
 
-        Ok(quote! { #(#attrs)* #new_pat #colon_token #ty })
-    }
-    pub fn pat_wild(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<Pat>(tokens)?;
+        // the variables of the condition aren't in scope in the else branch
+        drop(scope);
 
-        if let Pat::Wild(wild) = parsed {
-            Ok(wild.to_token_stream())
-        } else {
-            Err(Error::new(
-                parsed.span(),
-                "expected PatWild object in pattern",
-            ))
+        if let Some((else_token, else_expr)) = else_branch {
+            let new_expr = self.callback_in(
+                context,
+                CallbackType::Expr,
+                else_expr.to_token_stream(),
+                "else_branch",
+            )?;
+            tokens.push(quote! { #else_token #new_expr });
         }
-    }
-    pub fn expr(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<Expr>(tokens)?;
 
-        let result = match parsed {
-            Expr::Array(ref array) => {
-                self.callback(context, CallbackType::ExprArray, array.to_token_stream())
-            }
-            Expr::Assign(ref assign) => {
-                self.callback(context, CallbackType::ExprAssign, assign.to_token_stream())
-            }
-            Expr::AssignOp(ref assign) => self.callback(
-                context,
-                CallbackType::ExprAssignOp,
-                assign.to_token_stream(),
-            ),
-            Expr::Async(ref async_) => {
-                self.callback(context, CallbackType::ExprAsync, async_.to_token_stream())
-            }
-            Expr::Await(ref await_) => {
-                self.callback(context, CallbackType::ExprAwait, await_.to_token_stream())
-            }
-            Expr::Binary(ref binary) => {
-                self.callback(context, CallbackType::ExprBinary, binary.to_token_stream())
-            }
-            Expr::Block(ref block) => {
-                self.callback(context, CallbackType::ExprBlock, block.to_token_stream())
-            }
-            Expr::Box(ref box_) => {
-                self.callback(context, CallbackType::ExprBox, box_.to_token_stream())
-            }
-            Expr::Break(ref break_) => {
-                self.callback(context, CallbackType::ExprBreak, break_.to_token_stream())
-            }
-            Expr::Call(ref call) => {
-                self.callback(context, CallbackType::ExprCall, call.to_token_stream())
-            }
-            Expr::Cast(ref cast) => {
-                self.callback(context, CallbackType::ExprCast, cast.to_token_stream())
-            }
-            Expr::Closure(ref closure) => self.callback(
-                context,
-                CallbackType::ExprClosure,
-                closure.to_token_stream(),
-            ),
-            Expr::Continue(ref continue_) => self.callback(
-                context,
-                CallbackType::ExprContinue,
-                continue_.to_token_stream(),
-            ),
-            Expr::Field(ref field) => {
-                self.callback(context, CallbackType::ExprField, field.to_token_stream())
-            }
-            Expr::ForLoop(ref loop_) => {
-                self.callback(context, CallbackType::ExprForLoop, loop_.to_token_stream())
-            }
-            Expr::Group(ref group) => {
-                self.callback(context, CallbackType::ExprGroup, group.to_token_stream())
-            }
-            Expr::If(ref if_) => {
-                self.callback(context, CallbackType::ExprIf, if_.to_token_stream())
-            }
-            Expr::Index(ref index) => {
-                self.callback(context, CallbackType::ExprIndex, index.to_token_stream())
-            }
-            Expr::Let(ref let_) => {
-                self.callback(context, CallbackType::ExprLet, let_.to_token_stream())
-            }
-            Expr::Lit(ref lit) => {
-                self.callback(context, CallbackType::ExprLit, lit.to_token_stream())
-            }
-            Expr::Loop(ref loop_) => {
-                self.callback(context, CallbackType::ExprLoop, loop_.to_token_stream())
-            }
-            Expr::Macro(ref macro_) => {
-                self.callback(context, CallbackType::ExprMacro, macro_.to_token_stream())
-            }
-            Expr::Match(ref match_) => {
-                self.callback(context, CallbackType::ExprMatch, match_.to_token_stream())
-            }
-            Expr::MethodCall(ref method) => self.callback(
-                context,
-                CallbackType::ExprMethodCall,
-                method.to_token_stream(),
-            ),
-            Expr::Paren(ref paren) => {
-                self.callback(context, CallbackType::ExprParen, paren.to_token_stream())
-            }
-            Expr::Path(ref path) => {
-                self.callback(context, CallbackType::ExprPath, path.to_token_stream())
-            }
-            Expr::Range(ref range) => {
-                self.callback(context, CallbackType::ExprRange, range.to_token_stream())
-            }
-            Expr::Reference(ref ref_) => {
-                self.callback(context, CallbackType::ExprReference, ref_.to_token_stream())
-            }
-            Expr::Repeat(ref repeat) => {
-                self.callback(context, CallbackType::ExprRepeat, repeat.to_token_stream())
-            }
-            Expr::Return(ref ret) => {
-                self.callback(context, CallbackType::ExprReturn, ret.to_token_stream())
-            }
-            Expr::Struct(ref struct_) => {
-                self.callback(context, CallbackType::ExprStruct, struct_.to_token_stream())
-            }
-            Expr::Try(ref try_) => {
-                self.callback(context, CallbackType::ExprTry, try_.to_token_stream())
-            }
-            Expr::TryBlock(ref try_) => {
-                self.callback(context, CallbackType::ExprTryBlock, try_.to_token_stream())
-            }
-            Expr::Tuple(ref tuple) => {
-                self.callback(context, CallbackType::ExprTuple, tuple.to_token_stream())
-            }
-            Expr::Type(ref type_) => {
-                self.callback(context, CallbackType::ExprType, type_.to_token_stream())
-            }
-            Expr::Unary(ref unary) => {
-                self.callback(context, CallbackType::ExprUnary, unary.to_token_stream())
-            }
-            Expr::Unsafe(ref unsafe_) => {
-                self.callback(context, CallbackType::ExprUnsafe, unsafe_.to_token_stream())
-            }
-            Expr::Verbatim(ref verbatim) => {
-                self.callback(context, CallbackType::Verbatim, verbatim.clone())
-            }
-            Expr::While(ref while_) => {
-                self.callback(context, CallbackType::ExprWhile, while_.to_token_stream())
-            }
-            Expr::Yield(ref yield_) => {
-                self.callback(context, CallbackType::ExprYield, yield_.to_token_stream())
-            }
-            _ => Ok(parsed.to_token_stream()),
-        }?;
+        result.extend(tokens);
         Ok(result)
     }
-    pub fn expr_array(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprArray {
+    pub fn expr_index(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ExprIndex {
             attrs,
+            expr,
             bracket_token: _,
-            elems,
-        } = parse2::<ExprArray>(tokens)?;
+            index,
+        } = parse2::<ExprIndex>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
+
+        let new_expr = self.operand_callback(context, &expr, Precedence::Postfix, "expr")?;
+        let new_index = self.callback_in(
+            context,
+            CallbackType::Expr,
+            index.to_token_stream(),
+            "index",
+        )?;
+
+        Ok(quote! { #new_attrs #new_expr [ #new_index ] })
+    }
+    pub fn expr_infer(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let parsed = parse2::<Token![_]>(tokens)?;
+        Ok(parsed.to_token_stream())
+    }
+    pub fn expr_let(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ExprLet {
+            attrs,
+            let_token,
+            pat,
+            eq_token,
+            expr,
+        } = parse2::<ExprLet>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
 
-        let mut new_elems = Vec::<TokenStream>::new();
+        let new_pat = self.callback_in(context, CallbackType::Pat, pat.to_token_stream(), "pat")?;
+        // the scrutinee can't contain `&&` or `||`, which would join it to a
+        // let chain
+        let new_expr = self.operand_callback(context, &expr, Precedence::Compare, "expr")?;
 
-        for elem in &elems {
-            let new_elem = self.callback(context, CallbackType::Expr, elem.to_token_stream())?;
-            new_elems.push(new_elem);
-        }
+        Ok(quote! { #new_attrs #let_token #new_pat #eq_token #new_expr })
+    }
+    pub fn expr_lit(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let expr_lit = ExprLit::parse_node(tokens)?;
+
+        let ExprLit { attrs, lit } = expr_lit;
+        let new_attrs = self.attributes(context, &attrs)?;
 
-        Ok(quote! { #(#attrs)* [ #(#new_elems),* ] })
+        let new_lit = self.callback_in(context, CallbackType::Lit, lit.to_token_stream(), "lit")?;
+
+        Ok(quote! { #new_attrs #new_lit })
     }
-    pub fn expr_assign(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprAssign {
+    pub fn expr_loop(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ExprLoop {
             attrs,
-            left,
-            eq_token,
-            right,
-        } = parse2::<ExprAssign>(tokens)?;
+            label,
+            loop_token,
+            body,
+        } = parse2::<ExprLoop>(tokens)?;
+        let (outer_attrs, inner_attrs) = self.split_attributes(context, &attrs)?;
         let mut result = TokenStream::new();
         let mut tokens = Vec::<TokenStream>::new();
 
-        tokens.push(quote! { #(#attrs)* });
+        tokens.push(quote! { #outer_attrs });
 
-        let new_left = self.callback(context, CallbackType::Expr, left.to_token_stream())?;
-        tokens.push(new_left);
+        if let Some(label_token) = label {
+            tokens.push(self.loop_label(context, &label_token)?);
+        }
 
-        tokens.push(quote! { #eq_token });
+        tokens.push(quote! { #loop_token });
 
-        let new_right = self.callback(context, CallbackType::Expr, right.to_token_stream())?;
-        tokens.push(new_right);
+        let new_block =
+            self.callback_in(context, CallbackType::Block, body.to_token_stream(), "body")?;
+        tokens.push(inner_block(inner_attrs, new_block));
 
-        result.extend(tokens.into_iter());
+        result.extend(tokens);
         Ok(result)
     }
-    pub fn expr_assign_op(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprAssignOp {
-            attrs,
-            left,
-            op,
-            right,
-        } = parse2::<ExprAssignOp>(tokens)?;
-        let mut result = TokenStream::new();
-        let mut tokens = Vec::<TokenStream>::new();
-
-        tokens.push(quote! { #(#attrs)* });
+    pub fn expr_macro(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let expr_macro = ExprMacro::parse_node(tokens)?;
 
-        let new_left = self.callback(context, CallbackType::Expr, left.to_token_stream())?;
-        tokens.push(new_left);
-        tokens.push(quote! { #op });
+        let ExprMacro { attrs, mac } = expr_macro;
+        let new_attrs = self.attributes(context, &attrs)?;
 
-        let new_right = self.callback(context, CallbackType::Expr, right.to_token_stream())?;
-        tokens.push(new_right);
+        let new_mac =
+            self.callback_in(context, CallbackType::Macro, mac.to_token_stream(), "mac")?;
 
-        result.extend(tokens.into_iter());
-        Ok(result)
+        Ok(quote! { #new_attrs #new_mac })
     }
-    pub fn expr_async(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprAsync {
+    pub fn expr_match(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ExprMatch {
             attrs,
-            async_token,
-            capture,
-            block,
-        } = parse2::<ExprAsync>(tokens)?;
+            match_token,
+            expr,
+            brace_token: _,
+            arms,
+        } = parse2::<ExprMatch>(tokens)?;
+        let (outer_attrs, inner_attrs) = self.split_attributes(context, &attrs)?;
         let mut result = TokenStream::new();
         let mut tokens = Vec::<TokenStream>::new();
 
-        tokens.push(quote! { #(#attrs)* #async_token });
+        tokens.push(quote! { #outer_attrs #match_token });
 
-        if let Some(move_token) = capture {
-            tokens.push(quote! { #move_token });
+        let new_expr =
+            self.callback_in(context, CallbackType::Expr, expr.to_token_stream(), "expr")?;
+        tokens.push(new_expr);
+
+        let mut new_arms = Vec::<TokenStream>::new();
+
+        for (index, arm) in arms.iter().enumerate() {
+            let new_arm = self.callback_at(
+                context,
+                CallbackType::Arm,
+                arm.to_token_stream(),
+                "arms",
+                index,
+            )?;
+            new_arms.push(new_arm);
         }
 
-        let new_block = self.callback(context, CallbackType::Block, block.to_token_stream())?;
-        tokens.push(new_block);
+        tokens.push(quote! {
+            {
+                #inner_attrs
+                #(#new_arms)*
+            }
+        });
 
-        result.extend(tokens.into_iter());
+        result.extend(tokens);
         Ok(result)
     }
-    pub fn expr_await(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprAwait {
+    pub fn expr_method_call(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ExprMethodCall {
             attrs,
-            base,
+            receiver,
             dot_token,
-            await_token,
-        } = parse2::<ExprAwait>(tokens)?;
+            method,
+            turbofish,
+            paren_token: _,
+            args,
+        } = parse2::<ExprMethodCall>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
         let mut result = TokenStream::new();
         let mut tokens = Vec::<TokenStream>::new();
 
-        tokens.push(quote! { #(#attrs)* });
+        tokens.push(quote! { #new_attrs });
 
-        let new_expr = self.callback(context, CallbackType::Expr, base.to_token_stream())?;
-        tokens.push(quote! { #new_expr #dot_token #await_token });
+        let new_receiver =
+            self.operand_callback(context, &receiver, Precedence::Postfix, "receiver")?;
+        tokens.push(new_receiver);
+
+        let new_method = self.ident_callback(context, IdentRole::Method, &method, "method")?;
+        tokens.push(quote! { #dot_token #new_method });
+
+        if let Some(turbo) = turbofish {
+            let new_turbo = self.callback_in(
+                context,
+                CallbackType::MethodTurbofish,
+                turbo.to_token_stream(),
+                "turbofish",
+            )?;
+            tokens.push(new_turbo);
+        }
+
+        let mut new_args = Vec::<TokenStream>::new();
 
-        result.extend(tokens.into_iter());
+        for (index, arg) in args.iter().enumerate() {
+            let new_arg = self.callback_at(
+                context,
+                CallbackType::Expr,
+                arg.to_token_stream(),
+                "args",
+                index,
+            )?;
+            new_args.push(new_arg);
+        }
+
+        let new_args = zip_punctuated(&args, new_args);
+        tokens.push(quote! { ( #new_args ) });
+
+        result.extend(tokens);
         Ok(result)
     }
-    pub fn expr_binary(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprBinary {
+    pub fn expr_paren(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let expr_paren = ExprParen::parse_node(tokens)?;
+
+        let ExprParen {
             attrs,
-            left,
-            op,
-            right,
-        } = parse2::<ExprBinary>(tokens)?;
-        let mut result = TokenStream::new();
-        let mut tokens = Vec::<TokenStream>::new();
+            paren_token: _,
+            expr,
+        } = expr_paren;
+        let new_attrs = self.attributes(context, &attrs)?;
 
-        tokens.push(quote! { #(#attrs)* });
+        let new_expr =
+            self.callback_in(context, CallbackType::Expr, expr.to_token_stream(), "expr")?;
 
-        let new_left = self.callback(context, CallbackType::Expr, left.to_token_stream())?;
-        tokens.push(new_left);
-        tokens.push(quote! { #op });
+        Ok(quote! { #new_attrs ( #new_expr ) })
+    }
+    pub fn expr_path(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ExprPath { attrs, qself, path } = parse2::<ExprPath>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
 
-        let new_right = self.callback(context, CallbackType::Expr, right.to_token_stream())?;
-        tokens.push(new_right);
+        let new_path = self.qualified_path(context, qself, path)?;
 
-        result.extend(tokens.into_iter());
-        Ok(result)
+        Ok(quote! { #new_attrs #new_path })
     }
-    pub fn expr_block(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprBlock {
+    pub fn expr_range(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let expr_range = ExprRange::parse_node(tokens)?;
+
+        let ExprRange {
             attrs,
-            label,
-            block,
-        } = parse2::<ExprBlock>(tokens)?;
+            from,
+            limits,
+            to,
+        } = expr_range;
+        let new_attrs = self.attributes(context, &attrs)?;
         let mut result = TokenStream::new();
         let mut tokens = Vec::<TokenStream>::new();
 
-        tokens.push(quote! { #(#attrs)* });
+        tokens.push(quote! { #new_attrs });
 
-        if let Some(label_token) = label {
-            tokens.push(quote! { #label_token });
+        if let Some(from_expr) = from {
+            let new_expr = self.operand_callback(context, &from_expr, Precedence::Or, "from")?;
+            tokens.push(new_expr);
         }
 
-        let new_block = self.callback(context, CallbackType::Block, block.to_token_stream())?;
-        tokens.push(new_block);
+        tokens.push(quote! { #limits });
+
+        if let Some(to_expr) = to {
+            let new_expr = self.operand_callback(context, &to_expr, Precedence::Or, "to")?;
+            tokens.push(new_expr);
+        }
 
-        result.extend(tokens.into_iter());
+        result.extend(tokens);
         Ok(result)
     }
-    pub fn expr_box(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprBox {
-            attrs,
-            box_token,
-            expr,
-        } = parse2::<ExprBox>(tokens)?;
-
-        let new_expr = self.callback(context, CallbackType::Expr, expr.to_token_stream())?;
+    pub fn expr_reference(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let expr_reference = ExprReference::parse_node(tokens)?;
 
-        Ok(quote! { #(#attrs)* #box_token #new_expr })
-    }
-    pub fn expr_break(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprBreak {
+        let ExprReference {
             attrs,
-            break_token,
-            label,
+            and_token,
+            raw: _,
+            mutability,
             expr,
-        } = parse2::<ExprBreak>(tokens)?;
+        } = expr_reference;
+        let new_attrs = self.attributes(context, &attrs)?;
         let mut result = TokenStream::new();
         let mut tokens = Vec::<TokenStream>::new();
 
-        tokens.push(quote! { #(#attrs)* #break_token });
+        tokens.push(quote! { #new_attrs #and_token });
 
-        if let Some(label_token) = label {
-            tokens.push(quote! { #label_token });
+        if let Some(mut_token) = mutability {
+            tokens.push(quote! { #mut_token });
         }
 
-        if let Some(expr_opt) = expr {
-            let new_expr =
-                self.callback(context, CallbackType::Expr, expr_opt.to_token_stream())?;
-            tokens.push(new_expr);
-        }
+        let new_expr = self.operand_callback(context, &expr, Precedence::Prefix, "expr")?;
+        tokens.push(new_expr);
 
-        result.extend(tokens.into_iter());
+        result.extend(tokens);
         Ok(result)
     }
-    pub fn expr_call(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprCall {
-            attrs,
-            func,
-            paren_token: _,
-            args,
-        } = parse2::<ExprCall>(tokens)?;
+    pub fn expr_repeat(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let expr_repeat = ExprRepeat::parse_node(tokens)?;
 
-        let new_func = self.callback(context, CallbackType::Expr, func.to_token_stream())?;
-        let mut new_args = Vec::<TokenStream>::new();
+        let ExprRepeat {
+            attrs,
+            bracket_token: _,
+            expr,
+            semi_token,
+            len,
+        } = expr_repeat;
+        let new_attrs = self.attributes(context, &attrs)?;
 
-        for arg in &args {
-            let new_arg = self.callback(context, CallbackType::Expr, arg.to_token_stream())?;
-            new_args.push(new_arg);
-        }
+        let new_expr =
+            self.callback_in(context, CallbackType::Expr, expr.to_token_stream(), "expr")?;
+        let new_len =
+            self.callback_in(context, CallbackType::Expr, len.to_token_stream(), "len")?;
 
-        Ok(quote! { #(#attrs)* #new_func ( #(#new_args),* ) })
+        Ok(quote! { #new_attrs [ #new_expr #semi_token #new_len ] })
     }
-    pub fn expr_cast(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprCast {
+    pub fn expr_return(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let expr_return = ExprReturn::parse_node(tokens)?;
+
+        let ExprReturn {
             attrs,
+            return_token,
             expr,
-            as_token,
-            ty,
-        } = parse2::<ExprCast>(tokens)?;
+        } = expr_return;
+        let new_attrs = self.attributes(context, &attrs)?;
 
-        let new_expr = self.callback(context, CallbackType::Expr, expr.to_token_stream())?;
+        if let Some(ret_expr) = expr {
+            let new_expr = self.callback_in(
+                context,
+                CallbackType::Expr,
+                ret_expr.to_token_stream(),
+                "expr",
+            )?;
 
-        Ok(quote! { #(#attrs)* #new_expr #as_token #ty })
+            Ok(quote! { #new_attrs #return_token #new_expr })
+        } else {
+            Ok(quote! { #new_attrs #return_token })
+        }
     }
-    pub fn expr_closure(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprClosure {
+    pub fn expr_struct(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let expr_struct = ExprStruct::parse_node(tokens)?;
+
+        let ExprStruct {
             attrs,
-            movability,
-            asyncness,
-            capture,
-            or1_token,
-            inputs,
-            or2_token,
-            output,
-            body,
-        } = parse2::<ExprClosure>(tokens)?;
+            path,
+            brace_token: _,
+            fields,
+            dot2_token,
+            rest,
+        } = expr_struct;
+        let new_attrs = self.attributes(context, &attrs)?;
         let mut result = TokenStream::new();
         let mut tokens = Vec::<TokenStream>::new();
 
-        tokens.push(quote! { #(#attrs)* });
+        let new_path =
+            self.callback_in(context, CallbackType::Path, path.to_token_stream(), "path")?;
+        tokens.push(quote! { #new_attrs #new_path });
 
-        if let Some(static_token) = movability {
-            tokens.push(quote! { #static_token });
-        }
+        let mut new_fields = Vec::<TokenStream>::new();
 
-        if let Some(async_token) = asyncness {
-            tokens.push(quote! { #async_token });
+        for (index, field) in fields.iter().enumerate() {
+            let new_field = self.callback_at(
+                context,
+                CallbackType::FieldValue,
+                field.to_token_stream(),
+                "fields",
+                index,
+            )?;
+
+            // fields are removed by returning an empty stream
+            if !new_field.is_empty() {
+                new_fields.push(new_field);
+            }
         }
 
-        if let Some(move_token) = capture {
-            tokens.push(quote! { #move_token });
-        }
+        let comma = if !new_fields.is_empty() && (dot2_token.is_some() || fields.trailing_punct()) {
+            quote! { , }
+        } else {
+            TokenStream::new()
+        };
 
-        let mut new_inputs = Vec::<TokenStream>::new();
+        let new_rest = match rest {
+            Some(rest_expr) => self.callback_in(
+                context,
+                CallbackType::Expr,
+                rest_expr.to_token_stream(),
+                "rest",
+            )?,
+            None => TokenStream::new(),
+        };
 
-        for input in &inputs {
-            let new_input = self.callback(context, CallbackType::Pat, input.to_token_stream())?;
-            new_inputs.push(new_input);
-        }
+        tokens.push(quote! { { #(#new_fields),* #comma #dot2_token #new_rest } });
 
-        tokens.push(quote! { #or1_token #(#new_inputs),* #or2_token #output });
+        result.extend(tokens);
+        Ok(result)
+    }
+    pub fn expr_try(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ExprTry {
+            attrs,
+            expr,
+            question_token,
+        } = parse2::<ExprTry>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
 
-        let new_expr = self.callback(context, CallbackType::Expr, body.to_token_stream())?;
-        tokens.push(new_expr);
+        let new_expr = self.operand_callback(context, &expr, Precedence::Postfix, "expr")?;
 
-        result.extend(tokens.into_iter());
-        Ok(result)
+        Ok(quote! { #new_attrs #new_expr #question_token })
     }
-    pub fn expr_continue(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<ExprContinue>(tokens)?;
-        Ok(parsed.to_token_stream())
+    pub fn expr_try_block(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let expr_try_block = ExprTryBlock::parse_node(tokens)?;
+
+        let ExprTryBlock {
+            attrs,
+            try_token,
+            block,
+        } = expr_try_block;
+        let new_attrs = self.attributes(context, &attrs)?;
+
+        let new_block = self.callback_in(
+            context,
+            CallbackType::Block,
+            block.to_token_stream(),
+            "block",
+        )?;
+
+        Ok(quote! { #new_attrs #try_token #new_block })
     }
-    pub fn expr_field(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprField {
+    pub fn expr_tuple(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ExprTuple {
             attrs,
-            base,
-            dot_token,
-            member,
-        } = parse2::<ExprField>(tokens)?;
+            paren_token: _,
+            elems,
+        } = parse2::<ExprTuple>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
+
+        let mut new_elems = Vec::<TokenStream>::new();
 
-        let new_base = self.callback(context, CallbackType::Expr, base.to_token_stream())?;
+        for (index, elem) in elems.iter().enumerate() {
+            let new_elem = self.callback_at(
+                context,
+                CallbackType::Expr,
+                elem.to_token_stream(),
+                "elems",
+                index,
+            )?;
+            new_elems.push(new_elem);
+        }
 
-        Ok(quote! { #(#attrs)* #new_base #dot_token #member })
+        let new_elems = zip_punctuated(&elems, new_elems);
+        Ok(quote! { #new_attrs ( #new_elems ) })
     }
-    pub fn expr_for_loop(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprForLoop {
+    pub fn expr_type(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ExprType {
             attrs,
-            label,
-            for_token,
-            pat,
-            in_token,
             expr,
-            body,
-        } = parse2::<ExprForLoop>(tokens)?;
-        let mut result = TokenStream::new();
-        let mut tokens = Vec::<TokenStream>::new();
-
-        tokens.push(quote! { #(#attrs)* });
-
-        if let Some(label_token) = label {
-            tokens.push(quote! { #label_token });
-        }
+            colon_token,
+            ty,
+        } = parse2::<ExprType>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
 
-        let new_pat = self.callback(context, CallbackType::Pat, pat.to_token_stream())?;
-        tokens.push(quote! { #for_token #new_pat #in_token });
+        let new_expr =
+            self.callback_in(context, CallbackType::Expr, expr.to_token_stream(), "expr")?;
+        let new_ty = self.callback_in(context, CallbackType::Type, ty.to_token_stream(), "ty")?;
 
-        let new_expr = self.callback(context, CallbackType::Expr, expr.to_token_stream())?;
-        tokens.push(new_expr);
+        Ok(quote! { #new_attrs #new_expr #colon_token #new_ty })
+    }
+    pub fn expr_unary(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let expr_unary = ExprUnary::parse_node(tokens)?;
 
-        let new_block = self.callback(context, CallbackType::Block, body.to_token_stream())?;
-        tokens.push(new_block);
+        let ExprUnary { attrs, op, expr } = expr_unary;
+        let new_attrs = self.attributes(context, &attrs)?;
 
-        result.extend(tokens.into_iter());
-        Ok(result)
-    }
-    pub fn expr_group(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<Expr>(tokens)?;
-        let expr_group;
+        let new_expr = self.operand_callback(context, &expr, Precedence::Prefix, "expr")?;
+        let new_operation =
+            self.callback_in(context, CallbackType::UnOp, quote! { #op #new_expr }, "op")?;
 
-        if let Expr::Group(group) = parsed {
-            expr_group = group;
+        // like binary operators, a lone operator only replaces the operator
+        if let Ok(new_op) = parse2::<UnOp>(new_operation.clone()) {
+            Ok(quote! { #new_attrs #new_op #new_expr })
         } else {
-            return Err(Error::new(
-                parsed.span(),
-                "expected ExprGroup object in expression",
-            ));
+            Ok(quote! { #new_attrs #new_operation })
         }
+    }
+    pub fn expr_unsafe(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let expr_unsafe = ExprUnsafe::parse_node(tokens)?;
 
-        let ExprGroup {
+        let ExprUnsafe {
             attrs,
-            group_token: _,
-            expr,
-        } = expr_group;
-
-        let new_expr = self.callback(context, CallbackType::Expr, expr.to_token_stream())?;
-
-        Ok(quote! { #(#attrs)* #new_expr })
+            unsafe_token,
+            block,
+        } = expr_unsafe;
+
+        let (outer_attrs, inner_attrs) = self.split_attributes(context, &attrs)?;
+        let new_block = self.callback_in(
+            context,
+            CallbackType::Block,
+            block.to_token_stream(),
+            "block",
+        )?;
+        let new_block = inner_block(inner_attrs, new_block);
+
+        Ok(quote! { #outer_attrs #unsafe_token #new_block })
     }
-    pub fn expr_if(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprIf {
+    pub fn expr_while(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let ExprWhile {
             attrs,
-            if_token,
+            label,
+            while_token,
             cond,
-            then_branch,
-            else_branch,
-        } = parse2::<ExprIf>(tokens)?;
+            body,
+        } = parse2::<ExprWhile>(tokens)?;
+
+        let (outer_attrs, inner_attrs) = self.split_attributes(context, &attrs)?;
         let mut result = TokenStream::new();
         let mut tokens = Vec::<TokenStream>::new();
 
-        tokens.push(quote! { #(#attrs)* #if_token });
+        tokens.push(quote! { #outer_attrs });
 
-        let new_expr = self.callback(context, CallbackType::Expr, cond.to_token_stream())?;
-        tokens.push(new_expr);
+        if let Some(label_token) = label {
+            tokens.push(self.loop_label(context, &label_token)?);
+        }
 
-        let new_block =
-            self.callback(context, CallbackType::Block, then_branch.to_token_stream())?;
-        tokens.push(new_block);
+        let new_cond =
+            self.callback_in(context, cond_type(&cond), cond.to_token_stream(), "cond")?;
+        tokens.push(quote! { #while_token #new_cond });
 
-        if let Some((else_token, else_expr)) = else_branch {
-            let new_expr =
-                self.callback(context, CallbackType::Expr, else_expr.to_token_stream())?;
-            tokens.push(quote! { #else_token #new_expr });
-        }
+        let _scope = context.enter_scope();
+        context.bind_cond(&cond, BindingSite::WhileLet);
 
-        result.extend(tokens.into_iter());
+        let new_body =
+            self.callback_in(context, CallbackType::Block, body.to_token_stream(), "body")?;
+        tokens.push(inner_block(inner_attrs, new_body));
+
+        result.extend(tokens);
         Ok(result)
     }
-    pub fn expr_index(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprIndex {
-            attrs,
-            expr,
-            bracket_token: _,
-            index,
-        } = parse2::<ExprIndex>(tokens)?;
-
-        let new_expr = self.callback(context, CallbackType::Expr, expr.to_token_stream())?;
-        let new_index = self.callback(context, CallbackType::Expr, index.to_token_stream())?;
+    pub fn expr_yield(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let expr_yield = ExprYield::parse_node(tokens)?;
 
-        Ok(quote! { #(#attrs)* #new_expr [ #new_index ] })
-    }
-    pub fn expr_let(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprLet {
+        let ExprYield {
             attrs,
-            let_token,
-            pat,
-            eq_token,
+            yield_token,
             expr,
-        } = parse2::<ExprLet>(tokens)?;
+        } = expr_yield;
+        let new_attrs = self.attributes(context, &attrs)?;
 
-        let new_pat = self.callback(context, CallbackType::Pat, pat.to_token_stream())?;
-        let new_expr = self.callback(context, CallbackType::Expr, expr.to_token_stream())?;
+        if let Some(yield_expr) = expr {
+            let new_expr = self.callback_in(
+                context,
+                CallbackType::Expr,
+                yield_expr.to_token_stream(),
+                "expr",
+            )?;
 
-        Ok(quote! { #(#attrs)* #let_token #new_pat #eq_token #new_expr })
+            Ok(quote! { #new_attrs #yield_token #new_expr })
+        } else {
+            Ok(quote! { #new_attrs #yield_token })
+        }
     }
-    pub fn expr_lit(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprLit { attrs, lit } = parse2::<ExprLit>(tokens)?;
+    pub fn let_chain(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let conditions = get_let_chain(tokens)?;
+        let mut new_conditions = Vec::<TokenStream>::new();
+        let site = match context.position() {
+            Some(position) if position.parent == CallbackType::ExprWhile => BindingSite::WhileLet,
+            _ => BindingSite::IfLet,
+        };
+        let _scope = context.enter_scope();
 
-        let new_lit = self.callback(context, CallbackType::Lit, lit.to_token_stream())?;
+        for (index, condition) in conditions.iter().enumerate() {
+            let new_condition = self.callback_at(
+                context,
+                CallbackType::Expr,
+                condition.to_token_stream(),
+                "conditions",
+                index,
+            )?;
+            new_conditions.push(new_condition);
+
+            // the variables of a let are in scope in the conditions after it
+            context.bind_cond(condition, site);
+        }
 
-        Ok(quote! { #(#attrs)* #new_lit })
+        Ok(quote! { #(#new_conditions)&&* })
     }
-    pub fn expr_loop(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprLoop {
+    pub fn arm(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let Arm {
             attrs,
-            label,
-            loop_token,
+            pat,
+            guard,
+            fat_arrow_token,
             body,
-        } = parse2::<ExprLoop>(tokens)?;
+            comma,
+        } = parse2::<Arm>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
         let mut result = TokenStream::new();
         let mut tokens = Vec::<TokenStream>::new();
 
-        tokens.push(quote! { #(#attrs)* });
+        tokens.push(quote! { #new_attrs });
 
-        if let Some(label_token) = label {
-            tokens.push(quote! { #label_token });
+        let new_pat = self.callback_in(context, CallbackType::Pat, pat.to_token_stream(), "pat")?;
+        tokens.push(new_pat);
+
+        let _scope = context.enter_scope();
+        context.bind(&pat, BindingSite::MatchArm);
+
+        if let Some((if_token, if_expr)) = guard {
+            let new_expr = self.callback_in(
+                context,
+                CallbackType::Expr,
+                if_expr.to_token_stream(),
+                "guard",
+            )?;
+            tokens.push(quote! { #if_token #new_expr });
         }
 
-        tokens.push(quote! { #loop_token });
+        tokens.push(quote! { #fat_arrow_token });
 
-        let new_block = self.callback(context, CallbackType::Block, body.to_token_stream())?;
-        tokens.push(new_block);
+        let new_expr =
+            self.callback_in(context, CallbackType::Expr, body.to_token_stream(), "body")?;
+        tokens.push(new_expr);
+
+        if let Some(comma_token) = comma {
+            tokens.push(quote! { #comma_token });
+        }
 
-        result.extend(tokens.into_iter());
+        result.extend(tokens);
         Ok(result)
     }
-    pub fn expr_macro(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<ExprMacro>(tokens)?;
+    pub fn bin_op(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        get_bin_op(tokens.clone())?;
+        Ok(tokens)
+    }
+    pub fn un_op(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        get_un_op(tokens.clone())?;
+        Ok(tokens)
+    }
+    pub fn lit(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let parsed = parse2::<Lit>(tokens)?;
+
+        match parsed.callback_type() {
+            Some(ty) => self.dispatch(context, ty, parsed.to_token_stream()),
+            None => Ok(parsed.to_token_stream()),
+        }
+    }
+    pub fn lit_str(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let parsed = parse2::<LitStr>(tokens)?;
+        Ok(parsed.to_token_stream())
+    }
+    pub fn lit_byte_str(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let parsed = parse2::<LitByteStr>(tokens)?;
         Ok(parsed.to_token_stream())
     }
-    pub fn expr_match(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprMatch {
+    pub fn lit_byte(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let parsed = parse2::<LitByte>(tokens)?;
+        Ok(parsed.to_token_stream())
+    }
+    pub fn lit_char(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let parsed = parse2::<LitChar>(tokens)?;
+        Ok(parsed.to_token_stream())
+    }
+    pub fn lit_int(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let parsed = parse2::<LitInt>(tokens)?;
+        Ok(parsed.to_token_stream())
+    }
+    pub fn lit_float(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let parsed = parse2::<LitFloat>(tokens)?;
+        Ok(parsed.to_token_stream())
+    }
+    pub fn lit_bool(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let parsed = parse2::<LitBool>(tokens)?;
+        Ok(parsed.to_token_stream())
+    }
+    pub fn field_value(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let FieldValue {
             attrs,
-            match_token,
+            member,
+            colon_token,
             expr,
-            brace_token: _,
-            arms,
-        } = parse2::<ExprMatch>(tokens)?;
-        let mut result = TokenStream::new();
-        let mut tokens = Vec::<TokenStream>::new();
-
-        tokens.push(quote! { #(#attrs)* #match_token });
+        } = parse2::<FieldValue>(tokens)?;
+        let new_attrs = self.attributes(context, &attrs)?;
 
-        let new_expr = self.callback(context, CallbackType::Expr, expr.to_token_stream())?;
-        tokens.push(new_expr);
+        let new_member = self.member(context, &member)?;
+        let new_expr =
+            self.callback_in(context, CallbackType::Expr, expr.to_token_stream(), "expr")?;
 
-        let mut new_arms = Vec::<TokenStream>::new();
+        // shorthand initializers like `Point { x }` stay shorthand only as long as
+        // the member and the expression still agree
+        if colon_token.is_none() && new_member.to_string() == new_expr.to_string() {
+            Ok(quote! { #new_attrs #new_member })
+        } else {
+            let colon = colon_token.unwrap_or_default();
+            Ok(quote! { #new_attrs #new_member #colon #new_expr })
+        }
+    }
+    pub fn type_(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let parsed = parse2::<Type>(tokens)?;
 
-        for arm in &arms {
-            let new_arm = self.callback(context, CallbackType::Arm, arm.to_token_stream())?;
-            new_arms.push(new_arm);
+        match parsed.callback_type() {
+            Some(ty) => self.dispatch(context, ty, parsed.to_token_stream()),
+            None => Ok(parsed.to_token_stream()),
         }
+    }
+    pub fn type_array(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let TypeArray {
+            bracket_token: _,
+            elem,
+            semi_token,
+            len,
+        } = parse2::<TypeArray>(tokens)?;
 
-        tokens.push(quote! {
-            {
-                #(#new_arms)*
-            }
-        });
+        let new_elem =
+            self.callback_in(context, CallbackType::Type, elem.to_token_stream(), "elem")?;
+        let new_len =
+            self.callback_in(context, CallbackType::Expr, len.to_token_stream(), "len")?;
 
-        result.extend(tokens.into_iter());
-        Ok(result)
+        Ok(quote! { [ #new_elem #semi_token #new_len ] })
     }
-    pub fn expr_method_call(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprMethodCall {
-            attrs,
-            receiver,
-            dot_token,
-            method,
-            turbofish,
+    pub fn type_bare_fn(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let TypeBareFn {
+            lifetimes,
+            unsafety,
+            abi,
+            fn_token,
             paren_token: _,
-            args,
-        } = parse2::<ExprMethodCall>(tokens)?;
+            inputs,
+            variadic,
+            output,
+        } = parse2::<TypeBareFn>(tokens)?;
         let mut result = TokenStream::new();
         let mut tokens = Vec::<TokenStream>::new();
 
-        tokens.push(quote! { #(#attrs)* });
+        tokens.push(self.bound_lifetimes_opt(context, &lifetimes)?);
+        tokens.push(quote! { #unsafety #abi #fn_token });
 
-        let new_receiver =
-            self.callback(context, CallbackType::Expr, receiver.to_token_stream())?;
-        tokens.push(new_receiver);
-
-        tokens.push(quote! { #dot_token #method });
+        let mut new_inputs = Vec::<TokenStream>::new();
 
-        if let Some(turbo) = turbofish {
-            tokens.push(quote! { #turbo });
+        for (index, input) in inputs.iter().enumerate() {
+            let BareFnArg { attrs, name, ty } = input;
+            let new_attrs = self.attributes(context, attrs)?;
+            let new_ty = self.callback_at(
+                context,
+                CallbackType::Type,
+                ty.to_token_stream(),
+                "inputs",
+                index,
+            )?;
+
+            if let Some((ident, colon)) = name {
+                let new_ident = self.callback_with_role(
+                    context,
+                    CallbackType::Ident,
+                    ident.to_token_stream(),
+                    Some(IdentRole::Binding),
+                    Slot::Field("inputs", Some(index)),
+                )?;
+                new_inputs.push(quote! { #new_attrs #new_ident #colon #new_ty });
+            } else {
+                new_inputs.push(quote! { #new_attrs #new_ty });
+            }
         }
 
-        let mut new_args = Vec::<TokenStream>::new();
+        let mut args = zip_punctuated(&inputs, new_inputs);
 
-        for arg in &args {
-            let new_arg = self.callback(context, CallbackType::Expr, arg.to_token_stream())?;
-            new_args.push(new_arg);
+        if let Some(variadic_token) = variadic {
+            if !inputs.empty_or_trailing() {
+                args.extend(quote! { , });
+            }
+
+            args.extend(quote! { #variadic_token });
         }
 
-        tokens.push(quote! { ( #(#new_args),* ) });
+        tokens.push(quote! { ( #args ) });
+        tokens.push(self.return_type(context, &output)?);
 
-        result.extend(tokens.into_iter());
+        result.extend(tokens);
         Ok(result)
     }
-    pub fn expr_paren(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprParen {
-            attrs,
-            paren_token: _,
-            expr,
-        } = parse2::<ExprParen>(tokens)?;
+    pub fn type_group(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let TypeGroup { group_token, elem } = parse2::<TypeGroup>(tokens)?;
+
+        let new_elem =
+            self.callback_in(context, CallbackType::Type, elem.to_token_stream(), "elem")?;
+        let mut group = Group::new(Delimiter::None, new_elem);
+        group.set_span(group_token.span);
+
+        Ok(group.to_token_stream())
+    }
+    pub fn type_impl_trait(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let TypeImplTrait { impl_token, bounds } = parse2::<TypeImplTrait>(tokens)?;
 
-        let new_expr = self.callback(context, CallbackType::Expr, expr.to_token_stream())?;
+        let new_bounds = self.type_param_bounds(context, &bounds, "bounds")?;
 
-        Ok(quote! { #(#attrs)* ( #new_expr ) })
+        Ok(quote! { #impl_token #new_bounds })
     }
-    pub fn expr_path(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<ExprPath>(tokens)?;
+    pub fn type_infer(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let parsed = parse2::<TypeInfer>(tokens)?;
         Ok(parsed.to_token_stream())
     }
-    pub fn expr_range(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprRange {
-            attrs,
-            from,
-            limits,
-            to,
-        } = parse2::<ExprRange>(tokens)?;
-        let mut result = TokenStream::new();
-        let mut tokens = Vec::<TokenStream>::new();
+    pub fn type_macro(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let TypeMacro { mac } = parse2::<TypeMacro>(tokens)?;
+        self.callback_in(context, CallbackType::Macro, mac.to_token_stream(), "mac")
+    }
+    pub fn type_never(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let parsed = parse2::<TypeNever>(tokens)?;
+        Ok(parsed.to_token_stream())
+    }
+    pub fn type_paren(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        // TypeParen's own parser doesn't accept bounds such as `(dyn A + 'a)`.
+        let parsed = parse2::<Type>(tokens)?;
+        let elem;
+
+        if let Type::Paren(TypeParen {
+            elem: paren_elem, ..
+        }) = parsed
+        {
+            elem = paren_elem;
+        } else {
+            return Err(Error::new(parsed.span(), "expected TypeParen object"));
+        }
 
-        tokens.push(quote! { #(#attrs)* });
+        let new_elem =
+            self.callback_in(context, CallbackType::Type, elem.to_token_stream(), "elem")?;
 
-        if let Some(from_expr) = from {
-            let new_expr =
-                self.callback(context, CallbackType::Expr, from_expr.to_token_stream())?;
-            tokens.push(new_expr);
+        Ok(quote! { ( #new_elem ) })
+    }
+    pub fn type_path(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let TypePath { qself, path } = parse2::<TypePath>(tokens)?;
+        self.qualified_path(context, qself, path)
+    }
+    pub fn type_ptr(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let TypePtr {
+            star_token,
+            const_token,
+            mutability,
+            elem,
+        } = parse2::<TypePtr>(tokens)?;
+
+        let new_elem =
+            self.callback_in(context, CallbackType::Type, elem.to_token_stream(), "elem")?;
+
+        Ok(quote! { #star_token #const_token #mutability #new_elem })
+    }
+    pub fn type_reference(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let TypeReference {
+            and_token,
+            lifetime,
+            mutability,
+            elem,
+        } = parse2::<TypeReference>(tokens)?;
+
+        let new_lifetime = self.lifetime_opt(context, &lifetime)?;
+        let new_elem =
+            self.callback_in(context, CallbackType::Type, elem.to_token_stream(), "elem")?;
+
+        Ok(quote! { #and_token #new_lifetime #mutability #new_elem })
+    }
+    pub fn type_slice(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let TypeSlice {
+            bracket_token: _,
+            elem,
+        } = parse2::<TypeSlice>(tokens)?;
+
+        let new_elem =
+            self.callback_in(context, CallbackType::Type, elem.to_token_stream(), "elem")?;
+
+        Ok(quote! { [ #new_elem ] })
+    }
+    pub fn type_trait_object(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let TypeTraitObject { dyn_token, bounds } = parse2::<TypeTraitObject>(tokens)?;
+
+        let new_bounds = self.type_param_bounds(context, &bounds, "bounds")?;
+
+        Ok(quote! { #dyn_token #new_bounds })
+    }
+    pub fn type_tuple(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let TypeTuple {
+            paren_token: _,
+            elems,
+        } = parse2::<TypeTuple>(tokens)?;
+
+        let mut new_elems = Vec::<TokenStream>::new();
+
+        for (index, elem) in elems.iter().enumerate() {
+            let new_elem = self.callback_at(
+                context,
+                CallbackType::Type,
+                elem.to_token_stream(),
+                "elems",
+                index,
+            )?;
+            new_elems.push(new_elem);
         }
 
-        tokens.push(quote! { #limits });
+        let new_elems = zip_punctuated(&elems, new_elems);
 
-        if let Some(to_expr) = to {
-            let new_expr = self.callback(context, CallbackType::Expr, to_expr.to_token_stream())?;
-            tokens.push(new_expr);
+        Ok(quote! { ( #new_elems ) })
+    }
+    pub fn type_param_bound(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let parsed = parse2::<TypeParamBound>(tokens)?;
+
+        match parsed {
+            TypeParamBound::Trait(TraitBound {
+                paren_token,
+                modifier,
+                lifetimes,
+                path,
+            }) => {
+                let new_lifetimes = self.bound_lifetimes_opt(context, &lifetimes)?;
+                let new_path =
+                    self.callback_in(context, CallbackType::Path, path.to_token_stream(), "path")?;
+                let bound = quote! { #modifier #new_lifetimes #new_path };
+
+                if paren_token.is_some() {
+                    Ok(quote! { ( #bound ) })
+                } else {
+                    Ok(bound)
+                }
+            }
+            TypeParamBound::Lifetime(lifetime) => self.callback_in(
+                context,
+                CallbackType::Lifetime,
+                lifetime.to_token_stream(),
+                "lifetime",
+            ),
         }
-
-        result.extend(tokens.into_iter());
-        Ok(result)
     }
-    pub fn expr_reference(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprReference {
-            attrs,
-            and_token,
-            raw: _,
-            mutability,
-            expr,
-        } = parse2::<ExprReference>(tokens)?;
-        let mut result = TokenStream::new();
-        let mut tokens = Vec::<TokenStream>::new();
+    pub fn generics(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let Generics {
+            lt_token,
+            params,
+            gt_token,
+            where_clause: _,
+        } = parse2::<Generics>(tokens)?;
+
+        let mut new_params = Vec::<TokenStream>::new();
+
+        for (index, param) in params.iter().enumerate() {
+            let new_param = self.callback_at(
+                context,
+                CallbackType::GenericParam,
+                param.to_token_stream(),
+                "params",
+                index,
+            )?;
+            new_params.push(new_param);
+        }
 
-        tokens.push(quote! { #(#attrs)* #and_token });
+        let new_params = zip_punctuated(&params, new_params);
 
-        if let Some(mut_token) = mutability {
-            tokens.push(quote! { #mut_token });
+        Ok(quote! { #lt_token #new_params #gt_token })
+    }
+    pub fn generic_param(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let parsed = parse2::<GenericParam>(tokens)?;
+
+        match parsed {
+            GenericParam::Type(TypeParam {
+                attrs,
+                ident,
+                colon_token,
+                bounds,
+                eq_token,
+                default,
+            }) => {
+                let new_attrs = self.attributes(context, &attrs)?;
+                let mut result = TokenStream::new();
+                let mut tokens = Vec::<TokenStream>::new();
+
+                let new_ident = self.ident_callback(context, IdentRole::Item, &ident, "ident")?;
+                let new_bounds = self.type_param_bounds(context, &bounds, "bounds")?;
+                tokens.push(quote! { #new_attrs #new_ident #colon_token #new_bounds });
+
+                if let Some(default_ty) = default {
+                    let new_default = self.callback_in(
+                        context,
+                        CallbackType::Type,
+                        default_ty.to_token_stream(),
+                        "default",
+                    )?;
+                    tokens.push(quote! { #eq_token #new_default });
+                }
+
+                result.extend(tokens);
+                Ok(result)
+            }
+            GenericParam::Lifetime(lifetime_def) => self.lifetime_def(context, &lifetime_def),
+            GenericParam::Const(ConstParam {
+                attrs,
+                const_token,
+                ident,
+                colon_token,
+                ty,
+                eq_token,
+                default,
+            }) => {
+                let new_attrs = self.attributes(context, &attrs)?;
+                let new_ident = self.ident_callback(context, IdentRole::Item, &ident, "ident")?;
+                let new_ty =
+                    self.callback_in(context, CallbackType::Type, ty.to_token_stream(), "ty")?;
+                let new_default = match default {
+                    Some(ref expr) => {
+                        self.const_argument(context, expr, Slot::Field("default", None))?
+                    }
+                    None => TokenStream::new(),
+                };
+
+                Ok(
+                    quote! { #new_attrs #const_token #new_ident #colon_token #new_ty #eq_token #new_default },
+                )
+            }
         }
+    }
+    pub fn where_clause(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let WhereClause {
+            where_token,
+            predicates,
+        } = parse2::<WhereClause>(tokens)?;
 
-        let new_expr = self.callback(context, CallbackType::Expr, expr.to_token_stream())?;
-        tokens.push(new_expr);
+        let mut new_predicates = Vec::<TokenStream>::new();
 
-        result.extend(tokens.into_iter());
-        Ok(result)
-    }
-    pub fn expr_repeat(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprRepeat {
-            attrs,
-            bracket_token: _,
-            expr,
-            semi_token,
-            len,
-        } = parse2::<ExprRepeat>(tokens)?;
+        for (index, predicate) in predicates.iter().enumerate() {
+            let new_predicate = self.callback_at(
+                context,
+                CallbackType::WherePredicate,
+                predicate.to_token_stream(),
+                "predicates",
+                index,
+            )?;
+            new_predicates.push(new_predicate);
+        }
 
-        let new_expr = self.callback(context, CallbackType::Expr, expr.to_token_stream())?;
-        let new_len = self.callback(context, CallbackType::Expr, len.to_token_stream())?;
+        let new_predicates = zip_punctuated(&predicates, new_predicates);
 
-        Ok(quote! { #(#attrs)* [ #new_expr #semi_token #new_len ] })
+        Ok(quote! { #where_token #new_predicates })
     }
-    pub fn expr_return(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprReturn {
-            attrs,
-            return_token,
-            expr,
-        } = parse2::<ExprReturn>(tokens)?;
+    pub fn where_predicate(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let parsed = parse2::<WherePredicate>(tokens)?;
 
-        if let Some(ret_expr) = expr {
-            let new_expr =
-                self.callback(context, CallbackType::Expr, ret_expr.to_token_stream())?;
-
-            Ok(quote! { #(#attrs)* #return_token #new_expr })
-        } else {
-            Ok(quote! { #(#attrs)* #return_token })
+        match parsed {
+            WherePredicate::Type(PredicateType {
+                lifetimes,
+                bounded_ty,
+                colon_token,
+                bounds,
+            }) => {
+                let new_lifetimes = self.bound_lifetimes_opt(context, &lifetimes)?;
+                let new_ty = self.callback_in(
+                    context,
+                    CallbackType::Type,
+                    bounded_ty.to_token_stream(),
+                    "bounded_ty",
+                )?;
+                let new_bounds = self.type_param_bounds(context, &bounds, "bounds")?;
+
+                Ok(quote! { #new_lifetimes #new_ty #colon_token #new_bounds })
+            }
+            WherePredicate::Lifetime(PredicateLifetime {
+                lifetime,
+                colon_token,
+                bounds,
+            }) => {
+                let new_lifetime = self.callback_in(
+                    context,
+                    CallbackType::Lifetime,
+                    lifetime.to_token_stream(),
+                    "lifetime",
+                )?;
+                let new_bounds = self.lifetime_bounds(context, &bounds)?;
+
+                Ok(quote! { #new_lifetime #colon_token #new_bounds })
+            }
+            WherePredicate::Eq(PredicateEq {
+                lhs_ty,
+                eq_token,
+                rhs_ty,
+            }) => {
+                let new_lhs = self.callback_in(
+                    context,
+                    CallbackType::Type,
+                    lhs_ty.to_token_stream(),
+                    "lhs_ty",
+                )?;
+                let new_rhs = self.callback_in(
+                    context,
+                    CallbackType::Type,
+                    rhs_ty.to_token_stream(),
+                    "rhs_ty",
+                )?;
+
+                Ok(quote! { #new_lhs #eq_token #new_rhs })
+            }
         }
     }
-    pub fn expr_struct(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprStruct {
-            attrs,
-            path,
-            brace_token: _,
-            fields,
-            dot2_token,
-            rest,
-        } = parse2::<ExprStruct>(tokens)?;
+    pub fn signature(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let Signature {
+            constness,
+            asyncness,
+            unsafety,
+            abi,
+            fn_token,
+            ident,
+            generics,
+            paren_token: _,
+            inputs,
+            variadic,
+            output,
+        } = parse2::<Signature>(tokens)?;
         let mut result = TokenStream::new();
         let mut tokens = Vec::<TokenStream>::new();
 
-        tokens.push(quote! { #(#attrs)* #path });
-
-        let mut new_fields = Vec::<TokenStream>::new();
+        let new_ident = self.ident_callback(context, IdentRole::Item, &ident, "ident")?;
+        tokens.push(quote! { #constness #asyncness #unsafety #abi #fn_token #new_ident });
 
-        for field in &fields {
-            let new_field =
-                self.callback(context, CallbackType::FieldValue, field.to_token_stream())?;
-            new_fields.push(new_field);
-        }
+        let new_generics = self.callback_in(
+            context,
+            CallbackType::Generics,
+            generics.to_token_stream(),
+            "generics",
+        )?;
+        tokens.push(new_generics);
 
-        let mut end_data = Vec::<TokenStream>::new();
+        let mut new_inputs = Vec::<TokenStream>::new();
 
-        if let Some(dot2) = dot2_token {
-            end_data.push(quote! { , #dot2 });
+        for (index, input) in inputs.iter().enumerate() {
+            let new_input = self.callback_at(
+                context,
+                CallbackType::FnArg,
+                input.to_token_stream(),
+                "inputs",
+                index,
+            )?;
+            new_inputs.push(new_input);
         }
 
-        if let Some(rest_token) = rest {
-            if end_data.len() > 0 {
-                end_data.push(quote! { #rest_token });
-            } else {
-                end_data.push(quote! { , #rest_token });
+        let mut args = zip_punctuated(&inputs, new_inputs);
+
+        if let Some(variadic_token) = variadic {
+            if !inputs.empty_or_trailing() {
+                args.extend(quote! { , });
             }
+
+            args.extend(quote! { #variadic_token });
         }
 
-        tokens.push(quote! { { #(#new_fields),* #(#end_data)* } });
+        tokens.push(quote! { ( #args ) });
+        tokens.push(self.return_type(context, &output)?);
+        tokens.push(self.where_clause_opt(context, &generics.where_clause)?);
 
-        result.extend(tokens.into_iter());
+        result.extend(tokens);
         Ok(result)
     }
-    pub fn expr_try(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprTry {
-            attrs,
-            expr,
-            question_token,
-        } = parse2::<ExprTry>(tokens)?;
-
-        let new_expr = self.callback(context, CallbackType::Expr, expr.to_token_stream())?;
-
-        Ok(quote! { #(#attrs)* #new_expr #question_token })
+    pub fn fn_arg(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let parsed = parse2::<FnArg>(tokens)?;
+
+        match parsed {
+            FnArg::Receiver(Receiver {
+                attrs,
+                reference,
+                mutability,
+                self_token,
+            }) => {
+                let new_attrs = self.attributes(context, &attrs)?;
+                let mut result = TokenStream::new();
+                let mut tokens = Vec::<TokenStream>::new();
+
+                tokens.push(quote! { #new_attrs });
+
+                if let Some((and_token, lifetime)) = reference {
+                    tokens.push(quote! { #and_token });
+                    tokens.push(self.lifetime_opt(context, &lifetime)?);
+                }
+
+                tokens.push(quote! { #mutability #self_token });
+
+                result.extend(tokens);
+                Ok(result)
+            }
+            FnArg::Typed(pat_type) => {
+                self.dispatch(context, CallbackType::PatType, pat_type.to_token_stream())
+            }
+        }
     }
-    pub fn expr_try_block(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprTryBlock {
-            attrs,
-            try_token,
-            block,
-        } = parse2::<ExprTryBlock>(tokens)?;
+    pub fn fields(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        // unit structs and variants have no fields at all
+        if tokens.is_empty() {
+            return Ok(tokens);
+        }
 
-        let new_block = self.callback(context, CallbackType::Block, block.to_token_stream())?;
+        if let Ok(FieldsNamed {
+            brace_token: _,
+            named,
+        }) = parse2::<FieldsNamed>(tokens.clone())
+        {
+            let mut new_fields = Vec::<TokenStream>::new();
+
+            for (index, field) in named.iter().enumerate() {
+                let new_field = self.callback_at(
+                    context,
+                    CallbackType::Field,
+                    field.to_token_stream(),
+                    "named",
+                    index,
+                )?;
+                new_fields.push(new_field);
+            }
 
-        Ok(quote! { #(#attrs)* #try_token #new_block })
-    }
-    pub fn expr_tuple(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprTuple {
-            attrs,
-            paren_token: _,
-            elems,
-        } = parse2::<ExprTuple>(tokens)?;
+            let new_fields = zip_punctuated(&named, new_fields);
 
-        let mut new_elems = Vec::<TokenStream>::new();
+            Ok(quote! { { #new_fields } })
+        } else {
+            let FieldsUnnamed {
+                paren_token: _,
+                unnamed,
+            } = parse2::<FieldsUnnamed>(tokens)?;
+
+            let mut new_fields = Vec::<TokenStream>::new();
+
+            for (index, field) in unnamed.iter().enumerate() {
+                let new_field = self.callback_at(
+                    context,
+                    CallbackType::Field,
+                    field.to_token_stream(),
+                    "unnamed",
+                    index,
+                )?;
+                new_fields.push(new_field);
+            }
 
-        for elem in &elems {
-            let new_elem = self.callback(context, CallbackType::Expr, elem.to_token_stream())?;
-            new_elems.push(new_elem);
-        }
+            let new_fields = zip_punctuated(&unnamed, new_fields);
 
-        Ok(quote! { #(#attrs)* ( #(#new_elems),* ) })
+            Ok(quote! { ( #new_fields ) })
+        }
     }
-    pub fn expr_type(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprType {
+    pub fn field(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let Field {
             attrs,
-            expr,
+            vis,
+            ident,
             colon_token,
             ty,
-        } = parse2::<ExprType>(tokens)?;
-
-        let new_expr = self.callback(context, CallbackType::Expr, expr.to_token_stream())?;
+        } = get_field(tokens)?;
+
+        let new_attrs = self.attributes(context, &attrs)?;
+        let new_vis = self.callback_in(
+            context,
+            CallbackType::Visibility,
+            vis.to_token_stream(),
+            "vis",
+        )?;
+        let new_ident = match ident {
+            Some(ref field_ident) => {
+                self.ident_callback(context, IdentRole::Member, field_ident, "ident")?
+            }
+            None => TokenStream::new(),
+        };
+        let new_ty = self.callback_in(context, CallbackType::Type, ty.to_token_stream(), "ty")?;
 
-        Ok(quote! { #(#attrs)* #new_expr #colon_token #ty })
+        Ok(quote! { #new_attrs #new_vis #new_ident #colon_token #new_ty })
     }
-    pub fn expr_unary(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprUnary { attrs, op, expr } = parse2::<ExprUnary>(tokens)?;
-
-        let new_expr = self.callback(context, CallbackType::Expr, expr.to_token_stream())?;
+    pub fn macro_(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let parsed = parse2::<Macro>(tokens)?;
+
+        let new_path = self.callback_in(
+            context,
+            CallbackType::Path,
+            parsed.path.to_token_stream(),
+            "path",
+        )?;
+        let bang_token = parsed.bang_token;
+        let route = self.macro_routes.get(&macro_path_string(&parsed.path));
+        let body = match route {
+            Some(name) => {
+                let new_tokens = self.callback_in(
+                    context,
+                    CallbackType::Custom(name),
+                    parsed.tokens.clone(),
+                    "tokens",
+                )?;
+                macro_body(&parsed, new_tokens)
+            }
+            None => macro_body(&parsed, parsed.tokens.clone()),
+        };
 
-        Ok(quote! { #(#attrs)* #op #new_expr })
+        Ok(quote! { #new_path #bang_token #body })
     }
-    pub fn expr_unsafe(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprUnsafe {
-            attrs,
-            unsafe_token,
-            block,
-        } = parse2::<ExprUnsafe>(tokens)?;
-
-        let new_block = self.callback(context, CallbackType::Block, block.to_token_stream())?;
+    pub fn path(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let Path {
+            leading_colon,
+            segments,
+        } = get_path(tokens)?;
+
+        // the last segment of an invoked macro's path is the macro's name
+        let is_macro = matches!(
+            context.peek(1),
+            Some((CallbackType::Macro, _)) | Some((CallbackType::ItemMacro, _))
+        );
+        let mut new_segments = Vec::<TokenStream>::new();
 
-        Ok(quote! { #(#attrs)* #unsafe_token #new_block })
-    }
-    pub fn expr_while(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprWhile {
-            attrs,
-            label,
-            while_token,
-            cond,
-            body,
-        } = parse2::<ExprWhile>(tokens)?;
+        for (index, segment) in segments.iter().enumerate() {
+            let role = if is_macro && index + 1 == segments.len() {
+                IdentRole::Macro
+            } else {
+                IdentRole::Path
+            };
+            let new_segment = self.callback_with_role(
+                context,
+                CallbackType::PathSegment,
+                segment.to_token_stream(),
+                Some(role),
+                Slot::Field("segments", Some(index)),
+            )?;
+            new_segments.push(new_segment);
+        }
 
-        let new_cond = self.callback(context, CallbackType::Expr, cond.to_token_stream())?;
-        let new_body = self.callback(context, CallbackType::Block, body.to_token_stream())?;
+        let new_segments = zip_punctuated(&segments, new_segments);
 
-        if let Some(lbl) = label {
-            Ok(quote! { #(#attrs)* #lbl #while_token #new_cond #new_body })
-        } else {
-            Ok(quote! { #(#attrs)* #while_token #new_cond #new_body })
-        }
+        Ok(quote! { #leading_colon #new_segments })
     }
-    pub fn expr_yield(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let ExprYield {
-            attrs,
-            yield_token,
-            expr,
-        } = parse2::<ExprYield>(tokens)?;
-
-        if let Some(yield_expr) = expr {
-            let new_expr =
-                self.callback(context, CallbackType::Expr, yield_expr.to_token_stream())?;
+    pub fn path_segment(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let PathSegment { ident, arguments } = get_path_segment(tokens)?;
+        let mut result = TokenStream::new();
+        let mut tokens = Vec::<TokenStream>::new();
 
-            Ok(quote! { #(#attrs)* #yield_token #new_expr })
-        } else {
-            Ok(quote! { #(#attrs)* #yield_token })
+        let role = context.ident_role().unwrap_or(IdentRole::Path);
+        tokens.push(self.ident_callback(context, role, &ident, "ident")?);
+
+        match arguments {
+            PathArguments::None => (),
+            PathArguments::AngleBracketed(AngleBracketedGenericArguments {
+                colon2_token,
+                lt_token,
+                args,
+                gt_token,
+            }) => {
+                let mut new_args = Vec::<TokenStream>::new();
+
+                for (index, arg) in args.iter().enumerate() {
+                    let new_arg = self.callback_at(
+                        context,
+                        CallbackType::GenericArgument,
+                        arg.to_token_stream(),
+                        "args",
+                        index,
+                    )?;
+                    new_args.push(new_arg);
+                }
+
+                let new_args = zip_punctuated(&args, new_args);
+                tokens.push(quote! { #colon2_token #lt_token #new_args #gt_token });
+            }
+            PathArguments::Parenthesized(ParenthesizedGenericArguments {
+                paren_token: _,
+                inputs,
+                output,
+            }) => {
+                let mut new_inputs = Vec::<TokenStream>::new();
+
+                for (index, input) in inputs.iter().enumerate() {
+                    let new_input = self.callback_at(
+                        context,
+                        CallbackType::Type,
+                        input.to_token_stream(),
+                        "inputs",
+                        index,
+                    )?;
+                    new_inputs.push(new_input);
+                }
+
+                let new_inputs = zip_punctuated(&inputs, new_inputs);
+                tokens.push(quote! { ( #new_inputs ) });
+                tokens.push(self.return_type(context, &output)?);
+            }
         }
+
+        result.extend(tokens);
+        Ok(result)
     }
-    pub fn arm(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let Arm {
-            attrs,
-            pat,
-            guard,
-            fat_arrow_token,
-            body,
-            comma,
-        } = parse2::<Arm>(tokens)?;
+    pub fn qself(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let QualifiedPath {
+            qself:
+                QSelf {
+                    lt_token,
+                    ty,
+                    position,
+                    as_token,
+                    gt_token,
+                },
+            path,
+        } = get_qualified_path(tokens)?;
         let mut result = TokenStream::new();
         let mut tokens = Vec::<TokenStream>::new();
 
-        tokens.push(quote! { #(#attrs)* });
-
-        let new_pat = self.callback(context, CallbackType::Pat, pat.to_token_stream())?;
-        tokens.push(new_pat);
+        let new_ty = self.callback_in(context, CallbackType::Type, ty.to_token_stream(), "ty")?;
+        tokens.push(quote! { #lt_token #new_ty });
+
+        let position = std::cmp::min(position, path.segments.len());
+        let mut pairs = path.segments.pairs();
+
+        if position > 0 {
+            // the segments before the position are the trait the self type is
+            // being cast as, which is a path in its own right
+            let mut trait_path = Path {
+                leading_colon: path.leading_colon,
+                segments: punctuated::Punctuated::new(),
+            };
+            let mut trait_colon = None;
+
+            for (index, pair) in pairs.by_ref().take(position).enumerate() {
+                let (segment, punct) = pair.into_tuple();
+                trait_path.segments.push_value(segment.clone());
+
+                if index + 1 == position {
+                    trait_colon = punct.cloned();
+                } else if let Some(colon) = punct {
+                    trait_path.segments.push_punct(*colon);
+                }
+            }
 
-        if let Some((if_token, if_expr)) = guard {
-            let new_expr = self.callback(context, CallbackType::Expr, if_expr.to_token_stream())?;
-            tokens.push(quote! { #if_token #new_expr });
+            let as_token = as_token.unwrap_or_default();
+            let new_trait = self.callback_in(
+                context,
+                CallbackType::Path,
+                trait_path.to_token_stream(),
+                "path",
+            )?;
+            tokens.push(quote! { #as_token #new_trait #gt_token #trait_colon });
+        } else {
+            let leading_colon = path.leading_colon;
+            tokens.push(quote! { #gt_token #leading_colon });
         }
 
-        tokens.push(quote! { #fat_arrow_token });
-
-        let new_expr = self.callback(context, CallbackType::Expr, body.to_token_stream())?;
-        tokens.push(new_expr);
-
-        if let Some(comma_token) = comma {
-            tokens.push(quote! { #comma_token });
+        for (index, pair) in pairs.enumerate() {
+            let (segment, punct) = pair.into_tuple();
+            let new_segment = self.callback_with_role(
+                context,
+                CallbackType::PathSegment,
+                segment.to_token_stream(),
+                Some(IdentRole::Path),
+                Slot::Field("segments", Some(position + index)),
+            )?;
+            tokens.push(quote! { #new_segment #punct });
         }
 
-        result.extend(tokens.into_iter());
+        result.extend(tokens);
         Ok(result)
     }
-    pub fn lit(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<Lit>(tokens)?;
+    pub fn generic_argument(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let parsed = parse2::<GenericArgument>(tokens)?;
 
-        let result = match parsed {
-            Lit::Str(ref str_) => {
-                self.callback(context, CallbackType::LitStr, str_.to_token_stream())
-            }
-            Lit::ByteStr(ref str_) => {
-                self.callback(context, CallbackType::LitByteStr, str_.to_token_stream())
-            }
-            Lit::Byte(ref byte) => {
-                self.callback(context, CallbackType::LitByte, byte.to_token_stream())
-            }
-            Lit::Char(ref char_) => {
-                self.callback(context, CallbackType::LitChar, char_.to_token_stream())
+        match parsed {
+            GenericArgument::Lifetime(lifetime) => self.callback_in(
+                context,
+                CallbackType::Lifetime,
+                lifetime.to_token_stream(),
+                "lifetime",
+            ),
+            GenericArgument::Type(ty) => {
+                self.callback_in(context, CallbackType::Type, ty.to_token_stream(), "ty")
             }
-            Lit::Int(ref int) => {
-                self.callback(context, CallbackType::LitInt, int.to_token_stream())
+            GenericArgument::Binding(syn::Binding {
+                ident,
+                eq_token,
+                ty,
+            }) => {
+                let new_ident = self.ident_callback(context, IdentRole::Path, &ident, "ident")?;
+                let new_ty =
+                    self.callback_in(context, CallbackType::Type, ty.to_token_stream(), "ty")?;
+
+                Ok(quote! { #new_ident #eq_token #new_ty })
             }
-            Lit::Float(ref float) => {
-                self.callback(context, CallbackType::LitFloat, float.to_token_stream())
+            GenericArgument::Constraint(Constraint {
+                ident,
+                colon_token,
+                bounds,
+            }) => {
+                let new_ident = self.ident_callback(context, IdentRole::Path, &ident, "ident")?;
+                let new_bounds = self.type_param_bounds(context, &bounds, "bounds")?;
+
+                Ok(quote! { #new_ident #colon_token #new_bounds })
             }
-            Lit::Bool(ref bool_) => {
-                self.callback(context, CallbackType::LitBool, bool_.to_token_stream())
+            GenericArgument::Const(expr) => {
+                self.const_argument(context, &expr, Slot::Field("expr", None))
             }
-            Lit::Verbatim(_) => Ok(parsed.to_token_stream()),
-        }?;
-        Ok(result)
-    }
-    pub fn lit_str(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<LitStr>(tokens)?;
-        Ok(parsed.to_token_stream())
-    }
-    pub fn lit_byte_str(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<LitByteStr>(tokens)?;
-        Ok(parsed.to_token_stream())
+        }
     }
-    pub fn lit_byte(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<LitByte>(tokens)?;
-        Ok(parsed.to_token_stream())
+    pub fn method_turbofish(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let MethodTurbofish {
+            colon2_token,
+            lt_token,
+            args,
+            gt_token,
+        } = parse2::<MethodTurbofish>(tokens)?;
+
+        let mut new_args = Vec::<TokenStream>::new();
+
+        for (index, arg) in args.iter().enumerate() {
+            // method arguments are written the same way as the arguments of a path
+            let new_arg = self.callback_at(
+                context,
+                CallbackType::GenericArgument,
+                arg.to_token_stream(),
+                "args",
+                index,
+            )?;
+            new_args.push(new_arg);
+        }
+
+        let new_args = zip_punctuated(&args, new_args);
+
+        Ok(quote! { #colon2_token #lt_token #new_args #gt_token })
     }
-    pub fn lit_char(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<LitChar>(tokens)?;
+    pub fn ident(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        // keywords such as `self` and `crate` are identifiers in paths
+        let parsed = Ident::parse_any.parse2(tokens)?;
         Ok(parsed.to_token_stream())
     }
-    pub fn lit_int(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<LitInt>(tokens)?;
-        Ok(parsed.to_token_stream())
+    pub fn visibility(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let parsed = parse2::<Visibility>(tokens)?;
+
+        match parsed {
+            Visibility::Restricted(VisRestricted {
+                pub_token,
+                paren_token: _,
+                in_token: Some(in_token),
+                path,
+            }) => {
+                let new_path =
+                    self.callback_in(context, CallbackType::Path, path.to_token_stream(), "path")?;
+
+                Ok(quote! { #pub_token ( #in_token #new_path ) })
+            }
+            _ => Ok(parsed.to_token_stream()),
+        }
     }
-    pub fn lit_float(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<LitFloat>(tokens)?;
+    pub fn lifetime(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let parsed = parse2::<Lifetime>(tokens)?;
         Ok(parsed.to_token_stream())
     }
-    pub fn lit_bool(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let parsed = parse2::<LitBool>(tokens)?;
-        Ok(parsed.to_token_stream())
+    pub fn label(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let (Lifetime { apostrophe, ident }, colon_token) = label_name.parse2(tokens)?;
+
+        let new_ident = self.ident_callback(context, IdentRole::Label, &ident, "ident")?;
+        let mut new_apostrophe = Punct::new('\'', Spacing::Joint);
+        new_apostrophe.set_span(apostrophe);
+
+        Ok(quote! { #new_apostrophe #new_ident #colon_token })
     }
-    pub fn field_value(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
-        let FieldValue {
-            attrs,
-            member,
-            colon_token,
-            expr,
-        } = parse2::<FieldValue>(tokens)?;
+    pub fn bound_lifetimes(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let BoundLifetimes {
+            for_token,
+            lt_token,
+            lifetimes,
+            gt_token,
+        } = parse2::<BoundLifetimes>(tokens)?;
 
-        let new_expr = self.callback(context, CallbackType::Expr, expr.to_token_stream())?;
+        let mut new_defs = Vec::<TokenStream>::new();
 
-        if let Some(colon) = colon_token {
-            Ok(quote! { #(#attrs)* #member #colon #new_expr })
-        } else {
-            Ok(quote! { #(#attrs)* #member #new_expr })
+        for lifetime_def in &lifetimes {
+            new_defs.push(self.lifetime_def(context, lifetime_def)?);
         }
+
+        let new_defs = zip_punctuated(&lifetimes, new_defs);
+
+        Ok(quote! { #for_token #lt_token #new_defs #gt_token })
     }
     pub fn verbatim(&self, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
         Ok(tokens)
     }
+    pub fn custom(&self, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+        let name = match context.peek(0) {
+            Some((CallbackType::Custom(name), _)) => name,
+            _ => {
+                return Err(Error::new(
+                    tokens.span(),
+                    "expected a custom node on the top of the context stack",
+                ))
+            }
+        };
+
+        match self.custom_defaults.get(name) {
+            Some(default) => default(self, context, tokens),
+            None => {
+                let message = format!("couldn't find default callback for custom node {:?}", name);
+                let error = Error::new_spanned(tokens, message);
+
+                Err(context.raise(ErrorKind::MissingCallback, error))
+            }
+        }
+    }
+}
+impl Default for Moisture {
+    fn default() -> Self {
+        Self::new()
+    }
 }
@@ -0,0 +1,136 @@
+//! Checks of the ancestor queries of [`Context`].
+
+use std::cell::RefCell;
+
+use moisture::*;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{ExprCall, ItemFn, Result};
+
+type Queries = (
+    usize,
+    Option<(usize, String)>,
+    Option<usize>,
+    Option<usize>,
+    Option<usize>,
+);
+
+thread_local! {
+    static QUERIES: RefCell<Vec<Queries>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Record the depth of every integer literal, with the distances of its
+/// closest call, function, statement and integer literal ancestors.
+fn lit_int(_: &Moisture, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    let distance = |ty| context.nearest(ty).map(|(distance, _)| distance);
+    let call = context
+        .nearest(CallbackType::ExprCall)
+        .map(|(distance, tokens)| (distance, tokens.to_string()));
+
+    QUERIES.with(|queries| {
+        queries.borrow_mut().push((
+            context.depth(),
+            call,
+            distance(CallbackType::ItemFn),
+            distance(CallbackType::Stmt),
+            distance(CallbackType::LitInt),
+        ))
+    });
+
+    Ok(tokens)
+}
+
+#[test]
+fn nearest_reports_the_distance_of_the_closest_ancestor() {
+    let mut moisture = Moisture::new();
+    moisture.register_callback(CallbackType::LitInt, lit_int);
+    QUERIES.with(|queries| queries.borrow_mut().clear());
+
+    moisture
+        .callback(
+            &Context::new(),
+            CallbackType::Item,
+            quote! { fn f() { g(1, h(2)) } },
+        )
+        .unwrap();
+
+    // Item, ItemFn, Block, Stmts, Stmt, Expr, ExprCall, Expr, ExprLit, Lit, LitInt
+    let outer = (
+        11,
+        Some((4, quote! { g(1, h(2)) }.to_string())),
+        Some(9),
+        Some(6),
+        None,
+    );
+    // the inner call adds ExprCall and Expr between the outer call and `2`
+    let inner = (
+        13,
+        Some((4, quote! { h(2) }.to_string())),
+        Some(11),
+        Some(8),
+        None,
+    );
+
+    QUERIES.with(|queries| assert_eq!(*queries.borrow(), [outer, inner]));
+}
+
+#[test]
+fn nearest_matches_peek() {
+    let mut moisture = Moisture::new();
+    moisture.register_callback(CallbackType::LitInt, |_, context, _| {
+        for (ty, _) in context.ancestors() {
+            let (distance, nearest) = context.nearest(ty).unwrap();
+            let (peeked, peeked_tokens) = context.peek(distance).unwrap();
+
+            assert_eq!(peeked, ty);
+            assert_eq!(peeked_tokens.to_string(), nearest.to_string());
+            assert!(context.contains(ty));
+        }
+
+        assert_eq!(context.ancestors().count(), context.depth() - 1);
+
+        Ok(quote! { 0 })
+    });
+
+    let output = moisture
+        .callback(
+            &Context::new(),
+            CallbackType::Item,
+            quote! { fn f() { let x = [1, 2]; } },
+        )
+        .unwrap();
+    assert_eq!(
+        output.to_string(),
+        quote! { fn f() { let x = [0, 0]; } }.to_string()
+    );
+}
+
+#[test]
+fn missing_ancestors_are_none() {
+    let mut moisture = Moisture::new();
+    moisture.register_callback(CallbackType::LitInt, |_, context, _| {
+        assert!(context.nearest(CallbackType::ItemImpl).is_none());
+        assert!(!context.contains(CallbackType::ItemImpl));
+        assert!(context.nearest_parsed::<ExprCall>()?.is_none());
+
+        // the current callback isn't its own ancestor
+        assert!(context.nearest(CallbackType::LitInt).is_none());
+        assert!(context.nearest_parsed::<ItemFn>()?.is_some());
+
+        Ok(quote! { 2 })
+    });
+
+    let output = moisture
+        .callback(
+            &Context::new(),
+            CallbackType::Item,
+            quote! { fn f() { 1; } },
+        )
+        .unwrap();
+    assert_eq!(output.to_string(), quote! { fn f() { 2; } }.to_string());
+
+    let context = Context::new();
+    assert_eq!(context.depth(), 0);
+    assert_eq!(context.ancestors().count(), 0);
+    assert!(context.nearest(CallbackType::Item).is_none());
+}
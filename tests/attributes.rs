@@ -0,0 +1,191 @@
+//! Checks of the [`CallbackType::Attribute`] callback, and of the items
+//! generated with [`Context::emit`].
+
+use std::cell::RefCell;
+
+use moisture::*;
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{parse2, DeriveInput, Ident, Result};
+
+thread_local! {
+    static ISSUED: RefCell<Vec<(CallbackType, String)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Record every attribute with the node it belongs to.
+fn record(_: &Moisture, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    let (parent, _) = context.peek(1).unwrap();
+    ISSUED.with(|issued| issued.borrow_mut().push((parent, tokens.to_string())));
+
+    Ok(tokens)
+}
+
+/// Remove every `#[remove]` attribute.
+fn remove(_: &Moisture, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    let attr = get_attribute(tokens.clone())?;
+
+    if attr.path.is_ident("remove") {
+        Ok(TokenStream::new())
+    } else {
+        Ok(tokens)
+    }
+}
+
+/// Emit a function for every `#[getter(name)]` attribute.
+fn getter(_: &Moisture, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    let attr = get_attribute(tokens.clone())?;
+
+    if attr.path.is_ident("getter") {
+        let name = attr.parse_args::<Ident>()?;
+        context.emit(quote! { fn #name() {} });
+    }
+
+    Ok(tokens)
+}
+
+#[test]
+fn inner_and_outer_attributes_are_issued() {
+    let mut moisture = Moisture::new();
+    moisture.register_callback(CallbackType::Attribute, record);
+    ISSUED.with(|issued| issued.borrow_mut().clear());
+
+    let tokens = quote! {
+        #![allow(unused)]
+
+        #[inline]
+        fn f() {
+            #![allow(dead_code)]
+
+            #[allow(unused)]
+            let x = 1;
+            #[allow(unused)]
+            x + 1;
+            let closure = |#[allow(unused)] y| y;
+        }
+    };
+    let output = moisture
+        .callback(&Context::new(), CallbackType::File, tokens.clone())
+        .unwrap();
+    assert_eq!(output.to_string(), tokens.to_string());
+
+    ISSUED.with(|issued| {
+        assert_eq!(
+            *issued.borrow(),
+            [
+                (CallbackType::File, quote! { #![allow(unused)] }.to_string()),
+                (CallbackType::ItemFn, quote! { #[inline] }.to_string()),
+                (
+                    CallbackType::ItemFn,
+                    quote! { #![allow(dead_code)] }.to_string()
+                ),
+                (CallbackType::Local, quote! { #[allow(unused)] }.to_string()),
+                (
+                    CallbackType::ExprPath,
+                    quote! { #[allow(unused)] }.to_string()
+                ),
+                (
+                    CallbackType::PatIdent,
+                    quote! { #[allow(unused)] }.to_string()
+                ),
+            ]
+        );
+    });
+}
+
+#[test]
+fn inner_attributes_are_nodes() {
+    let mut moisture = Moisture::new();
+    moisture.register_callback(CallbackType::Path, |_, _, _| Ok(quote! { deny }));
+
+    let attr = get_attribute(quote! { #![allow(unused)] }).unwrap();
+    let new_attr = moisture.transform(&Context::new(), &attr).unwrap();
+
+    assert_eq!(
+        new_attr.to_token_stream().to_string(),
+        quote! { #![deny(unused)] }.to_string()
+    );
+}
+
+#[test]
+fn attributes_are_removed() {
+    let mut moisture = Moisture::new();
+    moisture.register_callback(CallbackType::Attribute, remove);
+
+    let output = moisture
+        .callback(
+            &Context::new(),
+            CallbackType::Item,
+            quote! {
+                #[remove]
+                #[inline]
+                fn f<#[remove] T>(#[remove] x: T) {
+                    #![remove]
+                    #[remove]
+                    let y = x;
+                    #[remove]
+                    y;
+                }
+            },
+        )
+        .unwrap();
+
+    assert_eq!(
+        output.to_string(),
+        quote! {
+            #[inline]
+            fn f<T>(x: T) {
+                let y = x;
+                y;
+            }
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn derive_returns_the_emitted_items() {
+    let mut moisture = Moisture::new();
+    moisture.register_callback(CallbackType::Attribute, getter);
+
+    let input = parse2::<DeriveInput>(quote! {
+        #[getter(s)]
+        enum E {
+            #[getter(a)]
+            A {
+                #[getter(b)]
+                b: u8,
+            },
+            C(#[getter(d)] u8),
+        }
+    })
+    .unwrap();
+    let output = moisture.derive(&Context::new(), &input).unwrap();
+
+    assert_eq!(
+        output.to_string(),
+        quote! { fn s() {} fn a() {} fn b() {} fn d() {} }.to_string()
+    );
+}
+
+#[test]
+fn emitted_items_are_shared_and_taken_once() {
+    let mut moisture = Moisture::new();
+    moisture.register_callback(CallbackType::ItemFn, |moisture, context, tokens| {
+        context.emit(quote! { const EMITTED: () = (); });
+
+        moisture.item_fn(context, tokens)
+    });
+
+    let context = Context::new();
+    let tokens = quote! { fn f() { fn g() {} } };
+    let output = moisture
+        .callback(&context, CallbackType::Item, tokens.clone())
+        .unwrap();
+    assert_eq!(output.to_string(), tokens.to_string());
+
+    assert_eq!(
+        context.take_emitted().to_string(),
+        quote! { const EMITTED: () = (); const EMITTED: () = (); }.to_string()
+    );
+    assert!(context.take_emitted().is_empty());
+}
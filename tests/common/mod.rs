@@ -0,0 +1,38 @@
+//! Helpers shared by the integration tests.
+
+// every test crate uses only some of the helpers
+#![allow(dead_code)]
+
+use moisture::*;
+use proc_macro2::TokenStream;
+
+/// Traverse the tokens as the given node kind from the given context,
+/// returning the output as a string.
+pub fn traverse_in(
+    moisture: &Moisture,
+    context: &Context,
+    ty: CallbackType,
+    tokens: TokenStream,
+) -> String {
+    moisture.callback(context, ty, tokens).unwrap().to_string()
+}
+
+/// Traverse the tokens as the given node kind from a new context, returning
+/// the output as a string.
+pub fn traverse(moisture: &Moisture, ty: CallbackType, tokens: TokenStream) -> String {
+    traverse_in(moisture, &Context::new(), ty, tokens)
+}
+
+/// Traverse the tokens as the given node kind with a single callback
+/// registered, returning the output as a string.
+pub fn run(
+    ty: CallbackType,
+    callback_ty: CallbackType,
+    callback: Callback,
+    tokens: TokenStream,
+) -> String {
+    let mut moisture = Moisture::new();
+    moisture.register_callback(callback_ty, callback);
+
+    traverse(&moisture, ty, tokens)
+}
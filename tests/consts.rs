@@ -0,0 +1,54 @@
+//! Checks of the expressions within types and generic arguments.
+
+mod common;
+
+use moisture::*;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse2, Expr, Lit, Result};
+
+fn replace(_: &Moisture, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    match parse2::<Expr>(tokens.clone())? {
+        Expr::Lit(lit) => match lit.lit {
+            Lit::Int(int) if int.base10_digits() == "1" => Ok(quote! { N }),
+            Lit::Int(int) if int.base10_digits() == "2" => Ok(quote! { N + 1 }),
+            Lit::Int(int) if int.base10_digits() == "3" => Ok(quote! { m::N }),
+            _ => Ok(tokens),
+        },
+        _ => Ok(tokens),
+    }
+}
+
+fn run(ty: CallbackType, tokens: TokenStream) -> String {
+    common::run(ty, CallbackType::ExprLit, replace, tokens)
+}
+
+#[test]
+fn const_arguments_are_braced_when_needed() {
+    let cases = [
+        (quote! { f::<1>() }, quote! { f::<N>() }),
+        (quote! { f::<2>() }, quote! { f::<{ N + 1 }>() }),
+        (quote! { f::<3>() }, quote! { f::<{ m::N }>() }),
+        (quote! { x.f::<1, 2>() }, quote! { x.f::<N, { N + 1 }>() }),
+        (quote! { f::<{ 2 }>() }, quote! { f::<{ N + 1 }>() }),
+    ];
+
+    for (input, expected) in cases {
+        assert_eq!(run(CallbackType::Expr, input), expected.to_string());
+    }
+}
+
+#[test]
+fn array_lengths_and_defaults_are_expressions() {
+    assert_eq!(
+        run(CallbackType::Type, quote! { [u8; 2] }),
+        quote! { [u8; N + 1] }.to_string()
+    );
+    assert_eq!(
+        run(
+            CallbackType::Item,
+            quote! { struct S<const A: usize = 1, const B: usize = 2>([u8; A]); }
+        ),
+        quote! { struct S<const A: usize = N, const B: usize = { N + 1 }>([u8; A]); }.to_string()
+    );
+}
@@ -0,0 +1,205 @@
+//! Outer and inner attributes in every position that allows them.
+#![allow(unused)]
+#![cfg_attr(feature = "nightly", feature(stmt_expr_attributes))]
+
+#[inline]
+fn function() {
+    #![allow(unused_variables)]
+    let x = 1;
+}
+
+#[derive(Default)]
+struct Attributed {
+    #[cfg(test)]
+    field: u8,
+}
+
+impl Attributed {
+    #![allow(clippy::new_without_default)]
+
+    #[must_use]
+    fn method(&self) -> u8 {
+        #![allow(unused)]
+        0
+    }
+}
+
+trait WithInner {
+    #![allow(unused)]
+
+    fn provided(&self) {
+        #![allow(unused)]
+    }
+}
+
+#[link(name = "c")]
+extern "C" {
+    #![allow(improper_ctypes)]
+
+    fn abs(x: i32) -> i32;
+}
+
+fn expressions(x: Option<u8>) {
+    #[allow(unused)]
+    let value = #[allow(unused)] {
+        #![allow(unused)]
+        1
+    };
+
+    let matched = match x {
+        #![allow(unused)]
+        #[allow(unused)]
+        Some(_) => 1,
+        None => 0,
+    };
+
+    loop {
+        #![allow(unused)]
+        break;
+    }
+
+    while false {
+        #![allow(unused)]
+    }
+
+    for _ in 0..1 {
+        #![allow(unused)]
+    }
+
+    unsafe {
+        #![allow(unused)]
+    }
+
+    let structure = Attributed {
+        #[cfg(test)]
+        field: 1,
+    };
+    let array = [#[allow(unused)] 1, 2];
+    let call = function(#[allow(unused)] ());
+}
+
+#[allow(unused)]
+const CONSTANT: u8 = 1;
+
+#[allow(unused)]
+static STATIC: u8 = 1;
+
+#[allow(unused_extern_crates)]
+extern crate core;
+
+#[allow(unused)]
+type BareFn = fn(#[allow(unused)] u8) -> u8;
+
+#[link(name = "c")]
+extern "C" {
+    #[link_name = "labs"]
+    fn long_abs(x: i64) -> i64;
+    #[link_name = "errno"]
+    static ERRNO: i32;
+    #[allow(unused)]
+    type Opaque;
+    #[allow(unused)]
+    foreign_macro!();
+}
+
+trait Items {
+    #[allow(unused)]
+    const CONSTANT: u8;
+    #[allow(unused)]
+    type Type;
+    #[allow(unused)]
+    trait_macro!();
+}
+
+impl Items for Attributed {
+    #[allow(unused)]
+    const CONSTANT: u8 = 1;
+    #[allow(unused)]
+    type Type = u8;
+    #[allow(unused)]
+    impl_macro!();
+}
+
+impl Attributed {
+    fn receiver(#[allow(unused)] self) {}
+}
+
+fn generics<#[allow(unused)] 'a, #[allow(unused)] T, #[allow(unused)] const N: usize>(
+    #[allow(unused)] x: &'a T,
+) where
+    for<#[allow(unused)] 'b> T: Fn(&'b u8),
+{
+}
+
+async fn statements(mut x: u8, s: Attributed, v: Vec<u8>, o: Option<u8>) -> Option<u8> {
+    #[allow(unused)]
+    x = 1;
+    #[allow(unused)]
+    x += 1;
+    #[allow(unused)]
+    async {};
+    #[allow(unused)]
+    async {}.await;
+    #[allow(unused)]
+    x + 1;
+    #[allow(unused)]
+    x as u16;
+    #[allow(unused)]
+    || 1;
+    #[allow(unused)]
+    s.field;
+    #[allow(unused)]
+    if true {}
+    #[allow(unused)]
+    v[0];
+    #[allow(unused)]
+    (x);
+    #[allow(unused)]
+    x;
+    #[allow(unused)]
+    0..1;
+    #[allow(unused)]
+    ..1;
+    #[allow(unused)]
+    [1, 2];
+    #[allow(unused)]
+    &x;
+    #[allow(unused)]
+    [0; 2];
+    #[allow(unused)]
+    Attributed { field: 1 };
+    #[allow(unused)]
+    o?;
+    #[allow(unused)]
+    !x;
+    #[allow(unused)]
+    loop {
+        #[allow(unused)]
+        break;
+    }
+    #[allow(unused)]
+    loop {
+        #[allow(unused)]
+        continue;
+    }
+    #[allow(unused)]
+    return None;
+}
+
+fn closure_patterns() {
+    let patterns = |#[allow(unused)] x: u8,
+                    #[allow(unused)] (a, b): (u8, u8),
+                    #[allow(unused)] [c, ..]: [u8; 2],
+                    #[allow(unused)] Attributed { field }: Attributed,
+                    #[allow(unused)] Some(d): Option<u8>,
+                    #[allow(unused)] &e: &u8,
+                    #[allow(unused)] _: u8| ();
+    let untyped = |#[allow(unused)] x,
+                   #[allow(unused)] (a, b),
+                   #[allow(unused)] [c, ..],
+                   #[allow(unused)] Attributed { field },
+                   #[allow(unused)] Some(d),
+                   #[allow(unused)] &e,
+                   #[allow(unused)] box f| ();
+    let literals = |#[allow(unused)] 1, #[allow(unused)] 1..=2, #[allow(unused)] m!(), #[allow(unused)] Path::X| ();
+}
@@ -0,0 +1,113 @@
+//! Expressions of every kind.
+
+fn expressions(mut x: i32, v: Vec<i32>, o: Option<i32>) -> i32 {
+    let array = [1, 2, 3,];
+    let repeat = [0u8; 16];
+    let tuple = (1, "two", 3.0);
+    let single = (1,);
+    let unit = ();
+    let parenthesized = (x + 1) * 2;
+    let reference = &mut x;
+    let raw = std::ptr::addr_of!(x);
+    let deref = *reference;
+    let negated = -x;
+    let not = !true;
+    let cast = x as u64 as f64;
+    let typed = 5u8;
+    let binary = x + 1 - 2 * 3 / 4 % 5 & 6 | 7 ^ 8 << 1 >> 2;
+    let logic = x == 1 && x != 2 || x < 3 && x > 4 || x <= 5 && x >= 6;
+    x += 1;
+    x -= 1;
+    x *= 2;
+    x /= 2;
+    x %= 3;
+    x <<= 1;
+    x >>= 1;
+    x &= 1;
+    x |= 1;
+    x ^= 1;
+    x = 4;
+    let call = std::cmp::max(x, 3);
+    let method = v.iter().map(|value| value * 2).collect::<Vec<_>>();
+    let turbofish = "3".parse::<i32>().unwrap();
+    let field = tuple.0;
+    let nested = ((1, 2), 3).0 .1;
+    let index = v[0] + array[1..][0];
+    let ranges = (.., 1.., ..2, 1..2, ..=3, 1..=3);
+    let closure = |a: i32, b| -> i32 { a + b };
+    let moved = move || x;
+    let asynchronous = async move { x };
+    let unit_closure = || ();
+    let structure = Point { x: 1, y: 2 };
+    let shorthand = Point { x, y: 3 };
+    let update = Point { x: 3, ..structure };
+    let tuple_struct = Tuple { 0: 1, 1: 2 };
+    let block = {
+        let inner = 1;
+        inner + 1
+    };
+    let unsafe_block = unsafe { *raw };
+    let conditional = if x > 0 {
+        1
+    } else if x < 0 {
+        -1
+    } else {
+        0
+    };
+    let matched = match o {
+        Some(value) if value > 0 => value,
+        Some(0) | None => 0,
+        Some(_) => {
+            -1
+        }
+    };
+    let labeled = 'outer: loop {
+        'inner: while x > 0 {
+            x -= 1;
+
+            if x == 5 {
+                continue 'outer;
+            }
+
+            break 'inner;
+        }
+
+        break 'outer x;
+    };
+
+    for (index, value) in v.iter().enumerate() {
+        continue;
+    }
+
+    while let Some(value) = o {
+        break;
+    }
+
+    let question = (|| -> Option<i32> { Some(o? + 1) })();
+    let boxed = Box::new(1);
+    let awaited = async { asynchronous.await };
+    let macro_call = vec![1, 2, 3];
+    let formatted = format!("{} {}", x, "y");
+    let literals = ('c', b'b', "str", b"bytes", r"raw", r#"raw "hash""#, 1_000, 0x10, 1e10, true);
+    let path = <Vec<i32> as IntoIterator>::into_iter(v.clone());
+    let assoc = <Vec<i32>>::new();
+    let generic_path = Vec::<i32>::with_capacity(3);
+    let qualified = <i32 as Default>::default;
+
+    #[allow(unused)]
+    let attributed = 3;
+
+    return x;
+}
+
+fn divergent() -> ! {
+    loop {}
+}
+
+fn tail() -> i32 {
+    if true { 1 } else { 2 }
+}
+
+fn empty_return() {
+    return;
+}
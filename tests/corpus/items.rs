@@ -0,0 +1,161 @@
+//! Items of every kind.
+#![allow(dead_code)]
+
+extern crate alloc;
+extern crate std as standard;
+
+use std::collections::{self, HashMap as Map};
+use std::fmt::{Debug, Display};
+use std::io::*;
+pub(crate) use self::inner::Inner;
+
+pub const ANSWER: u32 = 42;
+static mut COUNTER: usize = 0;
+pub(in crate::inner) static NAME: &str = "moisture";
+
+mod outer;
+
+pub mod inner {
+    //! An inline module with inner attributes.
+    #![allow(unused)]
+
+    pub struct Inner;
+
+    mod nested {
+        fn hidden() {}
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Unit;
+
+pub struct Tuple(pub u8, pub(crate) String, Vec<u8>,);
+
+pub struct Named<'a, T: Clone + 'a, const N: usize = 3>
+where
+    T: Debug,
+{
+    pub reference: &'a T,
+    array: [T; N],
+    #[doc = "attributed"]
+    pub(super) boxed: Box<dyn Fn(&T) -> bool + Send + 'a>,
+}
+
+pub struct WhereTuple<T>(T)
+where
+    T: Copy;
+
+pub union Bits {
+    pub int: u32,
+    pub float: f32,
+}
+
+#[repr(u8)]
+pub enum Enum<T>
+where
+    T: Default,
+{
+    Unit,
+    Tuple(T, u8),
+    Struct { field: T, other: Option<Box<Enum<T>>> },
+    Discriminant = 4,
+}
+
+pub enum Empty {}
+
+pub trait Trait<T>: Debug + Sized
+where
+    T: Clone,
+{
+    const CONSTANT: usize;
+    const DEFAULT: usize = 1;
+    type Assoc: Iterator<Item = T>;
+    type Defaulted: Clone = u8;
+
+    fn required(&self, value: T) -> Self::Assoc;
+
+    fn provided(&mut self) -> usize {
+        Self::CONSTANT + Self::DEFAULT
+    }
+
+    fn generic<U>(self: Box<Self>, u: U) -> U
+    where
+        U: Display,
+    {
+        u
+    }
+}
+
+pub unsafe trait UnsafeTrait {}
+
+pub auto trait AutoTrait {}
+
+pub trait Alias = Debug + Clone;
+
+impl<T: Clone + Debug> Trait<T> for Vec<T> {
+    const CONSTANT: usize = 2;
+    type Assoc = std::vec::IntoIter<T>;
+
+    fn required(&self, _value: T) -> Self::Assoc {
+        self.clone().into_iter()
+    }
+}
+
+unsafe impl UnsafeTrait for Unit {}
+
+impl !Send for Bits {}
+
+impl<'a, T> Named<'a, T, 3>
+where
+    T: Clone + Debug,
+{
+    pub fn new(reference: &'a T) -> Self {
+        unimplemented!()
+    }
+
+    pub(crate) const fn constant() -> usize {
+        3
+    }
+
+    async fn asynchronous(&self) {}
+
+    unsafe extern "C" fn foreign(&mut self, _: u8) {}
+
+    default fn specialized(&self) {}
+}
+
+pub type Result<T, E = std::io::Error> = std::result::Result<T, E>;
+
+extern "C" {
+    pub fn external(x: *const u8, ...) -> i32;
+    static EXTERNAL: u32;
+    pub type Opaque;
+    ffi_macro!();
+}
+
+extern "C" {}
+
+extern "system" fn callback(argument: usize) -> usize {
+    argument
+}
+
+pub fn generic<'a, 'b: 'a, T, const N: usize>(x: &'a [T; N], y: &'b mut T) -> impl Iterator<Item = &'a T> + 'a
+where
+    T: 'b + ?Sized + for<'c> Fn(&'c u8),
+    'b: 'a,
+{
+    x.iter()
+}
+
+fn patterns_in_arguments((a, b): (u8, u8), Tuple(c, ..): Tuple, mut d: u8, _: u8) {}
+
+macro_rules! square {
+    ($x:expr) => {
+        $x * $x
+    };
+}
+
+square!(2);
+lazy_static::lazy_static! {
+    static ref CACHE: u8 = 0;
+}
@@ -0,0 +1,36 @@
+//! Syntax syn only knows as verbatim tokens.
+
+fn let_else(option: Option<u8>) -> u8 {
+    let Some(value) = option else { return 0 };
+    let Ok(parsed): Result<u8, ()> = Ok(value) else {
+        panic!("unreachable");
+    };
+    parsed
+}
+
+fn inline_const() -> [u8; 4] {
+    let value = const { 1 + 2 };
+    [const { 0 }; 4]
+}
+
+fn infer() {
+    let mut tuple = (1, 2);
+    _ = tuple;
+    (_, tuple.1) = (3, 4);
+}
+
+fn closure_binder() {
+    let closure = for<'a> |x: &'a u8| -> &'a u8 { x };
+    let moved = for<'a> move |x: &'a u8| -> u8 { *x };
+}
+
+fn let_chains(a: Option<u8>, b: bool) {
+    if let Some(x) = a && b && let Some(y) = Some(x) {}
+
+    while let Some(x) = a && x > 0 {}
+}
+
+fn raw_references(mut x: u8) {
+    let pointer = &raw const x;
+    let mutable = &raw mut x;
+}
@@ -0,0 +1,4 @@
+//! A file referenced by `mod outer;`, which is also a corpus file of its own.
+#![cfg(any())]
+
+pub fn outer() {}
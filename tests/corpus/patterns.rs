@@ -0,0 +1,52 @@
+//! Patterns of every kind.
+
+fn patterns(value: Value, slice: &[u8], pair: (u8, u8)) {
+    let x = 1;
+    let mut y = 2;
+    let ref z = 3;
+    let ref mut w = 4;
+    let (a, b) = pair;
+    let (first, ..) = pair;
+    let (.., last) = pair;
+    let Point { x: px, y: ref py } = point();
+    let Point { x, .. } = point();
+    let Point { ref mut x, y } = point();
+    let Tuple(c, d) = tuple();
+    let Tuple(..) = tuple();
+    let &e = &5;
+    let &mut f = &mut 6;
+    let g: u8 = 7;
+    let [h, i, rest @ ..] = [1u8, 2, 3, 4];
+    let _ = 8;
+    let single @ 1..=9 = 5 else { return };
+
+    match value {
+        Value::Unit => {}
+        Value::Tuple(0, _) => {}
+        Value::Tuple(1..=5, n) | Value::Tuple(n, 1..=5) => {}
+        Value::Struct { field: Some(inner @ 0..=9), .. } => {}
+        Value::Struct { field, other } if other => {}
+        Value::Literal(-1) => {}
+        Value::Literal(i32::MAX) => {}
+        Value::Text("text") => {}
+        Value::Boxed(box inner) => {}
+        Value::Char('a'..='z') => {}
+        Value::Nested(ref nested @ Value::Unit) => {}
+        <Value as Trait>::CONSTANT => {}
+        matches!() => {}
+        _ => {}
+    }
+
+    match slice {
+        [] => {}
+        [one] => {}
+        [first, .., last] => {}
+        [.., tail] => {}
+    }
+
+    if let Some(x) | None = Some(1) {}
+
+    for (index, &item) in slice.iter().enumerate() {}
+
+    let closure = |(a, b): (u8, u8), Point { x, .. }: Point| a + b;
+}
@@ -0,0 +1,39 @@
+//! Types of every kind.
+
+type Never = !;
+type Unit = ();
+type Single = (u8,);
+type Tuple = (u8, String, Vec<u8>);
+type Array = [u8; 32];
+type Computed = [u8; 4 * 8];
+type Slice<'a> = &'a [u8];
+type MutReference<'a> = &'a mut str;
+type ConstPointer = *const u8;
+type MutPointer = *mut u8;
+type BareFn = fn(u8, &str) -> bool;
+type NamedFn = unsafe extern "C" fn(value: u8, ...) -> u8;
+type HigherRanked = for<'a> fn(&'a u8) -> &'a u8;
+type TraitObject = Box<dyn Fn(u8) -> u8 + Send + Sync + 'static>;
+type BareTraitObject<'a> = &'a (dyn std::any::Any + 'a);
+type ImplTrait = fn() -> Box<dyn Iterator<Item = impl Copy>>;
+type Parenthesized = (Box<dyn (Fn())>);
+type Qualified = <Vec<u8> as IntoIterator>::Item;
+type DeepQualified = <<Vec<u8> as IntoIterator>::IntoIter as Iterator>::Item;
+type Generic<T> = std::collections::HashMap<String, Vec<T>>;
+type Macro = type_macro!(u8);
+type Inferred = Vec<_>;
+type ConstArgument = Buffer<16, { 4 + 4 }>;
+type Constrained = Box<dyn Iterator<Item: Copy>>;
+type Global = ::std::string::String;
+
+fn impl_trait(x: impl Into<String>) -> impl Iterator<Item = u8> + Clone {
+    std::iter::empty()
+}
+
+fn higher_ranked<F>(f: F)
+where
+    F: for<'a> Fn(&'a u8) -> &'a u8,
+    for<'b> &'b F: Copy,
+    Vec<u8>: Clone,
+{
+}
@@ -0,0 +1,102 @@
+//! Syntax that is unstable, removed from the language or only accepted by
+//! the parser, which syn still understands. This file is never compiled.
+
+#![feature(box_syntax, generators, try_blocks, type_ascription, decl_macro)]
+
+pub macro m($x:expr) {
+    $x
+}
+
+fn f(x: Box<u8>) -> u8 {
+    let b = box 1;
+    let t: Result<u8, ()> = try { 1 };
+    let a = (x: Box<u8>);
+    let g = || yield 1;
+
+    'a: for i in 0..1 {
+        continue 'a;
+    }
+
+    'b: {
+        break 'b;
+    }
+
+    *b
+}
+
+fn no_body();
+static NO_VALUE: u8;
+
+extern "C" {
+    fn with_body() {}
+}
+
+impl !Sync {}
+
+impl S {
+    type Missing;
+}
+
+trait T {
+    type A
+    where
+        Self: Sized;
+    m!();
+    const fn g();
+}
+
+trait Alias = T where Self: Copy;
+
+type Alias2<T>
+where
+    T: Copy,
+= Vec<T>;
+
+impl T for S {
+    type A<U> = U where U: Copy;
+}
+
+#[repr(C)]
+union U<T>
+where
+    T: Copy,
+{
+    a: T,
+}
+
+enum E {
+    #[default]
+    A,
+}
+
+fn pats(x: u8) {
+    match x {
+        const { 1 } => (),
+        ..=5 => (),
+        _ => (),
+    }
+}
+
+trait Verbatim {
+    pub fn public();
+}
+
+type Star = dyn* Send;
+
+#[allow(unused)]
+trait AttributedAlias = Send;
+
+fn attributed_expressions(x: u8) {
+    #[allow(unused)]
+    box 1;
+    #[allow(unused)]
+    try {};
+    #[allow(unused)]
+    (x: u8);
+    #[allow(unused)]
+    || yield 1;
+    || {
+        #[allow(unused)]
+        yield 1;
+    };
+}
@@ -0,0 +1,92 @@
+//! Checks of custom node kinds and the routing of macro bodies to them.
+
+mod common;
+
+use moisture::*;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::Parser;
+use syn::punctuated::Punctuated;
+use syn::{Expr, Result, Token};
+
+/// Traverse a comma-separated list of expressions.
+fn args(moisture: &Moisture, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    let exprs = Punctuated::<Expr, Token![,]>::parse_terminated.parse2(tokens)?;
+    let mut new_exprs = Vec::<TokenStream>::new();
+
+    for expr in exprs {
+        new_exprs.push(moisture.callback(context, CallbackType::Expr, quote! { #expr })?);
+    }
+
+    Ok(quote! { #(#new_exprs),* })
+}
+
+fn one_to_two(_: &Moisture, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    if tokens.to_string() == "1" {
+        Ok(quote! { 2 })
+    } else {
+        Ok(tokens)
+    }
+}
+
+#[test]
+fn macro_bodies_are_routed() {
+    let mut moisture = Moisture::new();
+    moisture.register_custom("args", args);
+    moisture.register_callback(CallbackType::ExprLit, one_to_two);
+    moisture.route_macro("my :: args", "args");
+
+    let cases = [
+        (quote! { my::args!(1, 1) }, quote! { my::args!(2, 2) }),
+        (quote! { other!(1) }, quote! { other!(1) }),
+        (quote! { args!(1) }, quote! { args!(1) }),
+        (quote! { ::my::args!(1) }, quote! { ::my::args!(1) }),
+    ];
+
+    for (input, expected) in cases {
+        assert_eq!(
+            common::traverse(&moisture, CallbackType::Expr, input),
+            expected.to_string()
+        );
+    }
+
+    moisture.route_macro(" ::my::args", "args");
+
+    assert_eq!(
+        common::traverse(&moisture, CallbackType::Expr, quote! { ::my::args![1] }),
+        quote! { ::my::args![2] }.to_string()
+    );
+}
+
+fn reversed(moisture: &Moisture, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    // the default callback still traverses the body
+    let new_tokens = moisture.custom(context, tokens)?;
+    let exprs = Punctuated::<Expr, Token![,]>::parse_terminated.parse2(new_tokens)?;
+    let exprs = exprs.into_iter().rev();
+
+    Ok(quote! { #(#exprs),* })
+}
+
+#[test]
+fn custom_defaults_stay_available() {
+    let mut moisture = Moisture::new();
+    moisture.register_custom("args", args);
+    moisture.register_callback(CallbackType::Custom("args"), reversed);
+    moisture.register_callback(CallbackType::ExprLit, one_to_two);
+    moisture.route_macro("args", "args");
+
+    assert_eq!(
+        common::traverse(&moisture, CallbackType::Expr, quote! { args!(1, 3) }),
+        quote! { args!(3, 2) }.to_string()
+    );
+
+    let error = moisture
+        .traverse(
+            &Context::new(),
+            CallbackType::Custom("missing"),
+            quote! { 1 },
+        )
+        .unwrap_err();
+
+    assert_eq!(error.kind(), ErrorKind::MissingCallback);
+}
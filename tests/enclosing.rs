@@ -0,0 +1,91 @@
+//! Checks of the enclosing function, `impl` block and modules of a callback.
+
+mod common;
+
+use moisture::*;
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::Result;
+
+fn describe(_: &Moisture, context: &Context, _: TokenStream) -> Result<TokenStream> {
+    let sig = context.signature().map(|sig| sig.ident.to_string());
+    let self_ty = context.self_ty().map(|ty| ty.to_token_stream().to_string());
+    let impl_trait = context
+        .impl_trait()
+        .map(|path| path.to_token_stream().to_string());
+    let generics = context
+        .impl_generics()
+        .map(|generics| generics.params.len());
+    let module_path = context
+        .module_path()
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("::");
+    let description = format!(
+        "{:?} {:?} {:?} {:?} {}",
+        sig, self_ty, impl_trait, generics, module_path
+    );
+
+    Ok(quote! { #description })
+}
+
+fn run(tokens: TokenStream) -> Vec<String> {
+    let output = common::run(CallbackType::File, CallbackType::LitStr, describe, tokens);
+    let file = syn::parse_str::<syn::File>(&output).unwrap();
+    let mut descriptions = Vec::new();
+
+    collect(file.into_token_stream(), &mut descriptions);
+    descriptions
+}
+
+fn collect(tokens: TokenStream, descriptions: &mut Vec<String>) {
+    for tree in tokens {
+        match tree {
+            proc_macro2::TokenTree::Literal(lit) => {
+                let lit = syn::parse2::<syn::LitStr>(lit.into_token_stream()).unwrap();
+                descriptions.push(lit.value());
+            }
+            proc_macro2::TokenTree::Group(group) => collect(group.stream(), descriptions),
+            _ => (),
+        }
+    }
+}
+
+#[test]
+fn enclosing_items_are_tracked() {
+    let descriptions = run(quote! {
+        const A: &str = "";
+
+        mod outer {
+            mod inner {
+                impl<T> fmt::Display for Wrapper<T> {
+                    const B: &'static str = "";
+
+                    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                        let c = || "";
+                        fn helper() -> &'static str { "" }
+                        Ok(())
+                    }
+                }
+            }
+
+            impl Point {
+                async fn new() {
+                    let d = "";
+                }
+            }
+        }
+    });
+
+    assert_eq!(
+        descriptions,
+        [
+            r#"None None None None "#,
+            r#"None Some("Wrapper < T >") Some("fmt :: Display") Some(1) outer::inner"#,
+            r#"Some("fmt") Some("Wrapper < T >") Some("fmt :: Display") Some(1) outer::inner"#,
+            r#"Some("helper") None None None outer::inner"#,
+            r#"Some("new") Some("Point") None Some(0) outer"#,
+        ]
+    );
+}
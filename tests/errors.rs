@@ -0,0 +1,113 @@
+//! Checks of the kinds and callback paths of the [`MoistureError`]s returned
+//! by [`Moisture::traverse`].
+
+// both crates export many names, which must not collide
+use syn::*;
+
+use moisture::*;
+use proc_macro2::TokenStream;
+use quote::quote;
+
+fn reject(_: &Moisture, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    Err(Error::new_spanned(tokens, "calls aren't allowed"))
+}
+
+fn statement(_: &Moisture, _: &Context, _: TokenStream) -> Result<TokenStream> {
+    Ok(quote! { let x = 1; })
+}
+
+fn run(moisture: &Moisture, ty: CallbackType, tokens: TokenStream) -> MoistureError {
+    moisture.traverse(&Context::new(), ty, tokens).unwrap_err()
+}
+
+#[test]
+fn user_errors_carry_their_path() {
+    let mut moisture = Moisture::new();
+    moisture.register_callback(CallbackType::ExprCall, reject);
+
+    let error = run(&moisture, CallbackType::Item, quote! { fn f() { g(); } });
+
+    assert_eq!(error.kind(), ErrorKind::User);
+    assert_eq!(
+        error.path(),
+        &[
+            CallbackType::Item,
+            CallbackType::ItemFn,
+            CallbackType::Block,
+            CallbackType::Stmts,
+            CallbackType::Stmt,
+            CallbackType::Expr,
+            CallbackType::ExprCall,
+        ]
+    );
+
+    let message = error.to_compile_error().to_string();
+    assert!(message.contains("calls aren't allowed"));
+    assert!(message.contains("note: in Item > ItemFn > Block > Stmts > Stmt > Expr > ExprCall"));
+}
+
+#[test]
+fn errors_are_distinguished() {
+    let moisture = Moisture::new();
+
+    let error = run(&moisture, CallbackType::Item, quote! { fn f() -> });
+    assert_eq!(error.kind(), ErrorKind::Parse);
+    assert_eq!(error.path(), &[CallbackType::Item]);
+
+    let error = run(&moisture, CallbackType::Custom("missing"), quote! { a });
+    assert_eq!(error.kind(), ErrorKind::MissingCallback);
+    assert_eq!(error.path(), &[CallbackType::Custom("missing")]);
+
+    let mut moisture = Moisture::new();
+    moisture.register_callback(CallbackType::ExprCall, statement);
+    moisture.set_validation(true);
+
+    let error = run(&moisture, CallbackType::Expr, quote! { f(x) });
+    assert_eq!(error.kind(), ErrorKind::Validation);
+    assert_eq!(error.path(), &[CallbackType::Expr, CallbackType::ExprCall]);
+}
+
+fn parse_call(_: &Moisture, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    let call = parse2::<ExprMethodCall>(tokens)?;
+
+    Ok(quote! { #call })
+}
+
+#[test]
+fn syn_errors_of_callbacks_are_user_errors() {
+    let mut moisture = Moisture::new();
+    moisture.register_callback(CallbackType::ExprCall, parse_call);
+
+    let error = run(&moisture, CallbackType::Expr, quote! { f(x) });
+    assert_eq!(error.kind(), ErrorKind::User);
+    assert_eq!(error.path(), &[CallbackType::Expr, CallbackType::ExprCall]);
+
+    let error = run(&Moisture::new(), CallbackType::Label, quote! { 'a: : });
+    assert_eq!(error.kind(), ErrorKind::Parse);
+    assert_eq!(error.path(), &[CallbackType::Label]);
+}
+
+fn recover(moisture: &Moisture, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    let block = parse2::<ItemFn>(tokens.clone())?.block;
+
+    match moisture.callback(context, CallbackType::Block, quote! { #block }) {
+        Ok(_) => Ok(tokens),
+        Err(_) => Err(Error::new_spanned(tokens, "functions can't make calls")),
+    }
+}
+
+#[test]
+fn recovered_errors_are_forgotten() {
+    let mut moisture = Moisture::new();
+    moisture.register_callback(CallbackType::ExprCall, reject);
+    moisture.register_callback(CallbackType::ItemFn, recover);
+
+    let error = run(&moisture, CallbackType::Item, quote! { fn f() { g(); } });
+
+    assert_eq!(error.kind(), ErrorKind::User);
+    assert_eq!(error.path(), &[CallbackType::Item, CallbackType::ItemFn]);
+    assert_eq!(
+        error.to_string(),
+        "functions can't make calls\nnote: in Item > ItemFn"
+    );
+}
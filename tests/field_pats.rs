@@ -0,0 +1,69 @@
+//! Checks of the field pattern and subpattern callbacks.
+
+mod common;
+
+use moisture::*;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Member, Result};
+
+fn drop_skipped(_: &Moisture, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    match get_field_pat(tokens.clone())?.member {
+        Member::Named(ident) if ident == "skipped" => Ok(TokenStream::new()),
+        Member::Unnamed(_) => Ok(quote! { 0: zero }),
+        _ => Ok(tokens),
+    }
+}
+
+fn bound_subpat(_: &Moisture, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    if tokens.is_empty() {
+        Ok(quote! { @ 0..=9 })
+    } else {
+        Ok(TokenStream::new())
+    }
+}
+
+#[test]
+fn field_patterns_are_visited() {
+    let cases = [
+        (
+            quote! { P { x, skipped: _, y: ref z } },
+            quote! { P { x, y: ref z } },
+        ),
+        (quote! { P { skipped, .. } }, quote! { P { .. } }),
+        (quote! { P { 1: y } }, quote! { P { 0: zero } }),
+    ];
+
+    for (input, expected) in cases {
+        assert_eq!(
+            common::run(
+                CallbackType::Pat,
+                CallbackType::FieldPat,
+                drop_skipped,
+                input
+            ),
+            expected.to_string()
+        );
+    }
+}
+
+#[test]
+fn subpatterns_are_visited() {
+    let cases = [
+        (quote! { n }, quote! { n @ 0..=9 }),
+        (quote! { n @ Some(_) }, quote! { n }),
+        (quote! { (a, ref b) }, quote! { (a @ 0..=9, ref b @ 0..=9) }),
+    ];
+
+    for (input, expected) in cases {
+        assert_eq!(
+            common::run(
+                CallbackType::Pat,
+                CallbackType::PatIdentSubpat,
+                bound_subpat,
+                input
+            ),
+            expected.to_string()
+        );
+    }
+}
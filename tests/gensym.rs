@@ -0,0 +1,68 @@
+//! Checks of the identifiers generated by [`Context::gensym`].
+
+mod common;
+
+use moisture::*;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Result;
+
+fn expr_try(_: &Moisture, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    let tmp = context.gensym("tmp");
+
+    Ok(quote! { { let #tmp = #tokens; #tmp } })
+}
+
+#[test]
+fn identifiers_are_numbered_and_skip_taken_ones() {
+    let mut moisture = Moisture::new();
+    moisture.register_callback(CallbackType::ExprTry, expr_try);
+
+    assert_eq!(
+        common::traverse(&moisture, CallbackType::Expr, quote! { (a?, __tmp_1?, c?) }),
+        quote! {
+            (
+                { let __tmp_0 = a?; __tmp_0 },
+                { let __tmp_2 = __tmp_1?; __tmp_2 },
+                { let __tmp_3 = c?; __tmp_3 }
+            )
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn reused_contexts_start_over() {
+    let mut moisture = Moisture::new();
+    moisture.register_callback(CallbackType::ExprTry, expr_try);
+
+    let context = Context::new();
+
+    assert_eq!(
+        common::traverse_in(&moisture, &context, CallbackType::Expr, quote! { a? }),
+        quote! { { let __tmp_0 = a?; __tmp_0 } }.to_string()
+    );
+    assert_eq!(
+        common::traverse_in(&moisture, &context, CallbackType::Expr, quote! { b? }),
+        quote! { { let __tmp_0 = b?; __tmp_0 } }.to_string()
+    );
+    assert_eq!(
+        common::traverse_in(
+            &moisture,
+            &context,
+            CallbackType::Expr,
+            quote! { (__tmp_0, c?) }
+        ),
+        quote! { (__tmp_0, { let __tmp_1 = c?; __tmp_1 }) }.to_string()
+    );
+}
+
+#[test]
+fn prefixes_are_sanitized() {
+    let context = Context::new();
+
+    assert_eq!(context.gensym("my-tmp").to_string(), "__my_tmp_0");
+    assert_eq!(context.gensym("a b::c").to_string(), "__a_b__c_1");
+    assert_eq!(context.gensym("2nd").to_string(), "__2nd_2");
+    assert_eq!(context.gensym("größe").to_string(), "__größe_3");
+}
@@ -0,0 +1,77 @@
+//! Checks of the roles given to identifiers.
+
+mod common;
+
+use moisture::*;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, ToTokens};
+use syn::ext::IdentExt;
+use syn::parse::Parser;
+use syn::{Ident, Result};
+
+fn tag(_: &Moisture, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    let ident = Ident::parse_any.parse2(tokens)?;
+    let role = context.ident_role().expect("identifiers have a role");
+
+    Ok(format_ident!("{}_{}", format!("{:?}", role), ident).to_token_stream())
+}
+
+fn run(ty: CallbackType, tokens: TokenStream) -> String {
+    common::run(ty, CallbackType::Ident, tag, tokens)
+}
+
+#[test]
+fn declarations_bindings_and_paths() {
+    assert_eq!(
+        run(
+            CallbackType::Item,
+            quote! { fn f<T>(x: T) -> T { let y = g(x); y } }
+        ),
+        quote! { fn Item_f<Item_T>(Binding_x: Path_T) -> Path_T { let Binding_y = Path_g(Path_x); Path_y } }
+            .to_string()
+    );
+    assert_eq!(
+        run(CallbackType::Item, quote! { enum E { A(u8), B { c: u8 } } }),
+        quote! { enum Item_E { Item_A(Path_u8), Item_B { Member_c: Path_u8 } } }.to_string()
+    );
+    assert_eq!(
+        run(CallbackType::Stmt, quote! { let box x = b; }),
+        quote! { let box Binding_x = Path_b; }.to_string()
+    );
+}
+
+#[test]
+fn members_and_methods() {
+    assert_eq!(
+        run(CallbackType::Expr, quote! { a.b.c(d) }),
+        quote! { Path_a.Member_b.Method_c(Path_d) }.to_string()
+    );
+    assert_eq!(
+        run(CallbackType::Expr, quote! { P { x: 1, y } }),
+        quote! { Path_P { Member_x: 1, Member_y: Path_y } }.to_string()
+    );
+    assert_eq!(
+        run(CallbackType::Stmt, quote! { let P { x, y: ref w } = p; }),
+        quote! { let Path_P { Member_x: Binding_x, Member_y: ref Binding_w } = Path_p; }
+            .to_string()
+    );
+}
+
+#[test]
+fn labels_and_macros() {
+    assert_eq!(
+        run(
+            CallbackType::Expr,
+            quote! { 'outer: loop { break 'outer; } }
+        ),
+        quote! { 'Label_outer: loop { break 'Label_outer; } }.to_string()
+    );
+    assert_eq!(
+        run(CallbackType::Expr, quote! { std::println!("{}", a) }),
+        quote! { Path_std::Macro_println!("{}", a) }.to_string()
+    );
+    assert_eq!(
+        run(CallbackType::Item, quote! { macro_rules! m { () => {} } }),
+        quote! { Macro_macro_rules! Macro_m { () => {} } }.to_string()
+    );
+}
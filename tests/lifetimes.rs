@@ -0,0 +1,83 @@
+//! Checks of the lifetime and label callbacks.
+
+mod common;
+
+use moisture::*;
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{parse2, Lifetime, Result};
+
+fn rename(_: &Moisture, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    let lifetime = parse2::<Lifetime>(tokens)?;
+
+    if lifetime.ident == "a" {
+        Ok(quote! { 'z })
+    } else {
+        Ok(lifetime.to_token_stream())
+    }
+}
+
+fn run(ty: CallbackType, callback_ty: CallbackType, tokens: TokenStream) -> String {
+    common::run(ty, callback_ty, rename, tokens)
+}
+
+#[test]
+fn lifetimes_are_visited() {
+    let cases = [
+        (
+            quote! { fn f<'a, 'b: 'a>(x: &'a u8, y: Cow<'a, str>) -> &'b u8 where 'a: 'b {} },
+            quote! { fn f<'z, 'b: 'z>(x: &'z u8, y: Cow<'z, str>) -> &'b u8 where 'z: 'b {} },
+        ),
+        (
+            quote! { impl<'a> S<'a> { fn m(&'a self) -> Box<dyn Fn() + 'a> {} } },
+            quote! { impl<'z> S<'z> { fn m(&'z self) -> Box<dyn Fn() + 'z> {} } },
+        ),
+        (
+            quote! { type F = for<'a> fn(&'a u8); },
+            quote! { type F = for<'z> fn(&'z u8); },
+        ),
+    ];
+
+    for (input, expected) in cases {
+        assert_eq!(
+            run(CallbackType::Item, CallbackType::Lifetime, input),
+            expected.to_string()
+        );
+    }
+}
+
+#[test]
+fn labels_are_visited() {
+    let input = quote! {
+        'a: loop {
+            'b: while x {
+                'c: for i in y { continue 'a; }
+                break 'b;
+            }
+            let v = 'a: { break 'a 1; };
+        }
+    };
+    let expected = quote! {
+        'z: loop {
+            'b: while x {
+                'c: for i in y { continue 'z; }
+                break 'b;
+            }
+            let v = 'z: { break 'z 1; };
+        }
+    };
+
+    assert_eq!(
+        run(CallbackType::Expr, CallbackType::Label, input),
+        expected.to_string()
+    );
+    // labels aren't lifetimes
+    assert_eq!(
+        run(
+            CallbackType::Expr,
+            CallbackType::Lifetime,
+            quote! { 'a: loop { break 'a; } }
+        ),
+        quote! { 'a: loop { break 'a; } }.to_string()
+    );
+}
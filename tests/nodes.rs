@@ -0,0 +1,126 @@
+//! Checks of [`Moisture::transform`] and [`Moisture::rebuild`] on [`Node`]
+//! types.
+
+use moisture::*;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, ToTokens};
+use syn::{parse2, Fields, Generics, ItemFn, Label, Pat, PatType, Result, Type};
+
+fn rename(moisture: &Moisture, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    let ident = parse2::<syn::Ident>(tokens.clone())?;
+
+    if ident == "old" {
+        Ok(quote! { new })
+    } else {
+        moisture.ident(context, tokens)
+    }
+}
+
+fn renaming() -> Moisture {
+    let mut moisture = Moisture::new();
+    moisture.register_callback(CallbackType::Ident, rename);
+
+    moisture
+}
+
+fn assert_transform<T: Node>(moisture: &Moisture, input: TokenStream, expected: TokenStream) {
+    let node = T::parse_node(input).unwrap();
+    let new_node = moisture.transform(&Context::new(), &node).unwrap();
+
+    assert_eq!(new_node.to_token_stream().to_string(), expected.to_string());
+}
+
+#[test]
+fn transform_parses_the_output() {
+    let moisture = renaming();
+
+    assert_transform::<ItemFn>(
+        &moisture,
+        quote! { fn old(old: u8) {} },
+        quote! { fn new(new: u8) {} },
+    );
+    assert_transform::<Pat>(&moisture, quote! { (old, x) }, quote! { (new, x) });
+    assert_transform::<PatType>(&moisture, quote! { old: u8 }, quote! { new: u8 });
+    assert_transform::<Type>(&moisture, quote! { Vec<old> }, quote! { Vec<new> });
+}
+
+#[test]
+fn transform_nodes_without_syn_tokens() {
+    let moisture = renaming();
+
+    assert_transform::<Generics>(&moisture, quote! { <old, T> }, quote! { <new, T> });
+    assert_transform::<Fields>(
+        &moisture,
+        quote! { { old: u8, b: old } },
+        quote! { { new: u8, b: new } },
+    );
+    assert_transform::<Fields>(&moisture, quote! { (old) }, quote! { (new) });
+    assert_transform::<Fields>(&moisture, quote! {}, quote! {});
+    assert_transform::<Label>(&moisture, quote! { 'old: }, quote! { 'new: });
+    assert_transform::<QualifiedPath>(
+        &moisture,
+        quote! { <old as Trait>::old },
+        quote! { <new as Trait>::new },
+    );
+}
+
+#[test]
+fn transform_rejects_another_kind() {
+    let mut moisture = Moisture::new();
+    moisture.register_callback(CallbackType::ItemFn, |_, _, _| Ok(quote! { struct S; }));
+
+    let node = parse2::<ItemFn>(quote! { fn f() {} }).unwrap();
+    let error = moisture
+        .transform(&Context::new(), &node)
+        .map(|_| ())
+        .unwrap_err();
+
+    assert_eq!(error.to_string(), "expected ItemFn object");
+}
+
+fn item_fn(moisture: &Moisture, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    let item_fn = parse2::<ItemFn>(tokens)?;
+    let mut new_fn = moisture.rebuild(context, &item_fn)?;
+    new_fn.sig.ident = format_ident!("{}_fn", new_fn.sig.ident);
+
+    Ok(new_fn.to_token_stream())
+}
+
+#[test]
+fn rebuild_runs_the_default_callback() {
+    let mut moisture = renaming();
+    moisture.register_callback(CallbackType::ItemFn, item_fn);
+
+    let output = moisture
+        .callback(
+            &Context::new(),
+            CallbackType::Item,
+            quote! { fn old() { fn g() {} } },
+        )
+        .unwrap();
+
+    assert_eq!(
+        output.to_string(),
+        quote! { fn new_fn() { fn g_fn() {} } }.to_string()
+    );
+}
+
+#[test]
+fn rebuild_skips_the_registered_callback() {
+    let mut moisture = renaming();
+    moisture.register_callback(CallbackType::Generics, |_, _, _| Ok(quote! { <X> }));
+
+    let generics = parse2::<Generics>(quote! { <old, T> }).unwrap();
+
+    let rebuilt = moisture.rebuild(&Context::new(), &generics).unwrap();
+    assert_eq!(
+        rebuilt.to_token_stream().to_string(),
+        quote! { <new, T> }.to_string()
+    );
+
+    let transformed = moisture.transform(&Context::new(), &generics).unwrap();
+    assert_eq!(
+        transformed.to_token_stream().to_string(),
+        quote! { <X> }.to_string()
+    );
+}
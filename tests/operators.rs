@@ -0,0 +1,76 @@
+//! Checks of the operator callbacks.
+
+use moisture::*;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{BinOp, Result, UnOp};
+
+fn swap_bin_op(_: &Moisture, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    match get_bin_op(tokens.clone())?.1 {
+        BinOp::Add(_) => Ok(quote! { - }),
+        BinOp::AddEq(_) => Ok(quote! { -= }),
+        BinOp::Mul(_) => Ok(quote! { *= }),
+        BinOp::MulEq(_) => Ok(quote! { * }),
+        BinOp::Rem(_) => {
+            let (left, _, right) = get_bin_op(tokens)?;
+            Ok(quote! { #left.rem_euclid(#right) })
+        }
+        _ => Ok(tokens),
+    }
+}
+
+fn swap_un_op(_: &Moisture, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    match get_un_op(tokens.clone())? {
+        (UnOp::Not(_), _) => Ok(quote! { - }),
+        (UnOp::Neg(_), expr) => Ok(quote! { std::ops::Neg::neg(#expr) }),
+        _ => Ok(tokens),
+    }
+}
+
+fn run(tokens: TokenStream) -> MoistureResult<TokenStream> {
+    let mut moisture = Moisture::new();
+    moisture.register_callback(CallbackType::BinOp, swap_bin_op);
+    moisture.register_callback(CallbackType::UnOp, swap_un_op);
+
+    moisture.traverse(&Context::new(), CallbackType::Expr, tokens)
+}
+
+#[test]
+fn operators_are_replaced() {
+    let cases = [
+        (quote! { a + b }, quote! { a - b }),
+        (quote! { a += b }, quote! { a -= b }),
+        (quote! { a % b == c }, quote! { a.rem_euclid(b) == c }),
+        (quote! { !a }, quote! { -a }),
+        (quote! { -a }, quote! { std::ops::Neg::neg(a) }),
+        (quote! { *a }, quote! { *a }),
+    ];
+
+    for (input, expected) in cases {
+        assert_eq!(run(input).unwrap().to_string(), expected.to_string());
+    }
+}
+
+#[test]
+fn assignments_keep_their_kind() {
+    let cases = [
+        (
+            quote! { a *= b },
+            CallbackType::ExprAssignOp,
+            "expected a compound assignment operator",
+        ),
+        (
+            quote! { a * b },
+            CallbackType::ExprBinary,
+            "expected a binary operator",
+        ),
+    ];
+
+    for (input, parent, message) in cases {
+        let error = run(input).unwrap_err();
+
+        assert_eq!(error.kind(), ErrorKind::Validation);
+        assert_eq!(error.path().last(), Some(&parent));
+        assert!(error.to_string().contains(message), "{}", error);
+    }
+}
@@ -0,0 +1,100 @@
+//! Checks of the canonical paths of paths through `use` declarations and item
+//! definitions.
+
+mod common;
+
+use moisture::*;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{ExprPath, Result};
+
+fn canonical(_: &Moisture, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    let path = context.canonical_path(&syn::parse2::<ExprPath>(tokens)?.path);
+
+    Ok(quote! { #path })
+}
+
+fn run(tokens: TokenStream) -> String {
+    common::run(
+        CallbackType::File,
+        CallbackType::ExprPath,
+        canonical,
+        tokens,
+    )
+}
+
+#[test]
+fn paths_are_resolved_through_uses() {
+    let output = run(quote! {
+        use std::collections::{self, HashMap as Map};
+        use self::inner::Thing;
+        extern crate alloc as a;
+
+        fn f(x: u8) {
+            Map::<u8, u8>::new();
+            collections::HashSet::new();
+            Thing::new();
+            a::vec::Vec::new();
+            Vec::new();
+            x;
+            g();
+        }
+
+        fn g() {}
+
+        mod inner {
+            use super::g as h;
+
+            pub struct Thing;
+
+            fn i() {
+                h();
+                Map::new();
+
+                {
+                    use std::rc::Rc as Map;
+                    Map::new();
+                }
+
+                Map::new();
+            }
+        }
+    });
+    let expected = quote! {
+        use std::collections::{self, HashMap as Map};
+        use self::inner::Thing;
+        extern crate alloc as a;
+
+        fn f(x: u8) {
+            std::collections::HashMap::<u8, u8>::new();
+            std::collections::HashSet::new();
+            crate::inner::Thing::new();
+            ::alloc::vec::Vec::new();
+            Vec::new();
+            x;
+            crate::g();
+        }
+
+        fn g() {}
+
+        mod inner {
+            use super::g as h;
+
+            pub struct Thing;
+
+            fn i() {
+                crate::g();
+                Map::new();
+
+                {
+                    use std::rc::Rc as Map;
+                    std::rc::Rc::new();
+                }
+
+                Map::new();
+            }
+        }
+    };
+
+    assert_eq!(output, expected.to_string());
+}
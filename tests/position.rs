@@ -0,0 +1,83 @@
+//! Checks of the positions of nodes within their parents.
+
+mod common;
+
+use moisture::*;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Result;
+
+fn position(_: &Moisture, context: &Context, _: TokenStream) -> Result<TokenStream> {
+    let position = match context.position() {
+        Some(position) => position.to_string(),
+        None => String::from("root"),
+    };
+
+    Ok(quote! { #position })
+}
+
+fn run(tokens: TokenStream) -> String {
+    common::run(CallbackType::Expr, CallbackType::ExprPath, position, tokens)
+}
+
+#[test]
+fn expressions_know_their_position() {
+    let cases = [
+        (
+            quote! { f(a, b) },
+            quote! { "ExprCall.func"("ExprCall.args[0]", "ExprCall.args[1]") },
+        ),
+        (
+            quote! { x.g(y) },
+            quote! { "ExprMethodCall.receiver".g("ExprMethodCall.args[0]") },
+        ),
+        (
+            quote! { if c { t } else { e } },
+            quote! { if "ExprIf.cond" { "Stmt.expr" } else { "Stmt.expr" } },
+        ),
+        (
+            quote! { match s { _ if g => b } },
+            quote! { match "ExprMatch.expr" { _ if "Arm.guard" => "Arm.body" } },
+        ),
+        (
+            quote! { { let v = i; s; } },
+            quote! { { let v = "Local.init"; "Stmt.semi"; } },
+        ),
+        (
+            quote! { l + r },
+            quote! { "ExprBinary.left" + "ExprBinary.right" },
+        ),
+        (quote! { a }, quote! { "root" }),
+    ];
+
+    for (input, expected) in cases {
+        assert_eq!(run(input), expected.to_string());
+    }
+}
+
+fn index(_: &Moisture, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    // the statement is above the expression and its Expr callback
+    match context.position_at(2) {
+        Some(Position {
+            parent: CallbackType::Stmts,
+            field: "stmts",
+            index: Some(index),
+        }) => Ok(quote! { #index }),
+        _ => Ok(tokens),
+    }
+}
+
+#[test]
+fn statements_know_their_index() {
+    let mut moisture = Moisture::new();
+    moisture.register_callback(CallbackType::ExprPath, index);
+
+    let output = moisture
+        .callback(&Context::new(), CallbackType::Block, quote! { { a; b; c } })
+        .unwrap();
+
+    assert_eq!(
+        output.to_string(),
+        quote! { { 0usize; 1usize; 2usize } }.to_string()
+    );
+}
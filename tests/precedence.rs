@@ -0,0 +1,109 @@
+//! Checks that replacement expressions are parenthesized where their
+//! position binds more tightly than they do.
+
+mod common;
+
+use moisture::*;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse2, parse_str, BinOp, Expr, Result};
+
+fn sum(_: &Moisture, _: &Context, _: TokenStream) -> Result<TokenStream> {
+    Ok(quote! { b + c })
+}
+
+fn add(_: &Moisture, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    match get_bin_op(tokens.clone())?.1 {
+        BinOp::Mul(_) => Ok(quote! { + }),
+        _ => Ok(tokens),
+    }
+}
+
+fn run(moisture: &Moisture, tokens: TokenStream) -> Expr {
+    parse_str::<Expr>(&common::traverse(moisture, CallbackType::Expr, tokens)).unwrap()
+}
+
+#[test]
+fn replaced_operands_are_parenthesized() {
+    let mut moisture = Moisture::new();
+    moisture.register_callback(CallbackType::ExprCall, sum);
+
+    let cases = [
+        (quote! { a * f(x) }, quote! { a * (b + c) }),
+        (quote! { f(x) * a }, quote! { (b + c) * a }),
+        (quote! { a - f(x) }, quote! { a - (b + c) }),
+        (quote! { f(x) - a }, quote! { b + c - a }),
+        (quote! { -f(x) }, quote! { -(b + c) }),
+        (quote! { f(x) as u8 }, quote! { (b + c) as u8 }),
+        (quote! { f(x).g() }, quote! { (b + c).g() }),
+        (quote! { f(x).field }, quote! { (b + c).field }),
+        (quote! { f(x)? }, quote! { (b + c)? }),
+        (quote! { &f(x) }, quote! { &(b + c) }),
+        (quote! { box f(x) }, quote! { box (b + c) }),
+        (quote! { [f(x)] }, quote! { [b + c] }),
+    ];
+
+    for (input, expected) in cases {
+        assert_eq!(run(&moisture, input), parse2::<Expr>(expected).unwrap());
+    }
+}
+
+#[test]
+fn replaced_scrutinees_are_parenthesized() {
+    let mut moisture = Moisture::new();
+    moisture.register_callback(CallbackType::ExprCall, |_, _, _| Ok(quote! { a || b }));
+
+    let cases = [
+        (
+            quote! { if let Some(y) = f(x) {} },
+            quote! { if let Some(y) = (a || b) {} },
+        ),
+        (
+            quote! { while let Some(y) = f(x) && y {} },
+            quote! { while let Some(y) = (a || b) && y {} },
+        ),
+    ];
+
+    for (input, expected) in cases {
+        let output = moisture
+            .callback(&Context::new(), CallbackType::Expr, input)
+            .unwrap();
+
+        assert_eq!(output.to_string(), expected.to_string());
+    }
+
+    moisture.register_callback(CallbackType::ExprCall, |_, _, _| Ok(quote! { a == b }));
+
+    let output = moisture
+        .callback(
+            &Context::new(),
+            CallbackType::Expr,
+            quote! { if let true = f(x) {} },
+        )
+        .unwrap();
+
+    assert_eq!(
+        output.to_string(),
+        quote! { if let true = a == b {} }.to_string()
+    );
+}
+
+#[test]
+fn unchanged_operands_are_kept() {
+    let moisture = Moisture::new();
+    let input = quote! { a * (b + c) - -d as u8 };
+
+    assert_eq!(
+        run(&moisture, input.clone()),
+        parse2::<Expr>(input).unwrap()
+    );
+}
+
+#[test]
+fn replaced_operators_keep_their_operands() {
+    let mut moisture = Moisture::new();
+    moisture.register_callback(CallbackType::BinOp, add);
+
+    let output = run(&moisture, quote! { a - b * c });
+    assert_eq!(output, parse2::<Expr>(quote! { a - (b + c) }).unwrap());
+}
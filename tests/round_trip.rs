@@ -0,0 +1,177 @@
+//! Round-trip tests for the default callbacks.
+//!
+//! Every file of the corpus is parsed, run through a [`Moisture`] object with
+//! only default callbacks registered, and parsed again. The two
+//! [`syn::File`] objects must be equal. Validation is enabled, so the output of
+//! every default callback must also parse as its node.
+//!
+//! The corpus consists of the files in `tests/corpus`, including the files
+//! vendored from popular crates in `tests/corpus/vendor`, and the source of this
+//! crate. The ignored `external_corpus_round_trip` test runs over every `.rs`
+//! file in the directory named by the `MOISTURE_CORPUS` environment variable,
+//! such as a checkout of the Rust repository:
+//!
+//! ```text
+//! MOISTURE_CORPUS=/path/to/rust cargo test --test round_trip -- --ignored
+//! ```
+//!
+//! Files [syn] can't parse fail the test like any other file, so a corpus
+//! must only hold syntax [syn] understands.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use moisture::*;
+use proc_macro2::{Group, Punct, Spacing, TokenStream, TokenTree};
+use quote::ToTokens;
+use syn::File;
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let mut entries = match fs::read_dir(dir) {
+        Ok(entries) => entries.filter_map(|entry| entry.ok()).collect::<Vec<_>>(),
+        Err(_) => return,
+    };
+    entries.sort_by_key(|entry| entry.path());
+
+    for entry in entries {
+        let path = entry.path();
+
+        if path.is_dir() {
+            collect_files(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
+            files.push(path);
+        }
+    }
+}
+
+/// Reset the spacing of punctuation that isn't followed by more punctuation.
+///
+/// [syn](syn) keeps the tokens of syntax it doesn't understand verbatim,
+/// including the spacing of their punctuation, which the default callbacks
+/// can't always reproduce. Spacing only has a meaning between two punctuation
+/// characters, so it's erased everywhere else.
+fn normalize(tokens: TokenStream) -> TokenStream {
+    let trees = tokens.into_iter().collect::<Vec<_>>();
+    let mut result = TokenStream::new();
+
+    for (index, tree) in trees.iter().enumerate() {
+        let new_tree = match tree {
+            TokenTree::Group(group) => {
+                let mut new_group = Group::new(group.delimiter(), normalize(group.stream()));
+                new_group.set_span(group.span());
+                TokenTree::Group(new_group)
+            }
+            // the apostrophe of a lifetime has to stay joined to its name
+            TokenTree::Punct(punct) if punct.as_char() != '\'' => {
+                let joined = matches!(
+                    trees.get(index + 1),
+                    Some(TokenTree::Punct(next)) if next.as_char() != '\''
+                );
+                let spacing = if joined {
+                    punct.spacing()
+                } else {
+                    Spacing::Alone
+                };
+                let mut new_punct = Punct::new(punct.as_char(), spacing);
+                new_punct.set_span(punct.span());
+                TokenTree::Punct(new_punct)
+            }
+            _ => tree.clone(),
+        };
+
+        result.extend(Some(new_tree));
+    }
+
+    result
+}
+
+fn parse_normalized(tokens: TokenStream) -> syn::Result<File> {
+    syn::parse2::<File>(normalize(tokens))
+}
+
+fn round_trip(path: &Path) -> Result<(), String> {
+    let source = fs::read_to_string(path).map_err(|e| format!("couldn't read file: {}", e))?;
+    // syn panics on some literals it doesn't know, such as C string literals
+    let file = std::panic::catch_unwind(|| syn::parse_file(&source))
+        .map_err(|_| String::from("syn panicked while parsing the file"))?
+        .map_err(|e| format!("syn can't parse the file: {}", e))?;
+    let tokens = file.to_token_stream();
+    let mut moisture = Moisture::new();
+    moisture.set_validation(true);
+
+    let output = moisture
+        .callback(&Context::new(), CallbackType::File, tokens.clone())
+        .map_err(|e| format!("callback failed: {}", e))?;
+    let before = parse_normalized(tokens).map_err(|e| e.to_string())?;
+    let after = parse_normalized(output.clone())
+        .map_err(|e| format!("output doesn't parse: {}\n{}", e, output))?;
+
+    if before == after {
+        Ok(())
+    } else {
+        Err(format!(
+            "output differs from input\n{}",
+            first_difference(&before, &after)
+        ))
+    }
+}
+
+fn first_difference(before: &File, after: &File) -> String {
+    for (old, new) in before.items.iter().zip(&after.items) {
+        if old != new {
+            return format!(
+                "before: {}\nafter:  {}",
+                old.to_token_stream(),
+                new.to_token_stream()
+            );
+        }
+    }
+
+    format!(
+        "before: {} items, {} attributes\nafter:  {} items, {} attributes",
+        before.items.len(),
+        before.attrs.len(),
+        after.items.len(),
+        after.attrs.len()
+    )
+}
+
+fn run_corpus(dirs: &[PathBuf]) {
+    let mut files = Vec::<PathBuf>::new();
+
+    for dir in dirs {
+        collect_files(dir, &mut files);
+    }
+
+    assert!(!files.is_empty(), "no corpus files found in {:?}", dirs);
+
+    let failures = files
+        .iter()
+        .filter_map(|path| {
+            round_trip(path)
+                .err()
+                .map(|message| format!("{}: {}", path.display(), message))
+        })
+        .collect::<Vec<_>>();
+
+    assert!(
+        failures.is_empty(),
+        "{} of {} files failed to round-trip:\n\n{}",
+        failures.len(),
+        files.len(),
+        failures.join("\n\n")
+    );
+}
+
+#[test]
+fn corpus_round_trip() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    run_corpus(&[root.join("tests").join("corpus"), root.join("src")]);
+}
+
+#[test]
+#[ignore = "needs a corpus named by MOISTURE_CORPUS"]
+fn external_corpus_round_trip() {
+    let dir = std::env::var_os("MOISTURE_CORPUS").expect("MOISTURE_CORPUS isn't set");
+    run_corpus(&[PathBuf::from(dir)]);
+}
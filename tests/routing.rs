@@ -0,0 +1,67 @@
+//! Checks that paths are given to the Path callback wherever they occur.
+
+mod common;
+
+use moisture::*;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Result;
+
+fn rename(moisture: &Moisture, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    if get_path(tokens.clone())?.is_ident("old") {
+        Ok(quote! { new })
+    } else {
+        moisture.path(context, tokens)
+    }
+}
+
+fn run(ty: CallbackType, tokens: TokenStream) -> String {
+    common::run(ty, CallbackType::Path, rename, tokens)
+}
+
+#[test]
+fn attribute_paths_are_paths() {
+    assert_eq!(
+        run(
+            CallbackType::Item,
+            quote! { #[old] struct S { #[old(x)] a: u8 } }
+        ),
+        quote! { #[new] struct S { #[new(x)] a: u8 } }.to_string()
+    );
+}
+
+#[test]
+fn use_trees_are_paths() {
+    let cases = [
+        (quote! { use old; }, quote! { use new; }),
+        (quote! { use old::{a, old}; }, quote! { use new::{a, new}; }),
+        (quote! { use old as b; }, quote! { use new as b; }),
+        (quote! { use ::old::*; }, quote! { use ::new::*; }),
+        (quote! { use a::old; }, quote! { use a::old; }),
+    ];
+
+    for (input, expected) in cases {
+        assert_eq!(run(CallbackType::Item, input), expected.to_string());
+    }
+}
+
+#[test]
+fn turbofish_arguments_are_generic_arguments() {
+    assert_eq!(
+        run(CallbackType::Expr, quote! { x.f::<old, 3, { N }>() }),
+        quote! { x.f::<new, 3, { N }>() }.to_string()
+    );
+
+    let mut moisture = Moisture::new();
+    moisture.register_callback(CallbackType::GenericArgument, |_, _, _| Ok(quote! { u8 }));
+
+    let output = moisture
+        .callback(
+            &Context::new(),
+            CallbackType::Expr,
+            quote! { x.f::<T, 3>() },
+        )
+        .unwrap();
+
+    assert_eq!(output.to_string(), quote! { x.f::<u8, u8>() }.to_string());
+}
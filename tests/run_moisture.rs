@@ -0,0 +1,84 @@
+//! Checks of the forms accepted by [`run_moisture!`].
+
+use moisture::*;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Error, Result};
+
+fn foo_to_bar(_: &Moisture, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    if tokens.to_string() == "\"foo\"" {
+        Ok(quote! { "bar" })
+    } else {
+        Ok(tokens)
+    }
+}
+
+fn root(_: &Moisture, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    match context.get_stack().first() {
+        Some((CallbackType::ItemFn, _)) => Ok(quote! { in_fn }),
+        Some(_) => Ok(tokens),
+        None => Err(Error::new_spanned(tokens, "empty stack")),
+    }
+}
+
+fn reject(_: &Moisture, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    Err(Error::new_spanned(tokens, "rejected"))
+}
+
+struct Macro {
+    moisture: Moisture,
+}
+
+fn make_moisture() -> Moisture {
+    let mut moisture = Moisture::new();
+    moisture.register_callback(CallbackType::LitStr, foo_to_bar);
+
+    moisture
+}
+
+#[test]
+fn arguments_are_expressions() {
+    let state = Macro {
+        moisture: make_moisture(),
+    };
+
+    let output = run_moisture!(
+        state.moisture,
+        CallbackType::from_name("LitStr").unwrap(),
+        quote! { "foo" }
+    );
+    assert_eq!(output.to_string(), quote! { "bar" }.to_string());
+
+    let output = run_moisture!(make_moisture(), CallbackType::Lit, quote! { "foo" });
+    assert_eq!(output.to_string(), quote! { "bar" }.to_string());
+}
+
+#[test]
+fn initial_context_is_used() {
+    let mut moisture = Moisture::new();
+    moisture.register_callback(CallbackType::Ident, root);
+
+    let mut context = Context::new();
+    context.push(CallbackType::ItemFn, quote! { fn f() { x } });
+
+    let output = run_moisture!(moisture, &context, CallbackType::Expr, quote! { x });
+    assert_eq!(output.to_string(), quote! { in_fn }.to_string());
+
+    let output = run_moisture!(moisture, &Context::new(), CallbackType::Expr, quote! { x });
+    assert_eq!(output.to_string(), quote! { x }.to_string());
+}
+
+#[test]
+fn errors_become_compile_errors() {
+    let mut moisture = Moisture::new();
+    moisture.register_callback(CallbackType::Ident, reject);
+
+    let mut context = Context::new();
+    context.push(CallbackType::ItemFn, quote! { fn f() { x } });
+
+    let output = run_moisture!(moisture, &context, CallbackType::Expr, quote! { x }).to_string();
+
+    assert!(output.contains("compile_error"));
+    assert!(output.contains("rejected"));
+    assert!(output.contains("note: in ItemFn > Expr"));
+}
@@ -0,0 +1,126 @@
+//! Checks of the node schema exposed by [`CallbackType`].
+//!
+//! The edges of the schema are compared against the callbacks issued while
+//! traversing the files in `tests/corpus`: every callback must be issued from
+//! a parent the schema has an edge from, and every edge of the schema must be
+//! followed.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use moisture::*;
+use proc_macro2::{Delimiter, Group, TokenStream};
+use quote::{quote, ToTokens};
+use syn::Result;
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+
+        if path.is_dir() {
+            collect_files(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
+            files.push(path);
+        }
+    }
+}
+
+static SEEN: Mutex<Vec<(CallbackType, CallbackType)>> = Mutex::new(Vec::new());
+
+/// Record the edge from the parent of every node, then issue the callback
+/// registered for the node.
+fn record(
+    moisture: &Moisture,
+    context: &Context,
+    ty: CallbackType,
+    tokens: TokenStream,
+) -> Result<TokenStream> {
+    if let Some((parent, _)) = context.peek(1) {
+        SEEN.lock().unwrap().push((parent, ty));
+    }
+
+    moisture.delegate(context, ty, tokens)
+}
+
+#[test]
+fn names_round_trip() {
+    for ty in CallbackType::ALL {
+        assert_eq!(CallbackType::from_name(ty.name()), Some(*ty));
+    }
+
+    assert_eq!(CallbackType::from_name("NotANode"), None);
+    assert_eq!(CallbackType::Custom("route").name(), "route");
+}
+
+#[test]
+fn categories_are_dispatched() {
+    for ty in CallbackType::ALL {
+        if let Some(category) = ty.category() {
+            assert!(category.children().contains(ty), "{:?}", ty);
+        }
+    }
+
+    assert!(CallbackType::ExprCall.is_expr());
+    assert!(CallbackType::Pat.is_pat());
+    assert!(!CallbackType::FieldPat.is_pat());
+    assert_eq!(CallbackType::ItemConst.syn_type(), Some("syn::ItemConst"));
+}
+
+#[test]
+fn traversal_follows_edges() {
+    let mut moisture = Moisture::new();
+    moisture.register_wildcard(record);
+
+    let corpus = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("corpus");
+
+    let mut files = Vec::<PathBuf>::new();
+    collect_files(&corpus, &mut files);
+
+    for path in files {
+        let source = fs::read_to_string(path).unwrap();
+        let file = syn::parse_file(&source).unwrap();
+
+        moisture
+            .callback(&Context::new(), CallbackType::File, file.to_token_stream())
+            .unwrap();
+    }
+
+    // nodes only found in the output of macros or at the root of a traversal
+    let group = Group::new(Delimiter::None, quote! { u8 });
+    let roots = [
+        (CallbackType::DeriveInput, quote! { struct S; }),
+        (CallbackType::Pat, quote! { x: u8 }),
+        (CallbackType::Expr, quote! { #group as u16 }),
+        (CallbackType::Expr, quote! { #[allow(unused)] #group }),
+        (CallbackType::Pat, quote! { #[allow(unused)] A | B }),
+        (CallbackType::Type, quote! { Vec<#group> }),
+    ];
+
+    for (ty, tokens) in roots {
+        moisture.callback(&Context::new(), ty, tokens).unwrap();
+    }
+
+    let edges = CallbackType::edges().into_iter().collect::<HashSet<_>>();
+    let seen = SEEN.lock().unwrap().iter().copied().collect::<HashSet<_>>();
+    let mut missing = seen.difference(&edges).collect::<Vec<_>>();
+    missing.sort_by_key(|(parent, child)| (parent.name(), child.name()));
+
+    assert!(
+        missing.is_empty(),
+        "edges missing from the schema: {:?}",
+        missing
+    );
+
+    let mut unobserved = edges.difference(&seen).collect::<Vec<_>>();
+    unobserved.sort_by_key(|(parent, child)| (parent.name(), child.name()));
+
+    assert!(
+        unobserved.is_empty(),
+        "edges of the schema never followed by the corpus: {:?}",
+        unobserved
+    );
+}
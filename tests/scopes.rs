@@ -0,0 +1,145 @@
+//! Checks of the variables in scope during a traversal.
+
+mod common;
+
+use moisture::*;
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{ExprPath, Result};
+
+fn describe(_: &Moisture, context: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    let path = syn::parse2::<ExprPath>(tokens.clone())?.path;
+    let binding = match path.get_ident().and_then(|ident| context.resolve(ident)) {
+        Some(binding) => binding,
+        None => return Ok(tokens),
+    };
+    let ty = binding
+        .ty
+        .as_ref()
+        .map(|ty| ty.to_token_stream().to_string())
+        .unwrap_or_default();
+    let description = format!(
+        "{} {:?} {} {}",
+        binding.ident, binding.site, binding.mutable, ty
+    );
+
+    Ok(quote! { #description })
+}
+
+fn run(tokens: TokenStream) -> String {
+    common::run(CallbackType::Item, CallbackType::ExprPath, describe, tokens)
+}
+
+#[test]
+fn bindings_are_resolved() {
+    let output = run(quote! {
+        fn f(mut a: u8, (b, c): (u8, u8)) {
+            g(a, b, d);
+            let a = a;
+            let mut d: u16 = d;
+            g(a, d);
+
+            for e in e {
+                g(e);
+            }
+
+            match a {
+                Some(x) | None if x => g(x),
+                _ => g(x),
+            }
+
+            if let Some(y) = y {
+                g(y);
+            } else {
+                g(y);
+            }
+
+            while let Some(z) = z {
+                g(z);
+            }
+
+            if let Some(t) = t && t {
+                g(t);
+            }
+
+            let h = |w: u8, v| g(w, v, a);
+            let Some(u) = u else { return g(u) };
+            g(u);
+        }
+    });
+    let expected = quote! {
+        fn f(mut a: u8, (b, c): (u8, u8)) {
+            g("a Param true u8", "b Param false ", d);
+            let a = "a Param true u8";
+            let mut d: u16 = d;
+            g("a Local false ", "d Local true u16");
+
+            for e in e {
+                g("e ForLoop false ");
+            }
+
+            match "a Local false " {
+                Some(x) | None if "x MatchArm false " => g("x MatchArm false "),
+                _ => g(x),
+            }
+
+            if let Some(y) = y {
+                g("y IfLet false ");
+            } else {
+                g(y);
+            }
+
+            while let Some(z) = z {
+                g("z WhileLet false ");
+            }
+
+            if let Some(t) = t && "t IfLet false " {
+                g("t IfLet false ");
+            }
+
+            let h = |w: u8, v| g("w ClosureParam false u8", "v ClosureParam false ", "a Local false ");
+            let Some(u) = u else { return g(u) };
+            g("u Local false ");
+        }
+    };
+
+    assert_eq!(output, expected.to_string());
+}
+
+#[test]
+fn nested_items_and_blocks_are_boundaries() {
+    let output = run(quote! {
+        fn f(a: u8) {
+            {
+                let b = a;
+            }
+            g(b);
+            fn h() {
+                g(a);
+            }
+            impl S {
+                fn i(self) {
+                    g(self, a);
+                }
+            }
+        }
+    });
+    let expected = quote! {
+        fn f(a: u8) {
+            {
+                let b = "a Param false u8";
+            }
+            g(b);
+            fn h() {
+                g(a);
+            }
+            impl S {
+                fn i(self) {
+                    g("self Param false ", a);
+                }
+            }
+        }
+    };
+
+    assert_eq!(output, expected.to_string());
+}
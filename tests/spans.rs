@@ -0,0 +1,99 @@
+//! Checks of the locations given to tokens by [`Moisture::respan`], which
+//! needs proc-macro2's `span-locations` feature to report locations outside of
+//! a procedural macro.
+
+use std::str::FromStr;
+
+use moisture::*;
+use proc_macro2::{LineColumn, TokenStream, TokenTree};
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{parse2, ExprCall, Result};
+
+fn sum(_: &Moisture, _: &Context, _: TokenStream) -> Result<TokenStream> {
+    Ok(quote! { b + c })
+}
+
+fn respanned_sum(_: &Moisture, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    Ok(Moisture::respan(quote! { b + c }, tokens.span()))
+}
+
+fn respanned_func(_: &Moisture, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    let args = parse2::<ExprCall>(tokens.clone())?.args;
+    let new_func = Moisture::respan(quote! { g }, tokens.span());
+
+    Ok(quote! { #new_func(#args) })
+}
+
+fn starts(tokens: TokenStream) -> Vec<(String, LineColumn)> {
+    tokens
+        .into_iter()
+        .flat_map(|tree| match tree {
+            TokenTree::Group(group) => starts(group.stream()),
+            tree => vec![(tree.to_string(), tree.span().start())],
+        })
+        .collect()
+}
+
+fn at(line: usize, column: usize) -> LineColumn {
+    LineColumn { line, column }
+}
+
+#[test]
+fn respanned_tokens_take_the_original_location() {
+    let mut moisture = Moisture::new();
+    moisture.register_callback(CallbackType::ExprCall, respanned_sum);
+
+    let tokens = TokenStream::from_str("a *\n    f(x)").unwrap();
+    let output = moisture
+        .callback(&Context::new(), CallbackType::Expr, tokens)
+        .unwrap();
+
+    assert_eq!(
+        starts(output),
+        [
+            ("a".to_string(), at(1, 0)),
+            ("*".to_string(), at(1, 2)),
+            ("b".to_string(), at(2, 4)),
+            ("+".to_string(), at(2, 4)),
+            ("c".to_string(), at(2, 4)),
+        ]
+    );
+}
+
+#[test]
+fn interpolated_input_keeps_its_location() {
+    let mut moisture = Moisture::new();
+    moisture.register_callback(CallbackType::ExprCall, respanned_func);
+
+    let tokens = TokenStream::from_str("a *\n    f(\n        x)").unwrap();
+    let output = moisture
+        .callback(&Context::new(), CallbackType::Expr, tokens)
+        .unwrap();
+
+    assert_eq!(
+        starts(output),
+        [
+            ("a".to_string(), at(1, 0)),
+            ("*".to_string(), at(1, 2)),
+            ("g".to_string(), at(2, 4)),
+            ("x".to_string(), at(3, 8)),
+        ]
+    );
+}
+
+#[test]
+fn replaced_tokens_keep_the_call_site_location() {
+    let mut moisture = Moisture::new();
+    moisture.register_callback(CallbackType::ExprCall, sum);
+
+    let tokens = TokenStream::from_str("a *\n    f(x)").unwrap();
+    let output = moisture
+        .callback(&Context::new(), CallbackType::Expr, tokens)
+        .unwrap();
+    let call_site = proc_macro2::Span::call_site().start();
+
+    for (token, start) in starts(output).into_iter().skip(2) {
+        assert_eq!(start, call_site, "{}", token);
+    }
+}
@@ -0,0 +1,115 @@
+//! Checks of the callbacks issued for syntax syn only knows as verbatim
+//! tokens.
+
+use std::cell::RefCell;
+
+use moisture::*;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Result;
+
+thread_local! {
+    static EDGES: RefCell<Vec<(CallbackType, CallbackType)>> = const { RefCell::new(Vec::new()) };
+}
+
+fn record(
+    moisture: &Moisture,
+    context: &Context,
+    ty: CallbackType,
+    tokens: TokenStream,
+) -> Result<TokenStream> {
+    if let Some((parent, _)) = context.peek(1) {
+        EDGES.with(|edges| edges.borrow_mut().push((parent, ty)));
+    }
+
+    moisture.delegate(context, ty, tokens)
+}
+
+/// Traverse *tokens* and check that every edge of *expected* was followed.
+fn assert_edges(ty: CallbackType, tokens: TokenStream, expected: &[(CallbackType, CallbackType)]) {
+    let mut moisture = Moisture::new();
+    moisture.register_wildcard(record);
+    EDGES.with(|edges| edges.borrow_mut().clear());
+
+    let output = moisture
+        .callback(&Context::new(), ty, tokens.clone())
+        .unwrap();
+    assert_eq!(output.to_string(), tokens.to_string());
+
+    EDGES.with(|edges| {
+        let edges = edges.borrow();
+
+        for edge in expected {
+            assert!(edges.contains(edge), "{:?} not in {:?}", edge, edges);
+        }
+    });
+}
+
+#[test]
+fn let_else() {
+    assert_edges(
+        CallbackType::Stmt,
+        quote! { let Some(x) = opt else { return; }; },
+        &[
+            (CallbackType::Stmt, CallbackType::Local),
+            (CallbackType::Local, CallbackType::Pat),
+            (CallbackType::Local, CallbackType::Expr),
+            (CallbackType::Local, CallbackType::LocalElse),
+            (CallbackType::LocalElse, CallbackType::Block),
+        ],
+    );
+}
+
+#[test]
+fn let_chains() {
+    assert_edges(
+        CallbackType::Expr,
+        quote! { if let Some(x) = a && x > 1 {} },
+        &[
+            (CallbackType::ExprIf, CallbackType::LetChain),
+            (CallbackType::LetChain, CallbackType::Expr),
+            (CallbackType::Expr, CallbackType::ExprLet),
+            (CallbackType::Expr, CallbackType::ExprBinary),
+        ],
+    );
+    assert_edges(
+        CallbackType::Expr,
+        quote! { while let Some(x) = a && let Ok(y) = x {} },
+        &[(CallbackType::ExprWhile, CallbackType::LetChain)],
+    );
+}
+
+#[test]
+fn inline_const_and_infer() {
+    assert_edges(
+        CallbackType::Expr,
+        quote! { const { 1 + 2 } },
+        &[
+            (CallbackType::Expr, CallbackType::ExprConst),
+            (CallbackType::ExprConst, CallbackType::Block),
+        ],
+    );
+    assert_edges(
+        CallbackType::Expr,
+        quote! { _ = f() },
+        &[
+            (CallbackType::ExprAssign, CallbackType::Expr),
+            (CallbackType::Expr, CallbackType::ExprInfer),
+        ],
+    );
+}
+
+#[test]
+fn closure_binders() {
+    assert_edges(
+        CallbackType::Expr,
+        quote! { for<'a> |x: &'a u8| *x },
+        &[
+            (CallbackType::Expr, CallbackType::ExprClosure),
+            (CallbackType::ExprClosure, CallbackType::BoundLifetimes),
+            (CallbackType::BoundLifetimes, CallbackType::Lifetime),
+            (CallbackType::ExprClosure, CallbackType::PatType),
+            (CallbackType::ExprClosure, CallbackType::Expr),
+        ],
+    );
+}
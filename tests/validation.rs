@@ -0,0 +1,113 @@
+//! Checks of the output accepted by [`Moisture::set_validation`].
+
+use moisture::*;
+use proc_macro2::TokenStream;
+use quote::quote;
+
+fn validating(ty: CallbackType, callback: Callback) -> Moisture {
+    let mut moisture = Moisture::new();
+    moisture.register_callback(ty, callback);
+    moisture.set_validation(true);
+
+    moisture
+}
+
+fn run(moisture: &Moisture, ty: CallbackType, tokens: TokenStream) -> MoistureResult<String> {
+    moisture
+        .traverse(&Context::new(), ty, tokens)
+        .map(|output| output.to_string())
+}
+
+#[test]
+fn kinds_are_replaced_by_their_category() {
+    let moisture = validating(CallbackType::ExprCall, |_, _, _| Ok(quote! { b + c }));
+    assert_eq!(
+        run(&moisture, CallbackType::Expr, quote! { a * f(x) }).unwrap(),
+        quote! { a * (b + c) }.to_string()
+    );
+
+    let moisture = validating(CallbackType::PatIdent, |_, _, _| Ok(quote! { (_, ..) }));
+    assert_eq!(
+        run(&moisture, CallbackType::Pat, quote! { Some(x) }).unwrap(),
+        quote! { Some((_, ..)) }.to_string()
+    );
+
+    let moisture = validating(CallbackType::TypePath, |_, _, _| Ok(quote! { [u8; 4] }));
+    assert_eq!(
+        run(&moisture, CallbackType::Type, quote! { &T }).unwrap(),
+        quote! { &[u8; 4] }.to_string()
+    );
+}
+
+#[test]
+fn items_are_replaced_by_any_number_of_items() {
+    let moisture = validating(CallbackType::ItemFn, |_, _, tokens| {
+        Ok(quote! { #tokens fn g() {} })
+    });
+    assert_eq!(
+        run(&moisture, CallbackType::File, quote! { fn f() {} }).unwrap(),
+        quote! { fn f() {} fn g() {} }.to_string()
+    );
+
+    let moisture = validating(CallbackType::ImplItemMethod, |_, _, tokens| {
+        Ok(quote! { #tokens const N: u8 = 1; })
+    });
+    assert_eq!(
+        run(
+            &moisture,
+            CallbackType::Item,
+            quote! { impl S { fn f() {} } }
+        )
+        .unwrap(),
+        quote! { impl S { fn f() {} const N: u8 = 1; } }.to_string()
+    );
+
+    let moisture = validating(CallbackType::ItemFn, |_, _, _| Ok(quote! { a + b }));
+    let error = run(&moisture, CallbackType::File, quote! { fn f() {} }).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Validation);
+}
+
+#[test]
+fn statements_are_replaced_by_any_number_of_statements() {
+    let moisture = validating(CallbackType::Stmt, |_, _, tokens| {
+        Ok(quote! { log(); #tokens })
+    });
+    assert_eq!(
+        run(&moisture, CallbackType::Block, quote! { { a; b } }).unwrap(),
+        quote! { { log(); a; log(); b } }.to_string()
+    );
+}
+
+#[test]
+fn nodes_without_syn_types_are_checked() {
+    let cases: [(CallbackType, Callback, TokenStream); 4] = [
+        (
+            CallbackType::LetChain,
+            |_, _, _| Ok(quote! { let }),
+            quote! { if let Some(x) = a && x {} },
+        ),
+        (
+            CallbackType::LocalElse,
+            |_, _, _| Ok(quote! { return; }),
+            quote! { { let Some(x) = a else { return }; } },
+        ),
+        (
+            CallbackType::PatIdentSubpat,
+            |_, _, _| Ok(quote! { 1..=5 }),
+            quote! { { let n @ 1 = a; } },
+        ),
+        (
+            CallbackType::ExprInfer,
+            |_, _, _| Ok(quote! { let }),
+            quote! { { _ = f(); } },
+        ),
+    ];
+
+    for (ty, callback, tokens) in cases {
+        let moisture = validating(ty, callback);
+        let error = run(&moisture, CallbackType::Expr, tokens).unwrap_err();
+
+        assert_eq!(error.kind(), ErrorKind::Validation, "{:?}", ty);
+        assert_eq!(error.path().last(), Some(&ty));
+    }
+}
@@ -0,0 +1,79 @@
+//! Checks of the visibility callback.
+
+mod common;
+
+use moisture::*;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse2, Result, Visibility};
+
+fn restrict(_: &Moisture, _: &Context, tokens: TokenStream) -> Result<TokenStream> {
+    match parse2::<Visibility>(tokens.clone())? {
+        Visibility::Inherited => Ok(quote! { pub(crate) }),
+        Visibility::Public(_) => Ok(quote! { pub(super) }),
+        _ => Ok(tokens),
+    }
+}
+
+fn run(tokens: TokenStream) -> String {
+    common::run(
+        CallbackType::Item,
+        CallbackType::Visibility,
+        restrict,
+        tokens,
+    )
+}
+
+#[test]
+fn visibilities_are_visited() {
+    let cases = [
+        (quote! { fn f() {} }, quote! { pub(crate) fn f() {} }),
+        (
+            quote! { pub struct S(u8, pub u8); },
+            quote! { pub(super) struct S(pub(crate) u8, pub(super) u8); },
+        ),
+        (
+            quote! { pub(in a::b) use c::d; },
+            quote! { pub(in a::b) use c::d; },
+        ),
+        (
+            quote! { impl S { pub fn f() {} const C: u8 = 0; } },
+            quote! { impl S { pub(super) fn f() {} pub(crate) const C: u8 = 0; } },
+        ),
+        (
+            quote! { extern "C" { pub fn f(); static S: u8; } },
+            quote! { extern "C" { pub(super) fn f(); pub(crate) static S: u8; } },
+        ),
+    ];
+
+    for (input, expected) in cases {
+        assert_eq!(run(input), expected.to_string());
+    }
+}
+
+fn remove(_: &Moisture, context: &Context, _: TokenStream) -> Result<TokenStream> {
+    // the visibility of a field is removed, the rest is made private
+    match context.peek(1) {
+        Some((CallbackType::Field, _)) => Ok(TokenStream::new()),
+        _ => Ok(quote! { pub(self) }),
+    }
+}
+
+#[test]
+fn inherited_visibility_is_empty() {
+    let mut moisture = Moisture::new();
+    moisture.register_callback(CallbackType::Visibility, remove);
+
+    let output = moisture
+        .callback(
+            &Context::new(),
+            CallbackType::Item,
+            quote! { pub struct S { pub a: u8, b: u8 } },
+        )
+        .unwrap();
+
+    assert_eq!(
+        output.to_string(),
+        quote! { pub(self) struct S { a: u8, b: u8 } }.to_string()
+    );
+}
@@ -0,0 +1,132 @@
+//! Checks of the catch-all callback registered with
+//! [`Moisture::register_wildcard`].
+
+mod common;
+
+use std::cell::RefCell;
+
+use moisture::*;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Error, Result};
+
+thread_local! {
+    static ISSUED: RefCell<Vec<CallbackType>> = const { RefCell::new(Vec::new()) };
+}
+
+fn record(
+    moisture: &Moisture,
+    context: &Context,
+    ty: CallbackType,
+    tokens: TokenStream,
+) -> Result<TokenStream> {
+    let (top, top_tokens) = context.peek(0).unwrap();
+    assert_eq!(top, ty);
+    assert_eq!(top_tokens.to_string(), tokens.to_string());
+
+    ISSUED.with(|issued| issued.borrow_mut().push(ty));
+
+    moisture.delegate(context, ty, tokens)
+}
+
+fn negate_literals(
+    moisture: &Moisture,
+    context: &Context,
+    ty: CallbackType,
+    tokens: TokenStream,
+) -> Result<TokenStream> {
+    match ty {
+        CallbackType::LitInt => Ok(quote! { 2 }),
+        CallbackType::ExprLit => {
+            let literal = moisture.delegate(context, ty, tokens)?;
+
+            Ok(quote! { -#literal })
+        }
+        _ => moisture.delegate(context, ty, tokens),
+    }
+}
+
+#[test]
+fn wildcard_is_issued_for_every_node() {
+    let mut moisture = Moisture::new();
+    moisture.register_wildcard(record);
+    ISSUED.with(|issued| issued.borrow_mut().clear());
+
+    let tokens = quote! { a + 1 };
+    assert_eq!(
+        common::traverse(&moisture, CallbackType::Expr, tokens.clone()),
+        tokens.to_string()
+    );
+
+    ISSUED.with(|issued| {
+        let issued = issued.borrow();
+
+        assert_eq!(issued.first(), Some(&CallbackType::Expr));
+
+        for ty in [
+            CallbackType::ExprBinary,
+            CallbackType::ExprPath,
+            CallbackType::Path,
+            CallbackType::BinOp,
+            CallbackType::ExprLit,
+            CallbackType::LitInt,
+        ] {
+            assert!(issued.contains(&ty), "{:?} not in {:?}", ty, issued);
+        }
+    });
+}
+
+#[test]
+fn wildcard_delegates_and_replaces() {
+    let mut moisture = Moisture::new();
+    moisture.register_wildcard(negate_literals);
+
+    assert_eq!(
+        common::traverse(&moisture, CallbackType::Expr, quote! { f(a, 1) }),
+        quote! { f(a, -2) }.to_string()
+    );
+}
+
+#[test]
+fn wildcard_replaces_registered_callbacks() {
+    let mut moisture = Moisture::new();
+    moisture.register_callback(CallbackType::LitInt, |_, _, _| Ok(quote! { 3 }));
+
+    moisture.register_wildcard(|_, _, ty, tokens| match ty {
+        CallbackType::Ident => Ok(quote! { b }),
+        _ => Ok(tokens),
+    });
+    assert_eq!(
+        common::traverse(&moisture, CallbackType::Expr, quote! { a + 1 }),
+        quote! { a + 1 }.to_string()
+    );
+
+    moisture.register_wildcard(negate_literals);
+    assert_eq!(
+        common::traverse(&moisture, CallbackType::Expr, quote! { a + 1 }),
+        quote! { a + -2 }.to_string()
+    );
+
+    moisture.clear_wildcard();
+    assert_eq!(
+        common::traverse(&moisture, CallbackType::Expr, quote! { a + 1 }),
+        quote! { a + 3 }.to_string()
+    );
+}
+
+#[test]
+fn wildcard_errors_carry_the_path() {
+    let mut moisture = Moisture::new();
+    moisture.register_wildcard(|moisture, context, ty, tokens| match ty {
+        CallbackType::LitInt => Err(Error::new_spanned(tokens, "no literals")),
+        _ => moisture.delegate(context, ty, tokens),
+    });
+
+    let error = moisture
+        .traverse(&Context::new(), CallbackType::Expr, quote! { a + 1 })
+        .unwrap_err();
+
+    assert_eq!(error.kind(), ErrorKind::User);
+    assert_eq!(error.path().first(), Some(&CallbackType::Expr));
+    assert_eq!(error.path().last(), Some(&CallbackType::LitInt));
+}